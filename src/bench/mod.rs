@@ -0,0 +1,422 @@
+use crate::core::player::{scale_mode_for_viewport, with_default_scaler};
+use crate::core::render_budget::{FrameBudgetPolicy, RenderQuality};
+use crate::core::viewport::{ViewportLayout, ViewportMode};
+use crate::decoder::{open_source, RenderTarget, Scaler, SourceOptions};
+use crate::renderer::cell::CellData;
+use crate::renderer::{
+    ActiveRenderBackend, DisplayManager, DisplayMode, FrameProcessor, HeadlessTerminal, MonoMode,
+    MonoRenderer,
+};
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub video_path: PathBuf,
+    pub terminal_cols: u16,
+    pub terminal_rows: u16,
+    /// Stop after this many frames; sources may end earlier
+    pub max_frames: u64,
+    pub source_options: SourceOptions,
+    pub display_mode: DisplayMode,
+    pub viewport_mode: ViewportMode,
+    pub quality: RenderQuality,
+    pub mono: MonoMode,
+    /// Run once per scaler and compare them
+    pub compare_scalers: bool,
+    pub json: bool,
+}
+
+/// Per-frame samples of one pipeline stage
+#[derive(Debug, Clone, Default)]
+struct StageSamples(Vec<Duration>);
+
+impl StageSamples {
+    fn push(&mut self, value: Duration) {
+        self.0.push(value);
+    }
+
+    fn summary(&self) -> StageSummary {
+        let mut sorted = self.0.clone();
+        sorted.sort_unstable();
+        let total: Duration = sorted.iter().sum();
+        StageSummary {
+            p50_ms: millis(percentile(&sorted, 50.0)),
+            p95_ms: millis(percentile(&sorted, 95.0)),
+            p99_ms: millis(percentile(&sorted, 99.0)),
+            mean_ms: millis(total.checked_div(sorted.len() as u32).unwrap_or_default()),
+            max_ms: millis(sorted.last().copied().unwrap_or_default()),
+            total_ms: millis(total),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StageSummary {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StageReport {
+    pub decode: StageSummary,
+    pub resize: StageSummary,
+    pub letterbox: StageSummary,
+    pub filter: StageSummary,
+    pub cells: StageSummary,
+    pub encode: StageSummary,
+    pub write: StageSummary,
+    /// Whole frame, decode through write
+    pub frame: StageSummary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ByteSummary {
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub mean: u64,
+    pub max: u64,
+    pub total: u64,
+}
+
+/// What `gascii bench --json` prints; field names are kept stable so runs
+/// from different releases can be compared.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub version: &'static str,
+    pub source: String,
+    pub mode: &'static str,
+    pub scaler: &'static str,
+    /// Whether 1-bit rendering was on for the last frame
+    pub mono: bool,
+    pub terminal_cols: u16,
+    pub terminal_rows: u16,
+    pub pixel_width: u32,
+    pub pixel_height: u32,
+    pub frames: u64,
+    pub elapsed_ms: f64,
+    /// Frames per second the pipeline sustains with no pacing
+    pub achievable_fps: f64,
+    pub stages: StageReport,
+    pub bytes_per_frame: ByteSummary,
+}
+
+pub fn run(config: BenchConfig) -> Result<()> {
+    if config.compare_scalers {
+        return compare_scalers(&config);
+    }
+    let report = measure(&config)?;
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    crate::utils::logger::info(&format!(
+        "bench completed: source={} frames={} fps={:.1} p95_frame={:.2}ms bytes_p50={}",
+        report.source,
+        report.frames,
+        report.achievable_fps,
+        report.stages.frame.p95_ms,
+        report.bytes_per_frame.p50
+    ));
+    Ok(())
+}
+
+/// The same run with every scaler, one line (or JSON report) each.
+fn compare_scalers(config: &BenchConfig) -> Result<()> {
+    let mut reports = Vec::new();
+    for scaler in Scaler::ALL {
+        let mut config = config.clone();
+        config.source_options.scaler = Some(scaler);
+        reports.push(measure(&config)?);
+    }
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:>10} {:>10} {:>10} {:>9} {:>9}",
+        "scaler", "resize p50", "resize p95", "frame p50", "fps", "bytes p50"
+    );
+    for report in &reports {
+        println!(
+            "{:<12} {:>10.3} {:>10.3} {:>10.3} {:>9.1} {:>9}",
+            report.scaler,
+            report.stages.resize.p50_ms,
+            report.stages.resize.p95_ms,
+            report.stages.frame.p50_ms,
+            report.achievable_fps,
+            report.bytes_per_frame.p50
+        );
+    }
+    Ok(())
+}
+
+/// Runs the playback pipeline as fast as it will go against a headless
+/// display, timing every stage of every frame.
+pub fn measure(config: &BenchConfig) -> Result<BenchReport> {
+    let target = Arc::new(RwLock::new(RenderTarget::new(1, 2)));
+    let source_options =
+        with_default_scaler(&config.source_options, config.display_mode, config.mono)?;
+    let scaler = source_options.scaler.unwrap_or_default();
+    let mut decoder = open_source(
+        &config.video_path,
+        target.clone(),
+        scale_mode_for_viewport(config.viewport_mode),
+        source_options,
+    )?;
+
+    let mut display = DisplayManager::headless(
+        config.display_mode,
+        ActiveRenderBackend::for_mode(config.display_mode),
+        &HeadlessTerminal {
+            cols: config.terminal_cols,
+            rows: config.terminal_rows,
+            output: None,
+        },
+    )?;
+    let backend = display.active_backend();
+    let (term_cols, term_rows) = display.terminal_size_chars()?;
+    let layout = ViewportLayout::calculate(
+        term_cols,
+        term_rows,
+        config.viewport_mode,
+        None,
+        None,
+        FrameBudgetPolicy::for_backend(config.display_mode, backend, config.quality),
+        decoder.source_aspect_ratio(),
+        DisplayManager::render_pixel_aspect_correction(backend),
+        0,
+    );
+    {
+        let mut guard = target
+            .write()
+            .map_err(|_| anyhow!("render target lock poisoned"))?;
+        *guard = RenderTarget::new(layout.pixel_width, layout.pixel_height);
+    }
+
+    let processor = backend
+        .requires_cell_buffer()
+        .then(|| FrameProcessor::new(layout.pixel_width as usize, layout.pixel_height as usize));
+    let mut cells =
+        vec![CellData::default(); layout.pixel_width as usize * (layout.pixel_height as usize / 2)];
+    let mut buffer = Vec::new();
+    let viewport = layout.as_render_viewport();
+    let mut mono = MonoRenderer::for_backend(config.mono, backend);
+
+    let mut decode = StageSamples::default();
+    let mut resize = StageSamples::default();
+    let mut letterbox = StageSamples::default();
+    let mut filter = StageSamples::default();
+    let mut cell_stage = StageSamples::default();
+    let mut encode = StageSamples::default();
+    let mut write = StageSamples::default();
+    let mut frame_total = StageSamples::default();
+    let mut bytes = Vec::new();
+
+    let started_at = Instant::now();
+    while (bytes.len() as u64) < config.max_frames {
+        let frame_started = Instant::now();
+        let Some(frame) = decoder.read_frame_into(&mut buffer)? else {
+            break;
+        };
+        let read_time = frame_started.elapsed();
+        let scale_time =
+            frame.scale.resize_time + frame.scale.letterbox_time + frame.scale.filter_time;
+        decode.push(read_time.saturating_sub(scale_time));
+        resize.push(frame.scale.resize_time);
+        letterbox.push(frame.scale.letterbox_time);
+        filter.push(frame.scale.filter_time);
+
+        let cells_started = Instant::now();
+        let is_mono = mono.prepare(
+            &mut buffer,
+            layout.pixel_width as usize,
+            layout.pixel_height as usize,
+        );
+        let rgb_cells = match &processor {
+            Some(_) if is_mono => {
+                mono.cells_into(&mut cells);
+                Some(cells.as_slice())
+            }
+            Some(processor) => {
+                processor.process_frame_into(&buffer, &mut cells);
+                Some(cells.as_slice())
+            }
+            None => None,
+        };
+        cell_stage.push(cells_started.elapsed());
+
+        let before = display.output_stats();
+        display.render(&buffer, rgb_cells, viewport)?;
+        let after = display.output_stats();
+        encode.push(after.encode_time - before.encode_time);
+        write.push(after.write_time - before.write_time);
+        bytes.push(after.bytes - before.bytes);
+        frame_total.push(frame_started.elapsed());
+    }
+    let elapsed = started_at.elapsed();
+
+    if bytes.is_empty() {
+        bail!("{} produced no frames", config.video_path.display());
+    }
+
+    let frames = bytes.len() as u64;
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION"),
+        source: config.video_path.display().to_string(),
+        mode: match backend {
+            ActiveRenderBackend::AnsiRgb => "rgb",
+            ActiveRenderBackend::AnsiAscii => "ascii",
+        },
+        scaler: scaler.label(),
+        mono: mono.is_active(),
+        terminal_cols: term_cols,
+        terminal_rows: term_rows,
+        pixel_width: layout.pixel_width,
+        pixel_height: layout.pixel_height,
+        frames,
+        elapsed_ms: millis(elapsed),
+        achievable_fps: frames as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        stages: StageReport {
+            decode: decode.summary(),
+            resize: resize.summary(),
+            letterbox: letterbox.summary(),
+            filter: filter.summary(),
+            cells: cell_stage.summary(),
+            encode: encode.summary(),
+            write: write.summary(),
+            frame: frame_total.summary(),
+        },
+        bytes_per_frame: byte_summary(bytes),
+    })
+}
+
+fn print_report(report: &BenchReport) {
+    println!(
+        "{} ({}{}, {}, {}x{} cells, {}x{} px)",
+        report.source,
+        report.mode,
+        if report.mono { " 1-bit" } else { "" },
+        report.scaler,
+        report.terminal_cols,
+        report.terminal_rows,
+        report.pixel_width,
+        report.pixel_height
+    );
+    println!(
+        "프레임: {}  시간: {:.1}ms  처리 가능 FPS: {:.1}",
+        report.frames, report.elapsed_ms, report.achievable_fps
+    );
+    println!(
+        "{:<10} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "stage", "p50 ms", "p95 ms", "p99 ms", "mean ms", "max ms"
+    );
+    let stages = &report.stages;
+    for (name, stage) in [
+        ("decode", stages.decode),
+        ("resize", stages.resize),
+        ("letterbox", stages.letterbox),
+        ("filter", stages.filter),
+        ("cells", stages.cells),
+        ("encode", stages.encode),
+        ("write", stages.write),
+        ("frame", stages.frame),
+    ] {
+        println!(
+            "{:<10} {:>9.3} {:>9.3} {:>9.3} {:>9.3} {:>9.3}",
+            name, stage.p50_ms, stage.p95_ms, stage.p99_ms, stage.mean_ms, stage.max_ms
+        );
+    }
+    let bytes = report.bytes_per_frame;
+    println!(
+        "bytes/frame: p50={} p95={} p99={} mean={} max={}",
+        bytes.p50, bytes.p95, bytes.p99, bytes.mean, bytes.max
+    );
+}
+
+fn byte_summary(mut samples: Vec<u64>) -> ByteSummary {
+    samples.sort_unstable();
+    let total: u64 = samples.iter().sum();
+    ByteSummary {
+        p50: percentile(&samples, 50.0),
+        p95: percentile(&samples, 95.0),
+        p99: percentile(&samples, 99.0),
+        mean: total.checked_div(samples.len() as u64).unwrap_or(0),
+        max: samples.last().copied().unwrap_or(0),
+        total,
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile<T: Copy + Default>(sorted: &[T], percent: f64) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50.0), 50);
+        assert_eq!(percentile(&samples, 95.0), 95);
+        assert_eq!(percentile(&samples, 99.0), 99);
+        assert_eq!(percentile(&[7u64], 99.0), 7);
+        assert_eq!(percentile::<u64>(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn pattern_bench_reports_every_stage_and_bytes() {
+        let report = measure(&BenchConfig {
+            video_path: PathBuf::from("pattern:counter,size=64x36,fps=30,duration=0"),
+            terminal_cols: 40,
+            terminal_rows: 12,
+            max_frames: 5,
+            source_options: SourceOptions::default(),
+            display_mode: DisplayMode::Rgb,
+            viewport_mode: ViewportMode::Fullscreen,
+            quality: RenderQuality::Full,
+            mono: MonoMode::Off,
+            compare_scalers: false,
+            json: true,
+        })
+        .unwrap();
+
+        assert_eq!(report.frames, 5);
+        assert_eq!((report.terminal_cols, report.terminal_rows), (40, 12));
+        assert!(report.bytes_per_frame.max > 0);
+        assert!(report.achievable_fps > 0.0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        for stage in [
+            "decode",
+            "resize",
+            "letterbox",
+            "cells",
+            "encode",
+            "write",
+            "frame",
+        ] {
+            assert!(json["stages"][stage]["p99_ms"].is_number(), "{}", stage);
+        }
+    }
+}
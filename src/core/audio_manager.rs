@@ -114,6 +114,42 @@ impl AudioManager {
         Ok(())
     }
 
+    pub fn pause(&self) -> Result<()> {
+        match self.active_backend {
+            Some(AudioBackendKind::Rodio) => {
+                if let Some(rodio) = &self.rodio {
+                    rodio.set_paused(true)?;
+                }
+            }
+            #[cfg(windows)]
+            Some(AudioBackendKind::Native) => {
+                if let Some(native) = &self.native {
+                    native.pause()?;
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    pub fn resume(&self) -> Result<()> {
+        match self.active_backend {
+            Some(AudioBackendKind::Rodio) => {
+                if let Some(rodio) = &self.rodio {
+                    rodio.set_paused(false)?;
+                }
+            }
+            #[cfg(windows)]
+            Some(AudioBackendKind::Native) => {
+                if let Some(native) = &self.native {
+                    native.resume()?;
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
     pub fn is_finished(&self) -> Result<bool> {
         match self.active_backend {
             Some(AudioBackendKind::Rodio) => self
@@ -171,6 +207,19 @@ impl RodioAudio {
         Ok(())
     }
 
+    fn set_paused(&self, paused: bool) -> Result<()> {
+        let sink = self
+            .sink
+            .lock()
+            .map_err(|_| anyhow!("Audio sink mutex poisoned"))?;
+        if paused {
+            sink.pause();
+        } else {
+            sink.play();
+        }
+        Ok(())
+    }
+
     fn is_finished(&self) -> Result<bool> {
         let sink = self
             .sink
@@ -225,6 +274,14 @@ mod windows_mci {
             self.close()
         }
 
+        pub fn pause(&self) -> Result<()> {
+            mci_send(&format!("pause {}", self.alias))
+        }
+
+        pub fn resume(&self) -> Result<()> {
+            mci_send(&format!("resume {}", self.alias))
+        }
+
         pub fn is_finished(&self) -> Result<bool> {
             let status = mci_query(&format!("status {} mode", self.alias))?;
             Ok(matches!(status.as_str(), "stopped" | "not ready"))
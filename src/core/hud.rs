@@ -0,0 +1,142 @@
+use crate::renderer::{ActiveRenderBackend, CellOverlay, OutputStats};
+use std::time::{Duration, Instant};
+
+const HUD_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const HUD_FG: (u8, u8, u8) = (230, 230, 230);
+const HUD_BG: (u8, u8, u8) = (20, 20, 20);
+
+/// Values the HUD shows that the player already tracks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HudSnapshot {
+    pub target_fps: f64,
+    pub frames_dropped: u64,
+    /// Playback clock minus the frame's timestamp; positive means video lags
+    pub av_offset_ms: i64,
+    pub queue_depth: usize,
+    pub queue_capacity: usize,
+    pub target_width: u32,
+    pub target_height: u32,
+    pub backend: ActiveRenderBackend,
+}
+
+/// Performance overlay toggled with `i`
+///
+/// Rates are measured over a short window so the numbers stay readable; the
+/// counters keep running while hidden so the first frame after toggling on
+/// already shows real values.
+pub(crate) struct PerfHud {
+    visible: bool,
+    window_started_at: Instant,
+    window_frames: u64,
+    window_output: OutputStats,
+    render_fps: f64,
+    bytes_per_frame: u64,
+}
+
+impl PerfHud {
+    pub(crate) fn new() -> Self {
+        Self {
+            visible: false,
+            window_started_at: Instant::now(),
+            window_frames: 0,
+            window_output: OutputStats::default(),
+            render_fps: 0.0,
+            bytes_per_frame: 0,
+        }
+    }
+
+    pub(crate) fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Counts a rendered frame; `output` is the display's running total.
+    pub(crate) fn record_frame(&mut self, output: OutputStats) {
+        self.window_frames += 1;
+        let elapsed = self.window_started_at.elapsed();
+        if elapsed < HUD_REFRESH_INTERVAL {
+            return;
+        }
+
+        self.render_fps = self.window_frames as f64 / elapsed.as_secs_f64();
+        let frames = output.frames.saturating_sub(self.window_output.frames);
+        let bytes = output.bytes.saturating_sub(self.window_output.bytes);
+        self.bytes_per_frame = bytes.checked_div(frames).unwrap_or(0);
+        self.window_started_at = Instant::now();
+        self.window_frames = 0;
+        self.window_output = output;
+    }
+
+    pub(crate) fn lines(&self, snapshot: &HudSnapshot) -> Vec<String> {
+        vec![
+            format!("fps {:.1}/{:.1}", self.render_fps, snapshot.target_fps),
+            format!("drop {}", snapshot.frames_dropped),
+            format!("a/v {:+}ms", snapshot.av_offset_ms),
+            format!("queue {}/{}", snapshot.queue_depth, snapshot.queue_capacity),
+            format!("{} B/frame", self.bytes_per_frame),
+            format!(
+                "{}x{} {}",
+                snapshot.target_width,
+                snapshot.target_height,
+                snapshot.backend.label()
+            ),
+        ]
+    }
+
+    /// Draws the HUD into the top-left corner of the overlay's grid.
+    pub(crate) fn draw(&self, overlay: &mut CellOverlay, snapshot: &HudSnapshot) {
+        let lines = self.lines(snapshot);
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        for (row, line) in lines.iter().enumerate() {
+            overlay.put_text(0, row, &format!(" {:<width$} ", line), HUD_FG, HUD_BG);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> HudSnapshot {
+        HudSnapshot {
+            target_fps: 30.0,
+            frames_dropped: 4,
+            av_offset_ms: -12,
+            queue_depth: 3,
+            queue_capacity: 16,
+            target_width: 120,
+            target_height: 50,
+            backend: ActiveRenderBackend::AnsiRgb,
+        }
+    }
+
+    #[test]
+    fn hud_lines_cover_every_metric() {
+        let text = PerfHud::new().lines(&snapshot()).join("\n");
+
+        assert!(text.contains("fps 0.0/30.0"));
+        assert!(text.contains("drop 4"));
+        assert!(text.contains("a/v -12ms"));
+        assert!(text.contains("queue 3/16"));
+        assert!(text.contains("0 B/frame"));
+        assert!(text.contains("120x50 ansi-rgb"));
+    }
+
+    #[test]
+    fn hud_is_drawn_as_an_even_box_in_the_corner() {
+        let hud = PerfHud::new();
+        let mut overlay = CellOverlay::new();
+        overlay.begin(40, 10);
+        hud.draw(&mut overlay, &snapshot());
+
+        let cells = overlay.sorted_cells();
+        let widest = " 120x50 ansi-rgb ".len();
+        assert_eq!(cells.len(), 6 * widest);
+        assert!(cells
+            .iter()
+            .all(|(index, cell)| { index % 40 < widest && index / 40 < 6 && cell.bg == HUD_BG }));
+    }
+}
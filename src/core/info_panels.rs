@@ -0,0 +1,282 @@
+use crate::core::status_line::{format_status, StatusInfo};
+use crate::core::viewport::{PanelRect, ViewportLayout};
+use crate::decoder::SourceMetadata;
+use crate::renderer::cell::{char_width, text_width};
+use crate::renderer::{ActiveRenderBackend, DisplayManager, TextSpan};
+use crate::subtitle::{cue_rows, Cue, SUBTITLE_BG};
+use anyhow::Result;
+
+const TITLE_FG: (u8, u8, u8) = (245, 245, 245);
+const PANEL_FG: (u8, u8, u8) = (150, 150, 150);
+const PANEL_BG: (u8, u8, u8) = (0, 0, 0);
+
+/// Where each letterbox widget goes
+///
+/// The band above the video holds the title with the metadata under it; the
+/// band below holds subtitles next to the picture and the timeline on its
+/// last row. A widget without room is `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct PanelRegions {
+    pub title: Option<PanelRect>,
+    pub metadata: Option<PanelRect>,
+    pub subtitles: Option<PanelRect>,
+    pub timeline: Option<PanelRect>,
+}
+
+impl PanelRegions {
+    /// Splits the layout's letterbox bands; `with_timeline` is off when the
+    /// status line already shows the time.
+    pub(crate) fn for_layout(layout: ViewportLayout, with_timeline: bool) -> Self {
+        let mut regions = Self::default();
+        if let Some(band) = layout.top_band() {
+            let bottom = band.y + band.height - 1;
+            if band.height >= 2 {
+                regions.title = Some(row_of(band, bottom - 1));
+                regions.metadata = Some(row_of(band, bottom));
+            } else {
+                regions.title = Some(row_of(band, bottom));
+            }
+        }
+
+        if let Some(mut band) = layout.bottom_band() {
+            if with_timeline {
+                regions.timeline = Some(row_of(band, band.y + band.height - 1));
+                band.height -= 1;
+            }
+            // Keep a blank row between the picture and the subtitles when
+            // the band can spare it.
+            if band.height >= 3 {
+                band.y += 1;
+                band.height -= 1;
+            }
+            regions.subtitles = (band.height > 0).then_some(band);
+        }
+        regions
+    }
+}
+
+/// What the widgets show this refresh
+pub(crate) struct PanelContent<'a> {
+    pub title: &'a str,
+    pub metadata: &'a str,
+    pub status: StatusInfo<'a>,
+    /// `None` while subtitles are hidden
+    pub cues: Option<&'a [&'a Cue]>,
+}
+
+/// Draws every widget into its region.
+///
+/// The display caches each row, so widgets whose text did not change since
+/// the last call cost nothing and the video diff never touches them.
+pub(crate) fn draw_panels(
+    display: &mut DisplayManager,
+    regions: &PanelRegions,
+    content: &PanelContent<'_>,
+) -> Result<()> {
+    if let Some(rect) = regions.title {
+        draw_row(
+            display,
+            rect,
+            &centered(content.title, rect.width),
+            TITLE_FG,
+        )?;
+    }
+    if let Some(rect) = regions.metadata {
+        draw_row(
+            display,
+            rect,
+            &centered(content.metadata, rect.width),
+            PANEL_FG,
+        )?;
+    }
+    if let Some(rect) = regions.timeline {
+        let line = format_status(&content.status, rect.width as usize);
+        draw_row(display, rect, &line, PANEL_FG)?;
+    }
+    if let Some(rect) = regions.subtitles {
+        let width = rect.width as usize;
+        let lines = content
+            .cues
+            .map(|cues| cue_rows(cues, width, rect.height as usize))
+            .unwrap_or_default();
+        for row in 0..rect.height {
+            let span = match lines.get(row as usize) {
+                Some((text, fg)) => TextSpan {
+                    text: text.clone(),
+                    fg: *fg,
+                    bg: SUBTITLE_BG,
+                },
+                None => TextSpan {
+                    text: " ".repeat(width),
+                    fg: PANEL_FG,
+                    bg: SUBTITLE_BG,
+                },
+            };
+            display.draw_text_spans(rect.y + row, rect.x, &[span])?;
+        }
+    }
+    Ok(())
+}
+
+/// `1920x1080 · 29.97 fps · 03:20 · ansi-rgb`
+pub(crate) fn metadata_line(
+    metadata: &SourceMetadata,
+    playback_fps: f64,
+    backend: ActiveRenderBackend,
+) -> String {
+    let mut parts = Vec::new();
+    if metadata.width > 0 && metadata.height > 0 {
+        parts.push(format!("{}x{}", metadata.width, metadata.height));
+    }
+    if playback_fps > 0.0 {
+        parts.push(format!("{:.2} fps", playback_fps));
+    }
+    if let Some(duration) = metadata.duration {
+        parts.push(crate::core::status_line::format_timecode(duration));
+    }
+    parts.push(backend.label().to_string());
+    parts.join(" · ")
+}
+
+fn row_of(band: PanelRect, y: u16) -> PanelRect {
+    PanelRect {
+        y,
+        height: 1,
+        ..band
+    }
+}
+
+fn draw_row(
+    display: &mut DisplayManager,
+    rect: PanelRect,
+    text: &str,
+    fg: (u8, u8, u8),
+) -> Result<()> {
+    display.draw_text_spans(
+        rect.y,
+        rect.x,
+        &[TextSpan {
+            text: text.to_string(),
+            fg,
+            bg: PANEL_BG,
+        }],
+    )
+}
+
+/// `text` centered in exactly `width` columns, cut with `…` when too wide.
+fn centered(text: &str, width: u16) -> String {
+    let width = width as usize;
+    let mut fitted = String::new();
+    let mut used = 0;
+    if text_width(text) <= width {
+        fitted.push_str(text);
+        used = text_width(text);
+    } else if width > 0 {
+        for glyph in text.chars() {
+            let glyph_width = char_width(glyph);
+            if used + glyph_width + 1 > width {
+                break;
+            }
+            fitted.push(glyph);
+            used += glyph_width;
+        }
+        fitted.push('…');
+        used += 1;
+    }
+    let left = (width - used) / 2;
+    format!(
+        "{}{}{}",
+        " ".repeat(left),
+        fitted,
+        " ".repeat(width - used - left)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{DisplayMode, HeadlessTerminal};
+    use std::time::Duration;
+
+    fn letterboxed(top: u16, bottom: u16) -> ViewportLayout {
+        ViewportLayout {
+            terminal_cols: 40,
+            terminal_rows: top + 10 + bottom,
+            reserved_rows: 0,
+            offset_x: 0,
+            offset_y: top,
+            pixel_width: 40,
+            pixel_height: 20,
+        }
+    }
+
+    #[test]
+    fn bands_split_into_widget_regions() {
+        let regions = PanelRegions::for_layout(letterboxed(3, 4), true);
+
+        assert_eq!(regions.title.map(|rect| rect.y), Some(1));
+        assert_eq!(regions.metadata.map(|rect| rect.y), Some(2));
+        assert_eq!(regions.timeline.map(|rect| rect.y), Some(16));
+        let subtitles = regions.subtitles.unwrap();
+        assert_eq!((subtitles.y, subtitles.height), (14, 2));
+
+        let thin = PanelRegions::for_layout(letterboxed(1, 1), false);
+        assert_eq!(thin.title.map(|rect| rect.y), Some(0));
+        assert_eq!(thin.metadata, None);
+        assert_eq!(thin.timeline, None);
+        assert_eq!(
+            thin.subtitles.map(|rect| (rect.y, rect.height)),
+            Some((11, 1))
+        );
+
+        assert_eq!(
+            PanelRegions::for_layout(letterboxed(0, 0), true),
+            PanelRegions::default()
+        );
+    }
+
+    #[test]
+    fn centered_text_is_cut_by_display_width() {
+        assert_eq!(centered("abc", 7), "  abc  ");
+        assert_eq!(centered("가나다라", 7), "가나다…");
+        assert_eq!(text_width(&centered("가나다라", 6)), 6);
+    }
+
+    #[test]
+    fn unchanged_widgets_are_not_rewritten() {
+        let mut display = DisplayManager::headless(
+            DisplayMode::Rgb,
+            ActiveRenderBackend::AnsiRgb,
+            &HeadlessTerminal {
+                cols: 40,
+                rows: 17,
+                output: None,
+            },
+        )
+        .unwrap();
+        let regions = PanelRegions::for_layout(letterboxed(3, 4), true);
+        let mut content = PanelContent {
+            title: "Bad Apple!!",
+            metadata: "480x360 · 30.00 fps",
+            status: StatusInfo {
+                position: Duration::from_secs(1),
+                duration: Some(Duration::from_secs(219)),
+                title: "",
+                paused: false,
+                speed: 1.0,
+            },
+            cues: None,
+        };
+
+        draw_panels(&mut display, &regions, &content).unwrap();
+        let first = display.output_stats().bytes;
+        draw_panels(&mut display, &regions, &content).unwrap();
+        assert_eq!(display.output_stats().bytes, first);
+
+        // Only the timeline changed, so only its row is written.
+        content.status.position = Duration::from_secs(90);
+        draw_panels(&mut display, &regions, &content).unwrap();
+        let written = display.output_stats().bytes - first;
+        assert!(written > 0 && written < 256, "{} bytes", written);
+    }
+}
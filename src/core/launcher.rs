@@ -17,6 +17,9 @@ pub fn run() -> Result<()> {
     let viewport = match selection.viewport_mode {
         ViewportMode::Fullscreen => "fullscreen",
         ViewportMode::CinemaScope => "cinemascope",
+        ViewportMode::Original => "original",
+        ViewportMode::Aspect(_) => "aspect",
+        ViewportMode::ZoomPan => "zoom",
     };
 
     crate::utils::logger::info(&format!(
@@ -49,10 +52,20 @@ pub fn run() -> Result<()> {
         requested_width: None,
         requested_height: None,
         requested_fps: None,
+        source_options: crate::decoder::SourceOptions::default(),
         display_mode: selection.display_mode,
         viewport_mode: selection.viewport_mode,
         quality: selection.quality,
         truecolor_policy: crate::renderer::TruecolorPolicy::Auto,
+        headless: None,
+        status_line: false,
+        subtitles: crate::core::player::SidecarSource::Sibling,
+        sub_delay: 0.0,
+        lyrics: crate::core::player::SidecarSource::Sibling,
+        info_panels: false,
+        grade: None,
+        mono: crate::renderer::MonoMode::Auto,
+        temporal: crate::decoder::TemporalMode::Off,
     })?;
 
     Ok(())
@@ -1,8 +1,11 @@
 #![allow(unused)]
 pub mod audio_manager;
 pub mod extractor;
+mod hud;
+mod info_panels;
 pub mod launcher;
 mod playback_runtime;
 pub mod player;
 pub mod render_budget;
+mod status_line;
 pub mod viewport;
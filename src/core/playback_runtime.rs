@@ -2,7 +2,7 @@ use crate::core::render_budget::FrameBudgetPolicy;
 use crate::core::viewport::{ViewportLayout, ViewportMode};
 use crate::decoder::{FrameData, RenderTarget};
 use crate::renderer::cell::CellData;
-use crate::renderer::{DisplayManager, FrameProcessor};
+use crate::renderer::{DisplayManager, FrameProcessor, OutputStats};
 use anyhow::{anyhow, Result};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
@@ -19,6 +19,9 @@ pub(crate) struct PlaybackStats {
     pub frames_rendered: u64,
     pub frames_dropped: u64,
     pub started_at: Instant,
+    /// Time spent in `FrameProcessor` turning pixels into cells
+    pub process_time: Duration,
+    pub output: OutputStats,
 }
 
 impl PlaybackStats {
@@ -27,8 +30,22 @@ impl PlaybackStats {
             frames_rendered: 0,
             frames_dropped: 0,
             started_at: Instant::now(),
+            process_time: Duration::ZERO,
+            output: OutputStats::default(),
         }
     }
+
+    fn output_summary(&self) -> String {
+        format!(
+            "output: bytes={} bytes_per_frame={} process={:.2}ms encode={:.2}ms write={:.2}ms stale={}",
+            self.output.bytes,
+            self.output.bytes_per_frame(),
+            self.process_time.as_secs_f64() * 1000.0,
+            self.output.encode_time.as_secs_f64() * 1000.0,
+            self.output.write_time.as_secs_f64() * 1000.0,
+            self.output.stale_frames
+        )
+    }
 }
 
 pub(crate) fn is_too_late(
@@ -65,6 +82,7 @@ pub(crate) fn handle_resize(
         budget_policy,
         source_aspect,
         pixel_aspect_correction,
+        layout.reserved_rows,
     );
 
     let recentered = (*layout).recentered_for_terminal(cols, rows);
@@ -88,7 +106,7 @@ pub(crate) fn handle_resize(
         let mut guard = target
             .write()
             .map_err(|_| anyhow!("render target lock poisoned"))?;
-        *guard = RenderTarget::new(next_layout.pixel_width, next_layout.pixel_height);
+        *guard = guard.resized(next_layout.pixel_width, next_layout.pixel_height);
         *pending_layout = Some(next_layout);
     }
 
@@ -145,6 +163,7 @@ pub(crate) fn finalize(
         .map_err(|_| anyhow!("Decoder thread panicked"))?;
 
     let duration = stats.started_at.elapsed();
+    crate::utils::logger::info(&stats.output_summary());
     match reason {
         ShutdownReason::UserRequested => {
             crate::utils::logger::info(&format!(
@@ -175,17 +194,33 @@ pub(crate) fn finalize(
                 error
             ));
             println!("\n디코더 오류로 종료");
-            println!("렌더링 프레임: {}", stats.frames_rendered);
-            println!("드롭 프레임: {}", stats.frames_dropped);
-            println!("재생 시간: {:.2}초", duration.as_secs_f64());
+            print_stats(&stats, duration);
             return Err(error);
         }
     }
 
+    print_stats(&stats, duration);
+    Ok(())
+}
+
+fn print_stats(stats: &PlaybackStats, duration: Duration) {
     println!("렌더링 프레임: {}", stats.frames_rendered);
     println!("드롭 프레임: {}", stats.frames_dropped);
+    if stats.output.stale_frames > 0 {
+        println!("출력 지연으로 건너뛴 프레임: {}", stats.output.stale_frames);
+    }
     println!("재생 시간: {:.2}초", duration.as_secs_f64());
-    Ok(())
+    println!(
+        "출력: {} bytes (프레임당 {} bytes)",
+        stats.output.bytes,
+        stats.output.bytes_per_frame()
+    );
+    println!(
+        "처리 시간: 셀 변환 {:.2}ms, 인코딩 {:.2}ms, 쓰기 {:.2}ms",
+        stats.process_time.as_secs_f64() * 1000.0,
+        stats.output.encode_time.as_secs_f64() * 1000.0,
+        stats.output.write_time.as_secs_f64() * 1000.0
+    );
 }
 
 fn relayout(
@@ -201,7 +236,7 @@ fn relayout(
         let mut guard = target
             .write()
             .map_err(|_| anyhow!("render target lock poisoned"))?;
-        *guard = RenderTarget::new(layout.pixel_width, layout.pixel_height);
+        *guard = guard.resized(layout.pixel_width, layout.pixel_height);
     }
 
     if let Some(processor) = processor {
@@ -257,4 +292,50 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().to_string(), "decode failed");
     }
+
+    fn pattern_frames(spec: &str) -> Vec<FrameData> {
+        let source = crate::decoder::pattern::test_source(spec, RenderTarget::new(32, 16));
+        let fps = crate::decoder::FrameSource::fps(&source);
+        let (sender, receiver) = bounded(64);
+        let handle = crate::decoder::FrameSource::spawn_decoding_thread(
+            Box::new(source),
+            sender,
+            crate::decoder::DecodeTiming::retimed(fps),
+        );
+        handle.join().unwrap().unwrap();
+        receiver.try_iter().map(|frame| frame.unwrap()).collect()
+    }
+
+    #[test]
+    fn pattern_frames_are_timestamped_from_their_index() {
+        let frames = pattern_frames("pattern:counter,size=64x32,fps=10,duration=0.4");
+
+        let timestamps: Vec<u128> = frames
+            .iter()
+            .map(|frame| frame.timestamp.as_millis())
+            .collect();
+        assert_eq!(timestamps, vec![0, 100, 200, 300]);
+        assert!(frames
+            .iter()
+            .all(|frame| (frame.width, frame.height) == (32, 16)));
+    }
+
+    #[test]
+    fn pattern_frames_are_too_late_only_past_the_drop_threshold() {
+        let frames = pattern_frames("pattern:bars,size=64x32,fps=10,duration=0.4");
+        let policy = FrameBudgetPolicy::for_backend(
+            crate::renderer::DisplayMode::Rgb,
+            crate::renderer::ActiveRenderBackend::for_mode(crate::renderer::DisplayMode::Rgb),
+            crate::core::render_budget::RenderQuality::Full,
+        );
+        let playback_time = Duration::from_millis(150);
+
+        let late: Vec<bool> = frames
+            .iter()
+            .map(|frame| is_too_late(frame, playback_time, policy))
+            .collect();
+        // 150ms lag on the first frame exceeds the 90ms budget; the rest are
+        // within it or still in the future.
+        assert_eq!(late, vec![true, false, false, false]);
+    }
 }
@@ -1,19 +1,29 @@
 use crate::core::audio_manager::AudioManager;
+use crate::core::hud::{HudSnapshot, PerfHud};
+use crate::core::info_panels::{draw_panels, metadata_line, PanelContent, PanelRegions};
 use crate::core::playback_runtime::{
     classify_frame, finalize, handle_resize, is_too_late, wait_for_resized_frame, PlaybackStats,
     ShutdownReason,
 };
 use crate::core::render_budget::FrameBudgetPolicy;
-use crate::core::viewport::ViewportLayout;
-use crate::decoder::{RenderTarget, ScaleMode, VideoDecoder};
+use crate::core::status_line::{StatusInfo, StatusLine, STATUS_BG, STATUS_FG};
+use crate::core::viewport::{PanelRect, ViewportLayout, ZoomPan};
+use crate::decoder::{
+    open_source, BufferPool, DecodeTiming, RenderTarget, ScaleMode, Scaler, SourceOptions,
+    TemporalMode,
+};
 use crate::renderer::cell::CellData;
+use crate::renderer::grading::{GradeCycle, Look};
 use crate::renderer::{
-    ActiveRenderBackend, DisplayManager, DisplayMode, FrameProcessor, TruecolorPolicy,
+    ActiveRenderBackend, DisplayManager, DisplayMode, FrameProcessor, HeadlessTerminal, MonoMode,
+    MonoRenderer, TextSpan, TruecolorPolicy,
 };
-use crate::sync::MasterClock;
-use anyhow::{anyhow, Result};
-use crossterm::event::{self, Event, KeyCode};
-use std::path::PathBuf;
+use crate::subtitle::lyrics::{self, Lyrics, LYRIC_BG};
+use crate::subtitle::{draw_cues, SubtitleTrack};
+use crate::sync::{DropDeadline, MasterClock, PauseGate};
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
@@ -24,6 +34,13 @@ const DEFAULT_QUEUE_CAPACITY: usize = 16;
 const MIN_QUEUE_CAPACITY: usize = 3;
 const DEFAULT_QUEUE_MEMORY_BUDGET: usize = 128 * 1024 * 1024;
 const FALLBACK_RESIZE_POLL: Duration = Duration::from_millis(100);
+const STATUS_REFRESH: Duration = Duration::from_millis(250);
+const PAUSED_POLL: Duration = Duration::from_millis(10);
+/// Longest sleep while waiting for an early frame, so input stays responsive
+const MAX_FRAME_WAIT: Duration = PAUSED_POLL;
+const LYRICS_REFRESH: Duration = Duration::from_millis(50);
+const LYRICS_PANEL_MIN_WIDTH: u16 = 16;
+const INFO_PANELS_REFRESH: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone)]
 pub struct PlaybackConfig {
@@ -32,30 +49,63 @@ pub struct PlaybackConfig {
     pub requested_width: Option<u32>,
     pub requested_height: Option<u32>,
     pub requested_fps: Option<u32>,
+    pub source_options: SourceOptions,
     pub display_mode: DisplayMode,
     pub viewport_mode: ViewportMode,
     pub quality: RenderQuality,
     pub truecolor_policy: TruecolorPolicy,
+    /// Render into a virtual terminal instead of the real one
+    pub headless: Option<HeadlessTerminal>,
+    /// Reserve the bottom row for a timecode/progress line
+    pub status_line: bool,
+    pub subtitles: SidecarSource,
+    /// Seconds added to every cue's timing
+    pub sub_delay: f64,
+    pub lyrics: SidecarSource,
+    /// Title, metadata, timeline and subtitles in the letterbox bands
+    pub info_panels: bool,
+    /// Colour look to start with; 'c' cycles through the rest
+    pub grade: Option<Look>,
+    /// 1-bit rendering for black-and-white sources
+    pub mono: MonoMode,
+    /// How frames are converted when `requested_fps` differs from the source
+    pub temporal: TemporalMode,
+}
+
+/// Where a subtitle or lyrics file comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SidecarSource {
+    Off,
+    /// A file named like the media next to it, when present: `video.srt`
+    /// for subtitles, `audio.lrc` (or `video.lrc`) for lyrics
+    Sibling,
+    File(PathBuf),
 }
 
 pub fn play(config: PlaybackConfig) -> Result<()> {
     let requested_backend = ActiveRenderBackend::for_mode(config.display_mode);
-    let mut display = DisplayManager::new(
-        config.display_mode,
-        requested_backend,
-        config.truecolor_policy,
-    )?;
+    let mut display = match &config.headless {
+        Some(terminal) => {
+            DisplayManager::headless(config.display_mode, requested_backend, terminal)?
+        }
+        None => DisplayManager::new(
+            config.display_mode,
+            requested_backend,
+            config.truecolor_policy,
+        )?,
+    };
     let active_backend = display.active_backend();
     let pixel_aspect_correction = DisplayManager::render_pixel_aspect_correction(active_backend);
     let budget_policy =
         FrameBudgetPolicy::for_backend(config.display_mode, active_backend, config.quality);
-    let (term_cols, term_rows) = DisplayManager::current_terminal_size_chars()?;
+    let (term_cols, term_rows) = display.terminal_size_chars()?;
     let target = Arc::new(RwLock::new(RenderTarget::new(1, 2)));
 
-    let decoder = VideoDecoder::new(
-        config.video_path.to_string_lossy().as_ref(),
+    let decoder = open_source(
+        &config.video_path,
         target.clone(),
         scale_mode_for_viewport(config.viewport_mode),
+        with_default_scaler(&config.source_options, config.display_mode, config.mono)?,
     )?;
     let source_aspect = decoder.source_aspect_ratio();
     let mut layout = ViewportLayout::calculate(
@@ -67,6 +117,7 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         budget_policy,
         source_aspect,
         pixel_aspect_correction,
+        u16::from(config.status_line),
     );
     {
         let mut guard = target
@@ -75,7 +126,34 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         *guard = RenderTarget::new(layout.pixel_width, layout.pixel_height);
     }
 
-    let source_fps = decoder.get_fps();
+    let source_fps = decoder.fps();
+    let metadata = decoder.metadata();
+    let title = metadata.title.clone().unwrap_or_else(|| {
+        config
+            .video_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let subtitles = load_subtitles(&config)?;
+    // Generated audio is only written when it will play, and removed when
+    // this guard is dropped at the end of playback.
+    let companion_audio = match &config.audio_path {
+        None if !display.is_headless() => decoder.companion_audio()?,
+        _ => None,
+    };
+    let source_audio = config.audio_path.clone().or_else(|| {
+        companion_audio
+            .as_ref()
+            .map(|audio| audio.path().to_path_buf())
+    });
+    let lyrics = load_lyrics(&config, source_audio.as_deref())?;
+    let audio_path = if display.is_headless() {
+        crate::utils::logger::info("headless playback: audio output disabled");
+        None
+    } else {
+        source_audio
+    };
     let playback_fps = config
         .requested_fps
         .filter(|value| *value > 0)
@@ -88,7 +166,17 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         queue_capacity, layout.pixel_width, layout.pixel_height
     ));
     let (frame_sender, frame_receiver) = crossbeam_channel::bounded(queue_capacity);
-    let decoder_handle = decoder.spawn_decoding_thread(frame_sender, playback_fps);
+    let drop_deadline = DropDeadline::new();
+    let pause = PauseGate::new();
+    let timing = DecodeTiming {
+        playback_fps,
+        temporal: config.temporal,
+        skip_late: Some(drop_deadline.clone()),
+        recycle: Some(BufferPool::new(queue_capacity)),
+        pause: Some(pause.clone()),
+    };
+    let converts_rate = timing.converts_rate(source_fps);
+    let decoder_handle = decoder.spawn_decoding_thread(frame_sender, timing);
     let mut frame_receiver = Some(frame_receiver);
     let receiver = frame_receiver
         .as_ref()
@@ -109,8 +197,17 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         pending_future = wait_for_resized_frame(receiver, layout.pixel_width, layout.pixel_height)?;
     }
 
-    let (audio_manager, clock_start) = start_audio_or_fallback(config.audio_path.as_ref());
-    let clock = MasterClock::from_start(clock_start);
+    let (audio_manager, clock_start) = start_audio_or_fallback(audio_path.as_ref());
+    let mut clock = MasterClock::from_start(clock_start);
+    // Converted frames keep the source's speed; re-timed ones run at the
+    // ratio of the two rates.
+    let speed = if source_fps > 0.0 && !converts_rate {
+        playback_fps / source_fps
+    } else {
+        1.0
+    };
+
+    let metadata_text = metadata_line(&metadata, playback_fps, active_backend);
 
     let mut stats = PlaybackStats::new();
     let mut shutdown_reason = ShutdownReason::Completed;
@@ -119,14 +216,51 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
     let mut last_terminal_size = (layout.terminal_cols, layout.terminal_rows);
     let mut pending_layout: Option<ViewportLayout> = None;
     let mut future_frame = Some(pending_future);
+    let mut hud = PerfHud::new();
+    let mut status = config.status_line.then(StatusLine::new);
+    let mut last_status_refresh: Option<Instant> = None;
+    let mut captions_visible = true;
+    let mut last_lyrics_refresh: Option<Instant> = None;
+    let mut lyrics_panel: Option<PanelRect> = None;
+    let mut last_panels_refresh: Option<Instant> = None;
+    let mut zoom = ZoomPan::new();
+    let mut grading = GradeCycle::new(config.grade.clone());
+    let mut mono = MonoRenderer::for_backend(config.mono, active_backend);
+    set_mono_colors(&mut mono, grading.active());
 
     loop {
-        while event::poll(Duration::from_millis(0))? {
+        while !display.is_headless() && event::poll(Duration::from_millis(0))? {
             match event::read()? {
                 Event::Key(key) if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) => {
                     shutdown_reason = ShutdownReason::UserRequested;
                     break;
                 }
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Char('i') => hud.toggle(),
+                        KeyCode::Char(' ') => toggle_pause(&mut clock, &pause, &audio_manager),
+                        KeyCode::Char('s') => captions_visible = !captions_visible,
+                        KeyCode::Char('c') => {
+                            let name = grading.cycle().map_or("off", Look::name);
+                            crate::utils::logger::info(&format!("color grade: {}", name));
+                            set_mono_colors(&mut mono, grading.active());
+                        }
+                        code if config.viewport_mode == ViewportMode::ZoomPan
+                            && apply_zoom_key(&mut zoom, code) =>
+                        {
+                            target
+                                .write()
+                                .map_err(|_| anyhow!("render target lock poisoned"))?
+                                .crop = zoom.crop();
+                        }
+                        _ => {}
+                    }
+                    if let Some(status) = &mut status {
+                        status.note_input();
+                    }
+                    last_status_refresh = None;
+                    last_panels_refresh = None;
+                }
                 Event::Resize(cols, rows) => {
                     last_terminal_size = (cols, rows);
                     resize_playback(
@@ -149,7 +283,7 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
             break;
         }
 
-        if last_resize_probe.elapsed() >= FALLBACK_RESIZE_POLL {
+        if !display.is_headless() && last_resize_probe.elapsed() >= FALLBACK_RESIZE_POLL {
             let current_size = DisplayManager::current_terminal_size_chars()?;
             if current_size != last_terminal_size {
                 last_terminal_size = current_size;
@@ -169,7 +303,74 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
             last_resize_probe = Instant::now();
         }
 
+        if let (Some(status), Some(row)) = (&status, layout.status_row()) {
+            if last_status_refresh.is_none_or(|at| at.elapsed() >= STATUS_REFRESH) {
+                let info = StatusInfo {
+                    position: clock.elapsed().mul_f64(speed),
+                    duration: metadata.duration,
+                    title: &title,
+                    paused: clock.is_paused(),
+                    speed,
+                };
+                display.draw_text_row(
+                    row,
+                    &status.text(&info, layout.terminal_cols as usize),
+                    STATUS_FG,
+                    STATUS_BG,
+                )?;
+                last_status_refresh = Some(Instant::now());
+            }
+        }
+
+        if config.info_panels
+            && last_panels_refresh.is_none_or(|at| at.elapsed() >= INFO_PANELS_REFRESH)
+        {
+            let position = clock.elapsed().mul_f64(speed);
+            let cues = match &subtitles {
+                Some(track) => track.active_with_delay(position, config.sub_delay),
+                None => Vec::new(),
+            };
+            draw_panels(
+                &mut display,
+                &PanelRegions::for_layout(layout, !config.status_line),
+                &PanelContent {
+                    title: &title,
+                    metadata: &metadata_text,
+                    status: StatusInfo {
+                        position,
+                        duration: metadata.duration,
+                        title: "",
+                        paused: clock.is_paused(),
+                        speed,
+                    },
+                    cues: captions_visible.then_some(cues.as_slice()),
+                },
+            )?;
+            last_panels_refresh = Some(Instant::now());
+        }
+
+        if let Some(lyrics) = &lyrics {
+            if last_lyrics_refresh.is_none_or(|at| at.elapsed() >= LYRICS_REFRESH) {
+                let panel = layout.side_panel(LYRICS_PANEL_MIN_WIDTH);
+                if let Some(previous) = lyrics_panel.filter(|previous| Some(*previous) != panel) {
+                    clear_panel(&mut display, previous)?;
+                }
+                if let Some(panel) = panel {
+                    let time = clock.elapsed().mul_f64(speed);
+                    draw_lyrics_panel(&mut display, panel, lyrics, time, captions_visible)?;
+                }
+                lyrics_panel = panel;
+                last_lyrics_refresh = Some(Instant::now());
+            }
+        }
+
+        if clock.is_paused() {
+            std::thread::sleep(PAUSED_POLL);
+            continue;
+        }
+
         let playback_time = clock.elapsed();
+        drop_deadline.publish(playback_time.saturating_sub(budget_policy.drop_threshold));
         let mut frame_to_render = None;
 
         if let Some(frame) = future_frame.take() {
@@ -254,19 +455,34 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         if frame_to_render.is_none() {
             if let Some(frame) = future_frame.take() {
                 if frame.width == layout.pixel_width && frame.height == layout.pixel_height {
-                    if frame.timestamp > playback_time {
-                        let wait_time = frame.timestamp - playback_time;
-                        if wait_time > Duration::from_millis(1) {
-                            std::thread::sleep(wait_time);
-                        }
+                    let wait_time = frame.timestamp.saturating_sub(playback_time);
+                    if wait_time > MAX_FRAME_WAIT {
+                        // Keep handling input while waiting for a frame
+                        // that is still far ahead.
+                        std::thread::sleep(MAX_FRAME_WAIT);
+                        future_frame = Some(frame);
+                        continue;
+                    }
+                    if wait_time > Duration::from_millis(1) {
+                        std::thread::sleep(wait_time);
                     }
                     frame_to_render = Some(frame);
                 }
             }
         }
 
-        if let Some(frame) = frame_to_render {
+        if let Some(mut frame) = frame_to_render {
             let render_viewport = layout.as_render_viewport();
+            let process_started = Instant::now();
+            let is_mono = mono.prepare(
+                &mut frame.buffer,
+                frame.width as usize,
+                frame.height as usize,
+            );
+            if let Some(look) = grading.active().filter(|_| !is_mono) {
+                look.apply(&mut frame.buffer, frame.width as usize);
+            }
+            stats.process_time += process_started.elapsed();
             let rgb_cells = if active_backend.requires_cell_buffer() {
                 let processor = processor
                     .as_mut()
@@ -274,13 +490,61 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
                 let cells = cell_buffer
                     .as_mut()
                     .ok_or_else(|| anyhow!("RGB ANSI renderer missing cell buffer"))?;
-                processor.process_frame_into(&frame.buffer, cells);
+                let process_started = Instant::now();
+                if is_mono {
+                    mono.cells_into(cells);
+                } else {
+                    processor.process_frame_into(&frame.buffer, cells);
+                }
+                stats.process_time += process_started.elapsed();
                 Some(cells.as_slice())
             } else {
                 None
             };
 
+            let (grid_width, grid_height) = (
+                layout.pixel_width as usize,
+                layout.pixel_height as usize / 2,
+            );
+            let overlay = display.overlay_mut();
+            overlay.begin(grid_width, grid_height);
+            let subtitles_in_band = config.info_panels
+                && PanelRegions::for_layout(layout, !config.status_line)
+                    .subtitles
+                    .is_some();
+            if let Some(track) = subtitles
+                .as_ref()
+                .filter(|_| captions_visible && !subtitles_in_band)
+            {
+                let cues =
+                    track.active_with_delay(frame.timestamp.mul_f64(speed), config.sub_delay);
+                draw_cues(overlay, &cues, grid_width, grid_height);
+            }
+            if let Some(lyrics) = lyrics.as_ref().filter(|_| captions_visible) {
+                if lyrics_panel.is_none() {
+                    let time = clock.elapsed().mul_f64(speed);
+                    lyrics::draw_karaoke(overlay, lyrics, time, grid_width, grid_height);
+                }
+            }
+            if hud.is_visible() {
+                let av_offset = clock.elapsed().as_secs_f64() - frame.timestamp.as_secs_f64();
+                hud.draw(
+                    overlay,
+                    &HudSnapshot {
+                        target_fps: playback_fps,
+                        frames_dropped: stats.frames_dropped,
+                        av_offset_ms: (av_offset * 1000.0).round() as i64,
+                        queue_depth: receiver.len(),
+                        queue_capacity,
+                        target_width: layout.pixel_width,
+                        target_height: layout.pixel_height,
+                        backend: active_backend,
+                    },
+                );
+            }
+
             display.render(&frame.buffer, rgb_cells, render_viewport)?;
+            hud.record_frame(display.output_stats());
             stats.frames_rendered += 1;
             continue;
         }
@@ -296,6 +560,8 @@ pub fn play(config: PlaybackConfig) -> Result<()> {
         let _ = audio.stop();
     }
     drop(frame_receiver.take());
+    display.finish_output()?;
+    stats.output = display.output_stats();
 
     finalize(decoder_handle, stats, shutdown_reason)
 }
@@ -328,6 +594,139 @@ fn resize_playback(
     )
 }
 
+fn load_subtitles(config: &PlaybackConfig) -> Result<Option<SubtitleTrack>> {
+    let path = match &config.subtitles {
+        SidecarSource::Off => return Ok(None),
+        SidecarSource::File(path) => path.clone(),
+        SidecarSource::Sibling => match crate::subtitle::find_sibling(&config.video_path) {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+    let track = match SubtitleTrack::load(&path) {
+        Ok(track) => track,
+        Err(error) if config.subtitles == SidecarSource::Sibling => {
+            crate::utils::logger::error(&format!("Subtitles disabled: {:#}", error));
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+    crate::utils::logger::info(&format!(
+        "subtitles loaded: {} cues={} delay={:+.3}s",
+        path.display(),
+        track.cue_count(),
+        config.sub_delay
+    ));
+    Ok(Some(track))
+}
+
+fn load_lyrics(config: &PlaybackConfig, audio_path: Option<&Path>) -> Result<Option<Lyrics>> {
+    let path = match &config.lyrics {
+        SidecarSource::Off => return Ok(None),
+        SidecarSource::File(path) => path.clone(),
+        SidecarSource::Sibling => match lyrics::find_lyrics(audio_path, &config.video_path) {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+    let lyrics = match Lyrics::load(&path) {
+        Ok(lyrics) => lyrics,
+        Err(error) if config.lyrics == SidecarSource::Sibling => {
+            crate::utils::logger::error(&format!("Lyrics disabled: {:#}", error));
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+    crate::utils::logger::info(&format!(
+        "lyrics loaded: {} lines={}",
+        path.display(),
+        lyrics.line_count()
+    ));
+    Ok(Some(lyrics))
+}
+
+/// Draws the karaoke side panel; hidden lyrics leave it blank.
+fn draw_lyrics_panel(
+    display: &mut DisplayManager,
+    panel: PanelRect,
+    lyrics: &Lyrics,
+    time: Duration,
+    visible: bool,
+) -> Result<()> {
+    if !visible {
+        return clear_panel(display, panel);
+    }
+    let rows = lyrics::panel_rows(lyrics, time, panel.width as usize, panel.height as usize);
+    for (row, spans) in (panel.y..).zip(rows) {
+        let spans: Vec<TextSpan> = spans
+            .into_iter()
+            .map(|(text, fg)| TextSpan {
+                text,
+                fg,
+                bg: LYRIC_BG,
+            })
+            .collect();
+        display.draw_text_spans(row, panel.x, &spans)?;
+    }
+    Ok(())
+}
+
+fn clear_panel(display: &mut DisplayManager, panel: PanelRect) -> Result<()> {
+    let blank = [TextSpan {
+        text: " ".repeat(panel.width as usize),
+        fg: LYRIC_BG,
+        bg: LYRIC_BG,
+    }];
+    for row in panel.y..panel.y + panel.height {
+        display.draw_text_spans(row, panel.x, &blank)?;
+    }
+    Ok(())
+}
+
+/// `+`/`-` zoom, arrows pan, `0` resets; true when the crop changed.
+fn apply_zoom_key(zoom: &mut ZoomPan, code: KeyCode) -> bool {
+    let before = *zoom;
+    match code {
+        KeyCode::Char('+') | KeyCode::Char('=') => zoom.zoom_in(),
+        KeyCode::Char('-') => zoom.zoom_out(),
+        KeyCode::Char('0') => zoom.reset(),
+        KeyCode::Left => zoom.pan(-1.0, 0.0),
+        KeyCode::Right => zoom.pan(1.0, 0.0),
+        KeyCode::Up => zoom.pan(0.0, -1.0),
+        KeyCode::Down => zoom.pan(0.0, 1.0),
+        _ => return false,
+    }
+    *zoom != before
+}
+
+/// Mono draws in the active look's black and white.
+fn set_mono_colors(mono: &mut MonoRenderer, look: Option<&Look>) {
+    let mut colors = [0, 0, 0, 255, 255, 255];
+    if let Some(look) = look {
+        look.apply(&mut colors, 2);
+    }
+    mono.set_colors(
+        (colors[3], colors[4], colors[5]),
+        (colors[0], colors[1], colors[2]),
+    );
+}
+
+/// Pauses or resumes the clock, the decoder's pacing and the audio together.
+fn toggle_pause(clock: &mut MasterClock, pause: &PauseGate, audio_manager: &Option<AudioManager>) {
+    let result = if clock.is_paused() {
+        clock.resume();
+        pause.resume();
+        audio_manager.as_ref().map(AudioManager::resume)
+    } else {
+        clock.pause();
+        pause.pause();
+        audio_manager.as_ref().map(AudioManager::pause)
+    };
+    if let Some(Err(error)) = result {
+        crate::utils::logger::error(&format!("Audio pause/resume failed: {}", error));
+    }
+}
+
 fn audio_is_done(audio_manager: &Option<AudioManager>) -> bool {
     match audio_manager {
         Some(audio) => audio.is_finished().unwrap_or(true),
@@ -378,11 +777,41 @@ fn queue_capacity_for_frame_bytes(frame_bytes: usize) -> usize {
     budgeted.clamp(MIN_QUEUE_CAPACITY, DEFAULT_QUEUE_CAPACITY)
 }
 
-fn scale_mode_for_viewport(viewport_mode: ViewportMode) -> ScaleMode {
+pub(crate) fn scale_mode_for_viewport(viewport_mode: ViewportMode) -> ScaleMode {
     match viewport_mode {
-        ViewportMode::Fullscreen => ScaleMode::CropToFill,
-        ViewportMode::CinemaScope => ScaleMode::Fit,
+        ViewportMode::Fullscreen | ViewportMode::Aspect(_) | ViewportMode::ZoomPan => {
+            ScaleMode::CropToFill
+        }
+        ViewportMode::CinemaScope | ViewportMode::Original => ScaleMode::Fit,
+    }
+}
+
+/// Scaler used when none is given: box averaging where the frame ends up
+/// as brightness levels or two tones, Hamming for colour half-blocks.
+pub(crate) fn default_scaler(display_mode: DisplayMode, mono: MonoMode) -> Scaler {
+    match (display_mode, mono) {
+        (DisplayMode::Ascii, _) | (_, MonoMode::On) => Scaler::Area,
+        _ => Scaler::Hamming,
+    }
+}
+
+/// `options` with a missing scaler taken from `GASCII_SCALER`, or else the
+/// mode's default. `--scaler` and `scaler=` in `--filters` have already
+/// been folded into `options.scaler` and win over both.
+pub(crate) fn with_default_scaler(
+    options: &SourceOptions,
+    display_mode: DisplayMode,
+    mono: MonoMode,
+) -> Result<SourceOptions> {
+    let mut options = options.clone();
+    if options.scaler.is_none() {
+        let scaler = match std::env::var("GASCII_SCALER") {
+            Ok(name) => Scaler::parse(&name).context("invalid GASCII_SCALER")?,
+            Err(_) => default_scaler(display_mode, mono),
+        };
+        options.scaler = Some(scaler);
     }
+    Ok(options)
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use crate::core::viewport::{make_even, ViewportMode};
+use crate::core::viewport::make_even;
 use crate::renderer::{ActiveRenderBackend, DisplayMode};
 use std::time::Duration;
 
@@ -51,17 +51,9 @@ impl FrameBudgetPolicy {
         }
     }
 
-    pub(crate) fn apply_to_dimensions(
-        self,
-        width: u32,
-        height: u32,
-        mode: ViewportMode,
-    ) -> (u32, u32) {
+    pub(crate) fn apply_to_dimensions(self, width: u32, height: u32) -> (u32, u32) {
         if self.max_render_cells == u32::MAX {
-            return match mode {
-                ViewportMode::Fullscreen => (width.max(1), make_even(height.max(2))),
-                ViewportMode::CinemaScope => (width.max(1), make_even(height.max(2))),
-            };
+            return (width.max(1), make_even(height.max(2)));
         }
 
         let current_cells = width.saturating_mul((height / 2).max(1));
@@ -75,17 +67,14 @@ impl FrameBudgetPolicy {
         let scaled_width = scaled_width.max(1);
         let scaled_height = make_even(scaled_height.max(2));
 
-        match mode {
-            ViewportMode::Fullscreen => (scaled_width, scaled_height),
-            ViewportMode::CinemaScope => (scaled_width, scaled_height),
-        }
+        (scaled_width, scaled_height)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::viewport::ViewportLayout;
+    use crate::core::viewport::{ViewportLayout, ViewportMode};
 
     #[test]
     fn balanced_quality_scales_down_large_rgb_viewports() {
@@ -102,6 +91,7 @@ mod tests {
             ),
             16.0 / 9.0,
             1.0,
+            0,
         );
         let cells = layout.pixel_width * (layout.pixel_height / 2);
         assert!(cells <= 24_000);
@@ -122,6 +112,7 @@ mod tests {
             ),
             16.0 / 9.0,
             1.0,
+            0,
         );
         let cells = layout.pixel_width * (layout.pixel_height / 2);
         assert!(cells <= 18_000);
@@ -0,0 +1,197 @@
+use std::time::{Duration, Instant};
+
+use crate::renderer::cell::{char_width, text_width};
+
+const STATUS_AUTO_HIDE: Duration = Duration::from_secs(3);
+pub(crate) const STATUS_FG: (u8, u8, u8) = (220, 220, 220);
+pub(crate) const STATUS_BG: (u8, u8, u8) = (30, 30, 30);
+
+/// What the status line describes at one moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StatusInfo<'a> {
+    /// Position in the source's own timeline
+    pub position: Duration,
+    pub duration: Option<Duration>,
+    pub title: &'a str,
+    pub paused: bool,
+    /// Playback rate relative to the source frame rate
+    pub speed: f64,
+}
+
+/// Bottom status line with timecode and progress bar
+///
+/// Shown at start and after any key press, hidden again after a few quiet
+/// seconds; it stays up while paused. The row itself stays reserved so the
+/// video never jumps when the line hides.
+pub(crate) struct StatusLine {
+    last_input: Instant,
+}
+
+impl StatusLine {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_input: Instant::now(),
+        }
+    }
+
+    pub(crate) fn note_input(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    pub(crate) fn is_visible(&self, paused: bool) -> bool {
+        paused || self.last_input.elapsed() < STATUS_AUTO_HIDE
+    }
+
+    /// The row's text: the status when visible, blanks otherwise.
+    pub(crate) fn text(&self, info: &StatusInfo<'_>, cols: usize) -> String {
+        if self.is_visible(info.paused) {
+            format_status(info, cols)
+        } else {
+            " ".repeat(cols)
+        }
+    }
+}
+
+/// Lays out `▶ 01:02 / 03:04 [████░░░░] title` in exactly `cols` columns.
+pub(crate) fn format_status(info: &StatusInfo<'_>, cols: usize) -> String {
+    let state = if info.paused { "❚❚" } else { "▶" };
+    let time = match info.duration {
+        Some(duration) => format!(
+            "{} / {}",
+            format_timecode(info.position.min(duration)),
+            format_timecode(duration)
+        ),
+        None => format_timecode(info.position),
+    };
+    let speed = if (info.speed - 1.0).abs() > 0.005 {
+        format!(" {:.2}x", info.speed)
+    } else {
+        String::new()
+    };
+    let left = format!(" {} {}{} ", state, time, speed);
+
+    let left_width = text_width(&left);
+    let title_budget = cols.saturating_sub(left_width + 12).min(40);
+    let title = truncate(info.title, title_budget);
+    let right = if title.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", title)
+    };
+
+    let bar_width = cols.saturating_sub(left_width + text_width(&right) + 2);
+    let bar = match info.duration {
+        Some(duration) if bar_width > 0 && !duration.is_zero() => {
+            let ratio = (info.position.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0);
+            let filled = (ratio * bar_width as f64).round() as usize;
+            format!("[{}{}]", "█".repeat(filled), "░".repeat(bar_width - filled))
+        }
+        _ => " ".repeat(bar_width + 2),
+    };
+
+    let mut line = truncate(&format!("{}{}{}", left, bar, right), cols);
+    let width = text_width(&line);
+    line.push_str(&" ".repeat(cols.saturating_sub(width)));
+    line
+}
+
+pub(crate) fn format_timecode(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// `text` cut to at most `max_cols` terminal columns, ending in `…` when
+/// anything was dropped.
+fn truncate(text: &str, max_cols: usize) -> String {
+    if text_width(text) <= max_cols {
+        return text.to_string();
+    }
+    if max_cols == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for glyph in text.chars() {
+        let glyph_width = char_width(glyph);
+        if used + glyph_width + 1 > max_cols {
+            break;
+        }
+        truncated.push(glyph);
+        used += glyph_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(position_secs: u64, paused: bool) -> StatusInfo<'static> {
+        StatusInfo {
+            position: Duration::from_secs(position_secs),
+            duration: Some(Duration::from_secs(200)),
+            title: "clip",
+            paused,
+            speed: 1.0,
+        }
+    }
+
+    #[test]
+    fn timecode_adds_hours_only_when_needed() {
+        assert_eq!(format_timecode(Duration::from_secs(65)), "01:05");
+        assert_eq!(format_timecode(Duration::from_secs(3723)), "1:02:03");
+    }
+
+    #[test]
+    fn status_fills_the_row_with_time_bar_and_title() {
+        let line = format_status(&info(100, false), 60);
+
+        assert_eq!(line.chars().count(), 60);
+        assert!(line.starts_with(" ▶ 01:40 / 03:20 ["));
+        assert!(line.trim_end().ends_with("] clip"));
+        let filled = line.chars().filter(|c| *c == '█').count();
+        let empty = line.chars().filter(|c| *c == '░').count();
+        assert!(filled.abs_diff(empty) <= 1, "{} vs {}", filled, empty);
+    }
+
+    #[test]
+    fn status_shows_pause_and_speed() {
+        let mut paused = info(5, true);
+        paused.speed = 2.0;
+        let line = format_status(&paused, 60);
+
+        assert!(line.starts_with(" ❚❚ 00:05 / 03:20 2.00x ["));
+    }
+
+    #[test]
+    fn narrow_rows_are_truncated_not_overflowed() {
+        assert_eq!(format_status(&info(5, false), 8).chars().count(), 8);
+    }
+
+    #[test]
+    fn wide_glyph_titles_are_cut_by_columns() {
+        let mut wide = info(100, false);
+        wide.title = "배드 애플 그림자 예술 영상 모음집 고화질 버전 전체 재생";
+        for cols in [8, 30, 60, 80, 120] {
+            let line = format_status(&wide, cols);
+            assert_eq!(text_width(&line), cols, "{:?}", line);
+        }
+        assert!(format_status(&wide, 80).trim_end().ends_with('…'));
+    }
+
+    #[test]
+    fn paused_status_never_hides() {
+        let mut status = StatusLine::new();
+        status.last_input = Instant::now() - STATUS_AUTO_HIDE;
+
+        assert!(!status.is_visible(false));
+        assert!(status.is_visible(true));
+        assert_eq!(status.text(&info(5, false), 4), "    ");
+    }
+}
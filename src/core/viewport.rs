@@ -1,24 +1,159 @@
 use crate::core::render_budget::FrameBudgetPolicy;
+use crate::decoder::CropRect;
 use crate::renderer::RenderViewport;
+use anyhow::{bail, Result};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewportMode {
     Fullscreen,
     CinemaScope,
+    /// The source's own aspect ratio, letterboxed or pillarboxed
+    Original,
+    /// A fixed frame ratio; the source is cropped to fill it
+    Aspect(AspectRatio),
+    /// Fills the terminal; keys zoom into and pan around the source
+    ZoomPan,
+}
+
+impl ViewportMode {
+    /// Parses `fill`, `cinemascope`, `original`, `zoom`, a preset such as
+    /// `4:3`, `16:9`, `1.85` or `21:9`, or any `W:H` / decimal ratio.
+    pub fn parse(value: &str) -> Result<Self> {
+        Ok(match value.trim().to_ascii_lowercase().as_str() {
+            "fill" | "fullscreen" => Self::Fullscreen,
+            "cinemascope" | "scope" => Self::CinemaScope,
+            "original" | "source" => Self::Original,
+            "zoom" | "zoompan" => Self::ZoomPan,
+            ratio => Self::Aspect(AspectRatio::parse(ratio)?),
+        })
+    }
 }
 
 pub(crate) const CINEMASCOPE_ASPECT: f64 = 2.39;
+const MAX_ZOOM: f64 = 8.0;
+const ZOOM_STEP: f64 = 1.25;
+/// Pan distance per key press, as a share of the visible window
+const PAN_STEP: f64 = 0.1;
+
+/// Frame ratio as `width:height`, kept integral so modes stay comparable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AspectRatio {
+    pub fn parse(value: &str) -> Result<Self> {
+        let (width, height) = match value.split_once([':', '/', 'x']) {
+            Some((width, height)) => (width.trim().parse::<u32>()?, height.trim().parse::<u32>()?),
+            None => {
+                let ratio: f64 = value.trim().parse()?;
+                if !ratio.is_finite() || ratio <= 0.0 {
+                    bail!("aspect ratio must be positive: {}", value);
+                }
+                ((ratio * 1000.0).round() as u32, 1000)
+            }
+        };
+        if width == 0 || height == 0 {
+            bail!("aspect ratio must be positive: {}", value);
+        }
+        let divisor = gcd(width, height);
+        Ok(Self {
+            width: width / divisor,
+            height: height / divisor,
+        })
+    }
+
+    pub fn value(self) -> f64 {
+        f64::from(self.width) / f64::from(self.height)
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Crop window for `ViewportMode::ZoomPan`, centered on a point of the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ZoomPan {
+    zoom: f64,
+    center_x: f64,
+    center_y: f64,
+}
+
+impl ZoomPan {
+    pub(crate) fn new() -> Self {
+        Self {
+            zoom: 1.0,
+            center_x: 0.5,
+            center_y: 0.5,
+        }
+    }
+
+    pub(crate) fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * ZOOM_STEP).min(MAX_ZOOM);
+        self.clamp_center();
+    }
+
+    pub(crate) fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / ZOOM_STEP).max(1.0);
+        self.clamp_center();
+    }
+
+    /// Moves the window by `dx`/`dy` pan steps; positive is right/down.
+    pub(crate) fn pan(&mut self, dx: f64, dy: f64) {
+        let step = PAN_STEP / self.zoom;
+        self.center_x += dx * step;
+        self.center_y += dy * step;
+        self.clamp_center();
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub(crate) fn crop(self) -> CropRect {
+        let size = 1.0 / self.zoom;
+        CropRect::from_fractions(
+            self.center_x - size / 2.0,
+            self.center_y - size / 2.0,
+            size,
+            size,
+        )
+    }
+
+    fn clamp_center(&mut self) {
+        let half = 0.5 / self.zoom;
+        self.center_x = self.center_x.clamp(half, 1.0 - half);
+        self.center_y = self.center_y.clamp(half, 1.0 - half);
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ViewportLayout {
     pub terminal_cols: u16,
     pub terminal_rows: u16,
+    /// Bottom rows kept free of video, e.g. for the status line
+    pub reserved_rows: u16,
     pub offset_x: u16,
     pub offset_y: u16,
     pub pixel_width: u32,
     pub pixel_height: u32,
 }
 
+/// Terminal cells outside the video, in columns and rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PanelRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
 impl ViewportLayout {
     pub(crate) fn calculate(
         terminal_cols: u16,
@@ -29,63 +164,62 @@ impl ViewportLayout {
         budget_policy: FrameBudgetPolicy,
         source_aspect: f64,
         pixel_aspect_correction: f64,
+        reserved_rows: u16,
     ) -> Self {
         let terminal_cols = terminal_cols.max(1);
         let terminal_rows = terminal_rows.max(1);
+        let video_rows = terminal_rows.saturating_sub(reserved_rows).max(1);
 
         let max_pixel_width = terminal_cols as u32;
-        let max_pixel_height = (terminal_rows as u32).saturating_mul(2).max(2);
+        let max_pixel_height = (video_rows as u32).saturating_mul(2).max(2);
 
         let (pixel_width, pixel_height) = match viewport_mode {
-            ViewportMode::Fullscreen => budget_policy.apply_to_dimensions(
+            ViewportMode::Fullscreen | ViewportMode::ZoomPan => budget_policy.apply_to_dimensions(
                 requested_width
                     .map(|value| value.min(max_pixel_width).max(1))
                     .unwrap_or(max_pixel_width),
                 requested_height
                     .map(|value| value.min(max_pixel_height).max(2))
                     .unwrap_or(max_pixel_height),
-                viewport_mode,
             ),
-            ViewportMode::CinemaScope => {
-                let cinema_pixel_aspect =
-                    corrected_pixel_aspect(CINEMASCOPE_ASPECT, pixel_aspect_correction);
-                let fitted_width = max_pixel_width;
-                let fitted_height = make_even(
-                    ((fitted_width as f64 / cinema_pixel_aspect).floor() as u32)
-                        .min(max_pixel_height)
-                        .max(2),
-                );
-
-                let (bounded_width, bounded_height) = if fitted_height > max_pixel_height {
-                    let height = max_pixel_height;
-                    let width = ((height as f64 * cinema_pixel_aspect).floor() as u32)
-                        .min(max_pixel_width)
-                        .max(1);
-                    (width, height)
-                } else {
-                    (fitted_width, fitted_height)
-                };
-
-                let limit_width = requested_width
-                    .map(|value| value.min(bounded_width).max(1))
-                    .unwrap_or(bounded_width);
-                let limit_height = requested_height
-                    .map(|value| value.min(bounded_height).max(2))
-                    .unwrap_or(bounded_height);
-
-                let (width, height) = fit_aspect(limit_width, limit_height, cinema_pixel_aspect);
-                budget_policy.apply_to_dimensions(width, height, viewport_mode)
-            }
+            ViewportMode::CinemaScope => fitted_to_aspect(
+                CINEMASCOPE_ASPECT,
+                max_pixel_width,
+                max_pixel_height,
+                requested_width,
+                requested_height,
+                budget_policy,
+                pixel_aspect_correction,
+            ),
+            ViewportMode::Original => fitted_to_aspect(
+                source_aspect,
+                max_pixel_width,
+                max_pixel_height,
+                requested_width,
+                requested_height,
+                budget_policy,
+                pixel_aspect_correction,
+            ),
+            ViewportMode::Aspect(ratio) => fitted_to_aspect(
+                ratio.value(),
+                max_pixel_width,
+                max_pixel_height,
+                requested_width,
+                requested_height,
+                budget_policy,
+                pixel_aspect_correction,
+            ),
         };
 
         let char_width = pixel_width as u16;
         let char_height = (pixel_height / 2) as u16;
         let offset_x = (terminal_cols.saturating_sub(char_width)) / 2;
-        let offset_y = (terminal_rows.saturating_sub(char_height)) / 2;
+        let offset_y = (video_rows.saturating_sub(char_height)) / 2;
 
         Self {
             terminal_cols,
             terminal_rows,
+            reserved_rows,
             offset_x,
             offset_y,
             pixel_width,
@@ -96,6 +230,7 @@ impl ViewportLayout {
     pub(crate) fn recentered_for_terminal(self, terminal_cols: u16, terminal_rows: u16) -> Self {
         let terminal_cols = terminal_cols.max(1);
         let terminal_rows = terminal_rows.max(1);
+        let video_rows = terminal_rows.saturating_sub(self.reserved_rows).max(1);
         let char_width = self.pixel_width as u16;
         let char_height = (self.pixel_height / 2) as u16;
 
@@ -103,11 +238,69 @@ impl ViewportLayout {
             terminal_cols,
             terminal_rows,
             offset_x: (terminal_cols.saturating_sub(char_width)) / 2,
-            offset_y: (terminal_rows.saturating_sub(char_height)) / 2,
+            offset_y: (video_rows.saturating_sub(char_height)) / 2,
             ..self
         }
     }
 
+    /// First reserved row below the video; `None` when nothing is reserved
+    /// or the terminal is too small to spare it.
+    pub(crate) fn status_row(self) -> Option<u16> {
+        (self.reserved_rows > 0 && self.terminal_rows > self.reserved_rows)
+            .then(|| self.terminal_rows - self.reserved_rows)
+    }
+
+    /// Letterbox rows above the video; `None` when the picture starts at
+    /// the top row.
+    pub(crate) fn top_band(self) -> Option<PanelRect> {
+        (self.offset_y > 0).then_some(PanelRect {
+            x: 0,
+            y: 0,
+            width: self.terminal_cols,
+            height: self.offset_y,
+        })
+    }
+
+    /// Letterbox rows between the video and any reserved rows.
+    pub(crate) fn bottom_band(self) -> Option<PanelRect> {
+        let video_end = self
+            .offset_y
+            .saturating_add((self.pixel_height / 2).min(u32::from(u16::MAX)) as u16);
+        let height = self
+            .terminal_rows
+            .saturating_sub(self.reserved_rows)
+            .saturating_sub(video_end);
+        (height > 0).then_some(PanelRect {
+            x: 0,
+            y: video_end,
+            width: self.terminal_cols,
+            height,
+        })
+    }
+
+    /// The wider letterbox bar beside the video, for a side panel at least
+    /// `min_width` columns wide; a one-column gap separates it from the
+    /// picture. `None` when the video spans the terminal.
+    pub(crate) fn side_panel(self, min_width: u16) -> Option<PanelRect> {
+        let video_cols = self.pixel_width.min(u32::from(u16::MAX)) as u16;
+        let left = self.offset_x;
+        let right = self
+            .terminal_cols
+            .saturating_sub(self.offset_x.saturating_add(video_cols));
+        let (x, width) = if right >= left {
+            (self.terminal_cols - right + 1, right.saturating_sub(1))
+        } else {
+            (0, left.saturating_sub(1))
+        };
+        let height = self.terminal_rows.saturating_sub(self.reserved_rows);
+        (width >= min_width.max(1) && height > 0).then_some(PanelRect {
+            x,
+            y: 0,
+            width,
+            height,
+        })
+    }
+
     pub(crate) fn as_render_viewport(self) -> RenderViewport {
         RenderViewport {
             offset_x: self.offset_x,
@@ -120,6 +313,50 @@ impl ViewportLayout {
     }
 }
 
+/// Largest frame of `aspect` that fits the terminal and any requested size.
+fn fitted_to_aspect(
+    aspect: f64,
+    max_pixel_width: u32,
+    max_pixel_height: u32,
+    requested_width: Option<u32>,
+    requested_height: Option<u32>,
+    budget_policy: FrameBudgetPolicy,
+    pixel_aspect_correction: f64,
+) -> (u32, u32) {
+    let aspect = if aspect.is_finite() && aspect > 0.0 {
+        aspect
+    } else {
+        16.0 / 9.0
+    };
+    let pixel_aspect = corrected_pixel_aspect(aspect, pixel_aspect_correction);
+    let fitted_width = max_pixel_width;
+    let fitted_height = make_even(
+        ((fitted_width as f64 / pixel_aspect).floor() as u32)
+            .min(max_pixel_height)
+            .max(2),
+    );
+
+    let (bounded_width, bounded_height) = if fitted_height > max_pixel_height {
+        let height = max_pixel_height;
+        let width = ((height as f64 * pixel_aspect).floor() as u32)
+            .min(max_pixel_width)
+            .max(1);
+        (width, height)
+    } else {
+        (fitted_width, fitted_height)
+    };
+
+    let limit_width = requested_width
+        .map(|value| value.min(bounded_width).max(1))
+        .unwrap_or(bounded_width);
+    let limit_height = requested_height
+        .map(|value| value.min(bounded_height).max(2))
+        .unwrap_or(bounded_height);
+
+    let (width, height) = fit_aspect(limit_width, limit_height, pixel_aspect);
+    budget_policy.apply_to_dimensions(width, height)
+}
+
 pub(crate) fn corrected_pixel_aspect(visual_aspect: f64, pixel_aspect_correction: f64) -> f64 {
     let correction = if pixel_aspect_correction.is_finite() && pixel_aspect_correction > 0.0 {
         pixel_aspect_correction
@@ -163,6 +400,95 @@ mod tests {
     use crate::core::render_budget::{FrameBudgetPolicy, RenderQuality};
     use crate::renderer::{ActiveRenderBackend, DisplayMode};
 
+    fn full_quality() -> FrameBudgetPolicy {
+        FrameBudgetPolicy::for_backend(
+            DisplayMode::Rgb,
+            ActiveRenderBackend::AnsiRgb,
+            RenderQuality::Full,
+        )
+    }
+
+    #[test]
+    fn modes_parse_names_presets_and_custom_ratios() {
+        assert_eq!(
+            ViewportMode::parse("fill").unwrap(),
+            ViewportMode::Fullscreen
+        );
+        assert_eq!(
+            ViewportMode::parse("Original").unwrap(),
+            ViewportMode::Original
+        );
+        assert_eq!(ViewportMode::parse("zoom").unwrap(), ViewportMode::ZoomPan);
+        assert_eq!(
+            ViewportMode::parse("16:9").unwrap(),
+            ViewportMode::Aspect(AspectRatio {
+                width: 16,
+                height: 9
+            })
+        );
+        assert_eq!(
+            ViewportMode::parse("1.85").unwrap(),
+            ViewportMode::Aspect(AspectRatio {
+                width: 37,
+                height: 20
+            })
+        );
+        assert_eq!(
+            ViewportMode::parse("8:6").unwrap(),
+            ViewportMode::Aspect(AspectRatio {
+                width: 4,
+                height: 3
+            })
+        );
+        assert!(ViewportMode::parse("0:3").is_err());
+        assert!(ViewportMode::parse("wide").is_err());
+    }
+
+    #[test]
+    fn original_and_fixed_ratio_layouts_keep_their_shape() {
+        for (mode, aspect) in [
+            (ViewportMode::Original, 4.0 / 3.0),
+            (ViewportMode::parse("21:9").unwrap(), 21.0 / 9.0),
+        ] {
+            let layout = ViewportLayout::calculate(
+                240,
+                68,
+                mode,
+                None,
+                None,
+                full_quality(),
+                4.0 / 3.0,
+                1.0,
+                0,
+            );
+            let ratio = layout.pixel_width as f64 / layout.pixel_height as f64;
+            assert!((ratio - aspect).abs() < 0.05, "{:?}: {}", mode, ratio);
+        }
+    }
+
+    #[test]
+    fn zoom_window_stays_inside_the_source() {
+        let mut zoom = ZoomPan::new();
+        assert!(zoom.crop().is_full());
+
+        zoom.zoom_in();
+        zoom.zoom_in();
+        let crop = zoom.crop();
+        assert_eq!((crop.width, crop.height), (6_400, 6_400));
+        assert_eq!((crop.x, crop.y), (1_800, 1_800));
+
+        for _ in 0..50 {
+            zoom.pan(-1.0, 1.0);
+        }
+        let crop = zoom.crop();
+        assert_eq!((crop.x, crop.y), (0, CropRect::UNIT - crop.height));
+
+        for _ in 0..20 {
+            zoom.zoom_out();
+        }
+        assert!(zoom.crop().is_full());
+    }
+
     #[test]
     fn cinema_layout_keeps_cinemascope_ratio() {
         let layout = ViewportLayout::calculate(
@@ -178,6 +504,7 @@ mod tests {
             ),
             16.0 / 9.0,
             1.0,
+            0,
         );
         let ratio = layout.pixel_width as f64 / layout.pixel_height as f64;
         assert!((ratio - CINEMASCOPE_ASPECT).abs() < 0.05);
@@ -199,6 +526,7 @@ mod tests {
             ),
             16.0 / 9.0,
             correction,
+            0,
         );
 
         let visual_ratio = (layout.pixel_width as f64 / layout.pixel_height as f64) * correction;
@@ -220,6 +548,7 @@ mod tests {
             ),
             16.0 / 9.0,
             1.0,
+            0,
         );
         assert_eq!(layout.pixel_width, 120);
         assert_eq!(layout.pixel_height, 80);
@@ -240,6 +569,7 @@ mod tests {
             ),
             16.0 / 9.0,
             1.0,
+            0,
         );
         assert_eq!(layout.pixel_width, 320);
         assert_eq!(layout.pixel_height, 240);
@@ -247,6 +577,111 @@ mod tests {
         assert_eq!(layout.offset_y, 0);
     }
 
+    #[test]
+    fn reserved_rows_are_kept_free_of_video() {
+        let layout = ViewportLayout::calculate(
+            320,
+            120,
+            ViewportMode::Fullscreen,
+            None,
+            None,
+            FrameBudgetPolicy::for_backend(
+                DisplayMode::Rgb,
+                ActiveRenderBackend::AnsiRgb,
+                RenderQuality::Full,
+            ),
+            16.0 / 9.0,
+            1.0,
+            1,
+        );
+        assert_eq!(layout.pixel_height, 238);
+        assert_eq!(layout.status_row(), Some(119));
+
+        let recentered = layout.recentered_for_terminal(320, 130);
+        assert_eq!(recentered.offset_y, 5);
+        assert_eq!(recentered.status_row(), Some(129));
+    }
+
+    #[test]
+    fn bands_cover_the_rows_above_and_below_the_video() {
+        let layout = ViewportLayout {
+            terminal_cols: 80,
+            terminal_rows: 30,
+            reserved_rows: 1,
+            offset_x: 0,
+            offset_y: 4,
+            pixel_width: 80,
+            pixel_height: 40,
+        };
+        assert_eq!(
+            layout.top_band(),
+            Some(PanelRect {
+                x: 0,
+                y: 0,
+                width: 80,
+                height: 4,
+            })
+        );
+        assert_eq!(
+            layout.bottom_band(),
+            Some(PanelRect {
+                x: 0,
+                y: 24,
+                width: 80,
+                height: 5,
+            })
+        );
+
+        let filled = ViewportLayout {
+            offset_y: 0,
+            pixel_height: 58,
+            ..layout
+        };
+        assert_eq!(filled.top_band(), None);
+        assert_eq!(filled.bottom_band(), None);
+    }
+
+    #[test]
+    fn side_panel_uses_the_wider_letterbox_bar() {
+        let layout = ViewportLayout {
+            terminal_cols: 100,
+            terminal_rows: 30,
+            reserved_rows: 1,
+            offset_x: 20,
+            offset_y: 0,
+            pixel_width: 60,
+            pixel_height: 58,
+        };
+        assert_eq!(
+            layout.side_panel(16),
+            Some(PanelRect {
+                x: 81,
+                y: 0,
+                width: 19,
+                height: 29,
+            })
+        );
+        assert_eq!(layout.side_panel(20), None);
+
+        let pillarboxed_left = ViewportLayout {
+            offset_x: 30,
+            ..layout
+        };
+        assert_eq!(
+            pillarboxed_left
+                .side_panel(16)
+                .map(|panel| (panel.x, panel.width)),
+            Some((0, 29))
+        );
+
+        let full_width = ViewportLayout {
+            offset_x: 0,
+            pixel_width: 100,
+            ..layout
+        };
+        assert_eq!(full_width.side_panel(1), None);
+    }
+
     #[test]
     fn fullscreen_layout_ignores_non_16_9_source_aspect() {
         let layout = ViewportLayout::calculate(
@@ -262,6 +697,7 @@ mod tests {
             ),
             4.0 / 3.0,
             1.0,
+            0,
         );
         assert_eq!(layout.pixel_width, 320);
         assert_eq!(layout.pixel_height, 240);
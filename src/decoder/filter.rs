@@ -0,0 +1,547 @@
+use super::scale::Scaler;
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::path::Path;
+
+/// One pre-render adjustment of the RGB canvas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Offset in fractions of full scale, `-1.0..=1.0`
+    Brightness(f32),
+    /// Gain around mid-grey; `1.0` is unchanged
+    Contrast(f32),
+    /// `out = in^(1/gamma)`, so values above 1 lift the shadows
+    Gamma(f32),
+    /// `0.0` is greyscale, `1.0` unchanged, higher oversaturates
+    Saturation(f32),
+    /// Unsharp mask strength against a 3x3 blur
+    Sharpen(f32),
+    /// Sobel gradient magnitude as grey
+    Edges,
+    Threshold(Threshold),
+    Invert,
+}
+
+/// Cut-off of the black/white threshold filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    Level(u8),
+    /// Otsu's method on the frame's own luma histogram
+    Otsu,
+}
+
+impl Filter {
+    /// Parses `name` or `name=value`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, value) = match spec.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (spec.trim(), None),
+        };
+        let number = |default: Option<f32>| -> Result<f32> {
+            match (value, default) {
+                (Some(value), _) => value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| anyhow!("{}: '{}' is not a number", name, value)),
+                (None, Some(default)) => Ok(default),
+                (None, None) => bail!("{} needs a value, e.g. {}=1.2", name, name),
+            }
+        };
+
+        let filter = match name.to_ascii_lowercase().as_str() {
+            "brightness" => {
+                let value = number(None)?;
+                if !(-1.0..=1.0).contains(&value) {
+                    bail!("brightness must be between -1 and 1");
+                }
+                Self::Brightness(value)
+            }
+            "contrast" => Self::Contrast(non_negative(name, number(None)?)?),
+            "gamma" => {
+                let value = number(None)?;
+                if value <= 0.0 {
+                    bail!("gamma must be greater than 0");
+                }
+                Self::Gamma(value)
+            }
+            "saturation" => Self::Saturation(non_negative(name, number(None)?)?),
+            "sharpen" | "unsharp" => Self::Sharpen(non_negative(name, number(Some(1.0))?)?),
+            "edges" | "sobel" => Self::Edges,
+            "threshold" => match value {
+                None | Some("otsu") | Some("auto") => Self::Threshold(Threshold::Otsu),
+                Some(level) => {
+                    Self::Threshold(Threshold::Level(level.parse().map_err(|_| {
+                        anyhow!("threshold must be 0-255 or 'otsu', got '{}'", level)
+                    })?))
+                }
+            },
+            "invert" | "negate" => Self::Invert,
+            other => bail!(
+                "unknown filter '{}'; expected brightness, contrast, gamma, saturation, \
+                 sharpen, edges, threshold or invert",
+                other
+            ),
+        };
+        if value.is_some() && matches!(filter, Self::Edges | Self::Invert) {
+            bail!("{} takes no value", name);
+        }
+        Ok(filter)
+    }
+
+    /// How the filter runs; per-channel mappings become lookup tables.
+    fn stage(self) -> Stage {
+        match self {
+            Self::Brightness(offset) => channel_table(|v| v + offset * 255.0),
+            Self::Contrast(gain) => channel_table(|v| (v - 127.5) * gain + 127.5),
+            Self::Gamma(gamma) => channel_table(|v| 255.0 * (v / 255.0).powf(1.0 / gamma)),
+            Self::Invert => channel_table(|v| 255.0 - v),
+            Self::Saturation(amount) => Stage::Saturation(amount),
+            Self::Sharpen(amount) => Stage::Sharpen(amount),
+            Self::Edges => Stage::Edges,
+            Self::Threshold(threshold) => Stage::Threshold(threshold),
+        }
+    }
+}
+
+fn channel_table(map: impl Fn(f32) -> f32) -> Stage {
+    let mut table = Box::new([0u8; 256]);
+    for (value, entry) in table.iter_mut().enumerate() {
+        *entry = clamp_channel(map(value as f32));
+    }
+    Stage::Table(table)
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Brightness(value) => write!(f, "brightness={}", value),
+            Self::Contrast(value) => write!(f, "contrast={}", value),
+            Self::Gamma(value) => write!(f, "gamma={}", value),
+            Self::Saturation(value) => write!(f, "saturation={}", value),
+            Self::Sharpen(value) => write!(f, "sharpen={}", value),
+            Self::Edges => write!(f, "edges"),
+            Self::Threshold(Threshold::Otsu) => write!(f, "threshold=otsu"),
+            Self::Threshold(Threshold::Level(level)) => write!(f, "threshold={}", level),
+            Self::Invert => write!(f, "invert"),
+        }
+    }
+}
+
+fn non_negative(name: &str, value: f32) -> Result<f32> {
+    if value < 0.0 {
+        bail!("{} must not be negative", name);
+    }
+    Ok(value)
+}
+
+/// What actually runs; neighbouring per-channel filters share one table.
+#[derive(Debug, Clone, PartialEq)]
+enum Stage {
+    Table(Box<[u8; 256]>),
+    Saturation(f32),
+    Sharpen(f32),
+    Edges,
+    Threshold(Threshold),
+}
+
+/// Ordered filters applied to every canvas after it is scaled
+///
+/// Runs on the decoder thread over the visible part of the canvas only, so
+/// letterbox bars stay black and the cost scales with the output size.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterChain {
+    filters: Vec<Filter>,
+    stages: Vec<Stage>,
+    /// `scaler=NAME` from the spec, used unless `--scaler` is given
+    scaler: Option<Scaler>,
+}
+
+impl FilterChain {
+    pub fn new(filters: Vec<Filter>) -> Self {
+        let mut stages = Vec::new();
+        for filter in &filters {
+            // Neighbouring lookup tables fold into one.
+            match (filter.stage(), stages.last_mut()) {
+                (Stage::Table(next), Some(Stage::Table(table))) => {
+                    for entry in table.iter_mut() {
+                        *entry = next[usize::from(*entry)];
+                    }
+                }
+                (stage, _) => stages.push(stage),
+            }
+        }
+        Self {
+            filters,
+            stages,
+            scaler: None,
+        }
+    }
+
+    /// `contrast=1.3,gamma=1.2,sharpen` — commas or whitespace between
+    /// filters, applied left to right. A `scaler=NAME` entry picks the
+    /// resampling filter instead; the last one wins.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut scaler = None;
+        let mut filters = Vec::new();
+        for part in spec
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+        {
+            match part.split_once('=') {
+                Some((key, name)) if key.eq_ignore_ascii_case("scaler") => {
+                    scaler = Some(Scaler::parse(name)?);
+                }
+                _ => filters.push(Filter::parse(part)?),
+            }
+        }
+        Ok(Self {
+            scaler,
+            ..Self::new(filters)
+        })
+    }
+
+    /// A filter file: the same syntax as `parse`, with `#` comments.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read filter file {}", path.display()))?;
+        let spec: Vec<&str> = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .collect();
+        Self::parse(&spec.join("\n")).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// `--filters` value: a spec, or `@path` to a filter file.
+    pub fn from_arg(arg: &str) -> Result<Self> {
+        match arg.strip_prefix('@') {
+            Some(path) => Self::load(Path::new(path)),
+            None => Self::parse(arg),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    pub fn scaler(&self) -> Option<Scaler> {
+        self.scaler
+    }
+
+    /// Filters the `area` of a packed RGB canvas `canvas_width` pixels wide.
+    ///
+    /// `scratch` is reused between frames by the spatial filters.
+    pub(crate) fn apply(
+        &self,
+        canvas: &mut [u8],
+        canvas_width: u32,
+        area: CanvasArea,
+        scratch: &mut Vec<u8>,
+    ) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let mut image = AreaMut {
+            canvas,
+            stride: canvas_width as usize * 3,
+            area,
+        };
+        for stage in &self.stages {
+            match stage {
+                Stage::Table(table) => image.for_each_row(|row| {
+                    for value in row {
+                        *value = table[*value as usize];
+                    }
+                }),
+                Stage::Saturation(amount) => image.for_each_row(|row| {
+                    for pixel in row.chunks_exact_mut(3) {
+                        let grey = f32::from(luma(pixel));
+                        for value in pixel {
+                            *value = clamp_channel(grey + (f32::from(*value) - grey) * amount);
+                        }
+                    }
+                }),
+                Stage::Sharpen(amount) => sharpen(&mut image, *amount, scratch),
+                Stage::Edges => sobel(&mut image, scratch),
+                Stage::Threshold(threshold) => {
+                    let level = match threshold {
+                        Threshold::Level(level) => *level,
+                        Threshold::Otsu => otsu_level(&image),
+                    };
+                    image.for_each_row(|row| {
+                        for pixel in row.chunks_exact_mut(3) {
+                            let value = if luma(pixel) > level { 255 } else { 0 };
+                            pixel.fill(value);
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for FilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, filter) in self.filters.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pixel rectangle of a canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CanvasArea {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct AreaMut<'a> {
+    canvas: &'a mut [u8],
+    /// Bytes per canvas row
+    stride: usize,
+    area: CanvasArea,
+}
+
+impl AreaMut<'_> {
+    fn row_range(&self, row: usize) -> std::ops::Range<usize> {
+        let start = (self.area.y as usize + row) * self.stride + self.area.x as usize * 3;
+        start..start + self.area.width as usize * 3
+    }
+
+    fn row(&self, row: usize) -> &[u8] {
+        &self.canvas[self.row_range(row)]
+    }
+
+    fn for_each_row(&mut self, mut f: impl FnMut(&mut [u8])) {
+        for row in 0..self.area.height as usize {
+            let range = self.row_range(row);
+            f(&mut self.canvas[range]);
+        }
+    }
+
+    /// Copies the area into `scratch` as tightly packed RGB.
+    fn copy_to(&self, scratch: &mut Vec<u8>) {
+        scratch.clear();
+        for row in 0..self.area.height as usize {
+            scratch.extend_from_slice(self.row(row));
+        }
+    }
+}
+
+/// Rec. 601 luma in integer math
+fn luma(pixel: &[u8]) -> u8 {
+    ((77 * u32::from(pixel[0]) + 150 * u32::from(pixel[1]) + 29 * u32::from(pixel[2])) >> 8) as u8
+}
+
+fn clamp_channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// `value + amount * (value - blur3x3)` per channel, edges clamped.
+fn sharpen(image: &mut AreaMut<'_>, amount: f32, scratch: &mut Vec<u8>) {
+    image.copy_to(scratch);
+    let (width, height) = (image.area.width as usize, image.area.height as usize);
+    let source = &*scratch;
+    for y in 0..height {
+        let range = image.row_range(y);
+        let row = &mut image.canvas[range];
+        for x in 0..width {
+            for channel in 0..3 {
+                let mut sum = 0u32;
+                for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                        sum += u32::from(source[(ny * width + nx) * 3 + channel]);
+                    }
+                }
+                let count = ((y + 1).min(height - 1) - y.saturating_sub(1) + 1)
+                    * ((x + 1).min(width - 1) - x.saturating_sub(1) + 1);
+                let value = f32::from(source[(y * width + x) * 3 + channel]);
+                let blur = sum as f32 / count as f32;
+                row[x * 3 + channel] = clamp_channel(value + amount * (value - blur));
+            }
+        }
+    }
+}
+
+/// Sobel gradient magnitude of the luma, written back as grey.
+fn sobel(image: &mut AreaMut<'_>, scratch: &mut Vec<u8>) {
+    let (width, height) = (image.area.width as usize, image.area.height as usize);
+    scratch.clear();
+    for row in 0..height {
+        let range = image.row_range(row);
+        scratch.extend(image.canvas[range].chunks_exact(3).map(luma));
+    }
+    let source = &*scratch;
+    let at = |x: usize, y: usize, dx: isize, dy: isize| -> i32 {
+        let x = x.saturating_add_signed(dx).min(width - 1);
+        let y = y.saturating_add_signed(dy).min(height - 1);
+        i32::from(source[y * width + x])
+    };
+    for y in 0..height {
+        let range = image.row_range(y);
+        let row = &mut image.canvas[range];
+        for x in 0..width {
+            let gx = at(x, y, 1, -1) + 2 * at(x, y, 1, 0) + at(x, y, 1, 1)
+                - at(x, y, -1, -1)
+                - 2 * at(x, y, -1, 0)
+                - at(x, y, -1, 1);
+            let gy = at(x, y, -1, 1) + 2 * at(x, y, 0, 1) + at(x, y, 1, 1)
+                - at(x, y, -1, -1)
+                - 2 * at(x, y, 0, -1)
+                - at(x, y, 1, -1);
+            let magnitude = ((gx * gx + gy * gy) as f32).sqrt();
+            row[x * 3..x * 3 + 3].fill(clamp_channel(magnitude));
+        }
+    }
+}
+
+/// Level that best separates the area's luma histogram into two classes.
+fn otsu_level(image: &AreaMut<'_>) -> u8 {
+    let mut histogram = [0u64; 256];
+    for row in 0..image.area.height as usize {
+        for pixel in image.row(row).chunks_exact(3) {
+            histogram[luma(pixel) as usize] += 1;
+        }
+    }
+    let total: u64 = histogram.iter().sum();
+    let weighted_total: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, count)| level as f64 * *count as f64)
+        .sum();
+
+    let (mut best_level, mut best_variance) = (127u8, -1.0);
+    let (mut below, mut weighted_below) = (0u64, 0.0);
+    for (level, count) in histogram.iter().enumerate() {
+        below += count;
+        weighted_below += level as f64 * *count as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let mean_below = weighted_below / below as f64;
+        let mean_above = (weighted_total - weighted_below) / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_level = level as u8;
+        }
+    }
+    best_level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full(width: u32, height: u32) -> CanvasArea {
+        CanvasArea {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn specs_parse_in_order_and_reject_bad_values() {
+        let chain =
+            FilterChain::parse("contrast=1.5, gamma=2 sharpen,threshold=otsu,invert").unwrap();
+        assert_eq!(
+            chain.filters(),
+            [
+                Filter::Contrast(1.5),
+                Filter::Gamma(2.0),
+                Filter::Sharpen(1.0),
+                Filter::Threshold(Threshold::Otsu),
+                Filter::Invert
+            ]
+        );
+        assert_eq!(
+            chain.to_string(),
+            "contrast=1.5,gamma=2,sharpen=1,threshold=otsu,invert"
+        );
+
+        assert!(FilterChain::parse("gamma=0").is_err());
+        assert!(FilterChain::parse("contrast").is_err());
+        assert!(FilterChain::parse("threshold=300").is_err());
+        assert!(FilterChain::parse("invert=1").is_err());
+        assert!(FilterChain::parse("blur").is_err());
+    }
+
+    #[test]
+    fn specs_can_pick_the_scaler() {
+        let chain = FilterChain::parse("scaler=nearest, contrast=1.2 SCALER=Catmull-Rom").unwrap();
+        assert_eq!(chain.filters(), [Filter::Contrast(1.2)]);
+        assert_eq!(chain.scaler(), Some(Scaler::CatmullRom));
+        assert_eq!(FilterChain::parse("invert").unwrap().scaler(), None);
+        assert!(FilterChain::parse("scaler=bicubic").is_err());
+    }
+
+    #[test]
+    fn per_channel_filters_share_one_table() {
+        let chain = FilterChain::parse("brightness=0.1,contrast=2,saturation=0,invert").unwrap();
+        assert_eq!(chain.stages.len(), 3);
+
+        let mut canvas = vec![100, 150, 200];
+        chain.apply(&mut canvas, 1, full(1, 1), &mut Vec::new());
+        // (125, 225, 255) after the table, grey 198, inverted.
+        assert_eq!(canvas, [57, 57, 57]);
+    }
+
+    #[test]
+    fn otsu_splits_a_two_tone_frame_and_leaves_the_letterbox_alone() {
+        // 4x1 canvas, area is the middle two pixels: dark grey and light grey.
+        let mut canvas = vec![0, 0, 0, 60, 60, 60, 190, 190, 190, 0, 0, 0];
+        let chain = FilterChain::parse("threshold").unwrap();
+        let area = CanvasArea {
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 1,
+        };
+        chain.apply(&mut canvas, 4, area, &mut Vec::new());
+        assert_eq!(canvas, [0, 0, 0, 0, 0, 0, 255, 255, 255, 0, 0, 0]);
+
+        let mut canvas = vec![60, 60, 60, 190, 190, 190];
+        FilterChain::parse("invert,threshold=128").unwrap().apply(
+            &mut canvas,
+            2,
+            full(2, 1),
+            &mut Vec::new(),
+        );
+        assert_eq!(canvas, [255, 255, 255, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sobel_marks_edges_and_sharpen_steepens_them() {
+        // Left column black, right two white: the edge lights up, the far
+        // column stays black.
+        let mut canvas = Vec::new();
+        for _ in 0..3 {
+            canvas.extend_from_slice(&[0, 0, 0, 255, 255, 255, 255, 255, 255]);
+        }
+        FilterChain::parse("edges")
+            .unwrap()
+            .apply(&mut canvas, 3, full(3, 3), &mut Vec::new());
+        assert!(canvas[3] == 255 && canvas[6] == 0);
+
+        let mut canvas = Vec::new();
+        for _ in 0..3 {
+            canvas.extend_from_slice(&[100, 100, 100, 150, 150, 150, 150, 150, 150]);
+        }
+        let chain = FilterChain::parse("sharpen=2").unwrap();
+        chain.apply(&mut canvas, 3, full(3, 3), &mut Vec::new());
+        assert!(canvas[0] < 100 && canvas[3] > 150 && canvas[6] == 150);
+    }
+}
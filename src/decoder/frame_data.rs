@@ -1,3 +1,4 @@
+use super::pool::BufferPool;
 use std::time::Duration;
 
 /// Frame data structure for video frames
@@ -7,6 +8,7 @@ pub struct FrameData {
     pub width: u32,
     pub height: u32,
     pub timestamp: Duration,
+    pool: Option<BufferPool>,
 }
 
 impl FrameData {
@@ -16,6 +18,21 @@ impl FrameData {
             width,
             height,
             timestamp,
+            pool: None,
+        }
+    }
+
+    /// Returns the buffer to `pool` once the frame is dropped.
+    pub fn recycled_by(mut self, pool: BufferPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+}
+
+impl Drop for FrameData {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.put(std::mem::take(&mut self.buffer));
         }
     }
 }
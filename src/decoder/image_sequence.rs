@@ -0,0 +1,473 @@
+use super::filter::FilterChain;
+use super::frame_data::FrameData;
+use super::pump::{spawn_frame_pump, FrameReader, ReadOutcome};
+use super::scale::{FrameScaler, PixelOrder, RenderTarget, ScaleMode, Scaler, SourceImage};
+use super::source::{title_from_path, DecodeTiming, FrameSource, SourceFrame, SourceMetadata};
+use crate::shared::constants;
+use crate::utils::file_utils::has_allowed_extension;
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_channel::Sender;
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, RgbImage};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Browsers treat tiny GIF delays as "unset" and fall back to 100ms; do the same.
+const MIN_GIF_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_GIF_DELAY: Duration = Duration::from_millis(100);
+/// A still image re-sends its frame this often even without a resize so the
+/// decoder thread notices when playback has ended.
+const STILL_KEEPALIVE: Duration = Duration::from_secs(1);
+
+enum ImageFrames {
+    /// Files decoded lazily, one per frame, at a fixed input fps
+    Files { paths: Vec<PathBuf>, next: usize },
+    /// Fully decoded animation frames with their presentation times
+    Animation {
+        frames: Vec<(Duration, RgbImage)>,
+        duration: Duration,
+        next: usize,
+    },
+    /// A single image shown until playback stops
+    Still {
+        image: RgbImage,
+        shown_at: Option<Instant>,
+        last_sent: Option<(RenderTarget, Instant)>,
+    },
+}
+
+/// Frame source for directories/globs of images, single stills and animated GIFs
+pub struct ImageSequenceDecoder {
+    frames: ImageFrames,
+    fps: f64,
+    source_width: u32,
+    source_height: u32,
+    scale_mode: ScaleMode,
+    target: Arc<RwLock<RenderTarget>>,
+    scaler: FrameScaler,
+    title: Option<String>,
+}
+
+impl ImageSequenceDecoder {
+    /// Returns `None` when `path` does not look like image input.
+    pub fn open(
+        path: &Path,
+        target: Arc<RwLock<RenderTarget>>,
+        scale_mode: ScaleMode,
+        input_fps: Option<f64>,
+    ) -> Result<Option<Self>> {
+        let input_fps = input_fps
+            .filter(|fps| fps.is_finite() && *fps > 0.0)
+            .unwrap_or(constants::DEFAULT_IMAGE_SEQUENCE_FPS);
+
+        let (frames, fps) = if path.is_dir() {
+            (files_frames(list_image_files(path)?), input_fps)
+        } else if frames_are_files(path) {
+            (files_frames(glob_image_files(path)?), input_fps)
+        } else if has_allowed_extension(path, constants::ANIMATED_IMAGE_EXTENSIONS) {
+            let (frames, duration) = decode_gif(path)?;
+            let fps = average_fps(frames.len(), duration).unwrap_or(input_fps);
+            (
+                ImageFrames::Animation {
+                    frames,
+                    duration,
+                    next: 0,
+                },
+                fps,
+            )
+        } else if has_allowed_extension(path, constants::IMAGE_EXTENSIONS) {
+            let image = open_rgb(path)?;
+            (
+                ImageFrames::Still {
+                    image,
+                    shown_at: None,
+                    last_sent: None,
+                },
+                input_fps,
+            )
+        } else {
+            return Ok(None);
+        };
+
+        let (source_width, source_height) = match &frames {
+            ImageFrames::Files { paths, .. } => image::image_dimensions(&paths[0])
+                .with_context(|| format!("Failed to read {}", paths[0].display()))?,
+            ImageFrames::Animation { frames, .. } => frames
+                .first()
+                .map(|(_, image)| image.dimensions())
+                .ok_or_else(|| anyhow!("GIF has no frames: {}", path.display()))?,
+            ImageFrames::Still { image, .. } => image.dimensions(),
+        };
+
+        crate::utils::logger::info(&format!(
+            "image input opened: path={} kind={} size={}x{} fps={:.2}",
+            path.display(),
+            frames.kind(),
+            source_width,
+            source_height,
+            fps
+        ));
+
+        Ok(Some(Self {
+            frames,
+            fps,
+            source_width: source_width.max(1),
+            source_height: source_height.max(1),
+            scale_mode,
+            target,
+            scaler: FrameScaler::new(),
+            title: sequence_title(path),
+        }))
+    }
+
+    fn current_target(&self) -> Result<RenderTarget> {
+        self.target
+            .read()
+            .map(|guard| *guard)
+            .map_err(|_| anyhow!("render target lock poisoned"))
+    }
+}
+
+impl FrameSource for ImageSequenceDecoder {
+    fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    fn source_aspect_ratio(&self) -> f64 {
+        self.source_width as f64 / self.source_height as f64
+    }
+
+    fn metadata(&self) -> SourceMetadata {
+        let (frame_count, duration) = match &self.frames {
+            ImageFrames::Files { paths, .. } => {
+                let count = paths.len() as u64;
+                (
+                    Some(count),
+                    SourceMetadata::duration_from_frames(count, self.fps),
+                )
+            }
+            ImageFrames::Animation {
+                frames, duration, ..
+            } => (Some(frames.len() as u64), Some(*duration)),
+            ImageFrames::Still { .. } => (Some(1), None),
+        };
+        SourceMetadata {
+            width: self.source_width,
+            height: self.source_height,
+            fps: self.fps,
+            frame_count,
+            duration,
+            title: self.title.clone(),
+        }
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        match &mut self.frames {
+            ImageFrames::Files { paths, next } => {
+                let index = (position.as_secs_f64() * self.fps).round() as usize;
+                *next = index.min(paths.len());
+            }
+            ImageFrames::Animation { frames, next, .. } => {
+                let after = frames.partition_point(|(timestamp, _)| *timestamp <= position);
+                *next = after.saturating_sub(1);
+            }
+            ImageFrames::Still { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn set_scaler(&mut self, scaler: Scaler) {
+        self.scaler.set_scaler(scaler);
+    }
+
+    fn set_filters(&mut self, filters: FilterChain) {
+        self.scaler.set_filters(filters);
+    }
+
+    /// A still image yields exactly one frame here; only the decoder thread
+    /// keeps redrawing it.
+    fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<SourceFrame>> {
+        if let ImageFrames::Still { last_sent, .. } = &self.frames {
+            if last_sent.is_some() {
+                return Ok(None);
+            }
+        }
+        match self.read_next(buffer)? {
+            ReadOutcome::Frame { target, timestamp } => Ok(Some(SourceFrame {
+                target,
+                timestamp,
+                scale: self.scaler.last_report(),
+            })),
+            ReadOutcome::Idle | ReadOutcome::Eof => Ok(None),
+        }
+    }
+
+    fn spawn_decoding_thread(
+        self: Box<Self>,
+        sender: Sender<Result<FrameData>>,
+        timing: DecodeTiming,
+    ) -> std::thread::JoinHandle<Result<()>> {
+        let source_fps = self.fps();
+        spawn_frame_pump(*self, sender, source_fps, timing)
+    }
+}
+
+impl FrameReader for ImageSequenceDecoder {
+    fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome> {
+        let target = self.current_target()?;
+        let (image, timestamp) = match &mut self.frames {
+            ImageFrames::Files { paths, next } => {
+                let Some(path) = paths.get(*next) else {
+                    return Ok(ReadOutcome::Eof);
+                };
+                *next += 1;
+                (open_rgb(path)?, None)
+            }
+            ImageFrames::Animation { frames, next, .. } => {
+                let Some((timestamp, image)) = frames.get(*next) else {
+                    return Ok(ReadOutcome::Eof);
+                };
+                *next += 1;
+                let source = rgb_source(image);
+                self.scaler
+                    .scale_into(source, target, self.scale_mode, buffer)?;
+                return Ok(ReadOutcome::Frame {
+                    target,
+                    timestamp: Some(*timestamp),
+                });
+            }
+            ImageFrames::Still {
+                image,
+                shown_at,
+                last_sent,
+            } => {
+                let now = Instant::now();
+                let due = last_sent.is_none_or(|(sent_target, sent_at)| {
+                    sent_target != target || now.duration_since(sent_at) >= STILL_KEEPALIVE
+                });
+                if !due {
+                    return Ok(ReadOutcome::Idle);
+                }
+                let shown_at = *shown_at.get_or_insert(now);
+                *last_sent = Some((target, now));
+                self.scaler
+                    .scale_into(rgb_source(image), target, self.scale_mode, buffer)?;
+                return Ok(ReadOutcome::Frame {
+                    target,
+                    timestamp: Some(now.duration_since(shown_at)),
+                });
+            }
+        };
+
+        self.scaler
+            .scale_into(rgb_source(&image), target, self.scale_mode, buffer)?;
+        Ok(ReadOutcome::Frame { target, timestamp })
+    }
+}
+
+impl ImageFrames {
+    fn kind(&self) -> &'static str {
+        match self {
+            ImageFrames::Files { .. } => "sequence",
+            ImageFrames::Animation { .. } => "animation",
+            ImageFrames::Still { .. } => "still",
+        }
+    }
+}
+
+fn frames_are_files(path: &Path) -> bool {
+    !path.exists() && is_glob_pattern(path)
+}
+
+/// Directories are titled by their own name, globs by the directory they match in.
+fn sequence_title(path: &Path) -> Option<String> {
+    if path.is_dir() {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    } else if frames_are_files(path) {
+        path.parent().and_then(title_from_path)
+    } else {
+        title_from_path(path)
+    }
+}
+
+fn files_frames(paths: Vec<PathBuf>) -> ImageFrames {
+    ImageFrames::Files { paths, next: 0 }
+}
+
+fn rgb_source(image: &RgbImage) -> SourceImage<'_> {
+    SourceImage {
+        bytes: image.as_raw(),
+        width: image.width(),
+        height: image.height(),
+        order: PixelOrder::Rgb,
+    }
+}
+
+fn open_rgb(path: &Path) -> Result<RgbImage> {
+    Ok(image::open(path)
+        .with_context(|| format!("Failed to decode image: {}", path.display()))?
+        .to_rgb8())
+}
+
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy()
+        .chars()
+        .any(|c| matches!(c, '*' | '?' | '['))
+}
+
+fn list_image_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && has_allowed_extension(path, constants::IMAGE_EXTENSIONS))
+        .collect();
+
+    // Zero-padded names (frame_000001.png) sort into playback order
+    files.sort();
+    if files.is_empty() {
+        bail!("No PNG/JPEG images found in '{}'", dir.display());
+    }
+    Ok(files)
+}
+
+fn glob_image_files(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.to_string_lossy();
+    let mut files: Vec<PathBuf> = glob::glob(&pattern)
+        .with_context(|| format!("Invalid glob pattern: {}", pattern))?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file() && has_allowed_extension(path, constants::IMAGE_EXTENSIONS))
+        .collect();
+
+    files.sort();
+    if files.is_empty() {
+        bail!("No PNG/JPEG images match '{}'", pattern);
+    }
+    Ok(files)
+}
+
+fn decode_gif(path: &Path) -> Result<(Vec<(Duration, RgbImage)>, Duration)> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open GIF: {}", path.display()))?;
+    let decoder = GifDecoder::new(BufReader::new(file))
+        .with_context(|| format!("Failed to read GIF: {}", path.display()))?;
+    let frames = decoder
+        .into_frames()
+        .collect_frames()
+        .with_context(|| format!("Failed to decode GIF frames: {}", path.display()))?;
+
+    let mut timestamp = Duration::ZERO;
+    let mut decoded = Vec::with_capacity(frames.len());
+    for frame in frames {
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = gif_delay(numer, denom);
+        let image = image::DynamicImage::ImageRgba8(frame.into_buffer()).to_rgb8();
+        decoded.push((timestamp, image));
+        timestamp += delay;
+    }
+    Ok((decoded, timestamp))
+}
+
+fn gif_delay(numer: u32, denom: u32) -> Duration {
+    let delay = Duration::from_secs_f64(numer as f64 / denom.max(1) as f64 / 1000.0);
+    if delay < MIN_GIF_DELAY {
+        DEFAULT_GIF_DELAY
+    } else {
+        delay
+    }
+}
+
+/// GIFs carry their own timing; the reported fps only sizes the frame budget.
+fn average_fps(frame_count: usize, duration: Duration) -> Option<f64> {
+    (frame_count > 0 && !duration.is_zero()).then(|| frame_count as f64 / duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("gascii-image-seq-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_png(path: &Path, color: [u8; 3]) {
+        RgbImage::from_pixel(4, 2, image::Rgb(color))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn directory_frames_play_in_name_order() {
+        let dir = temp_dir("dir");
+        write_png(&dir.join("frame_000002.png"), [0, 0, 255]);
+        write_png(&dir.join("frame_000001.png"), [255, 0, 0]);
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let target = Arc::new(RwLock::new(RenderTarget::new(4, 2)));
+        let mut decoder = ImageSequenceDecoder::open(&dir, target, ScaleMode::Fit, Some(12.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoder.fps(), 12.0);
+        assert_eq!(decoder.source_aspect_ratio(), 2.0);
+
+        let mut buffer = Vec::new();
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_some());
+        assert_eq!(&buffer[..3], &[255, 0, 0]);
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_some());
+        assert_eq!(&buffer[..3], &[0, 0, 255]);
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn still_image_is_resent_only_when_target_changes() {
+        let dir = temp_dir("still");
+        let path = dir.join("poster.png");
+        write_png(&path, [10, 20, 30]);
+
+        let target = Arc::new(RwLock::new(RenderTarget::new(4, 2)));
+        let mut decoder = ImageSequenceDecoder::open(&path, target.clone(), ScaleMode::Fit, None)
+            .unwrap()
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        assert!(matches!(
+            decoder.read_next(&mut buffer).unwrap(),
+            ReadOutcome::Frame { .. }
+        ));
+        assert_eq!(decoder.read_next(&mut buffer).unwrap(), ReadOutcome::Idle);
+
+        *target.write().unwrap() = RenderTarget::new(8, 4);
+        match decoder.read_next(&mut buffer).unwrap() {
+            ReadOutcome::Frame { target, .. } => assert_eq!(target, RenderTarget::new(8, 4)),
+            other => panic!("expected a redraw, got {:?}", other),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unset_gif_delays_fall_back_to_browser_default() {
+        assert_eq!(gif_delay(0, 1), DEFAULT_GIF_DELAY);
+        assert_eq!(gif_delay(10, 1), DEFAULT_GIF_DELAY);
+        assert_eq!(gif_delay(40, 1), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn non_image_paths_are_left_to_the_video_decoder() {
+        let target = Arc::new(RwLock::new(RenderTarget::new(4, 2)));
+        let opened =
+            ImageSequenceDecoder::open(Path::new("movie.mp4"), target, ScaleMode::Fit, None)
+                .unwrap();
+        assert!(opened.is_none());
+    }
+}
@@ -1,7 +1,28 @@
 #![allow(unused)]
+pub mod filter;
 pub mod frame_data;
+pub mod image_sequence;
+pub mod pattern;
+pub mod pool;
+mod pump;
+pub mod raw;
+mod scale;
+pub mod source;
+mod temporal;
+#[cfg(feature = "opencv")]
 pub mod video;
 
+pub use filter::FilterChain;
 pub use frame_data::FrameData;
+pub use image_sequence::ImageSequenceDecoder;
+pub use pattern::{PatternKind, PatternSource, PatternSpec};
+pub use pool::BufferPool;
+pub use raw::{RawPixelFormat, RawStreamDecoder, RawVideoFormat};
+pub use scale::{CropRect, RenderTarget, ScaleMode, ScaleReport, Scaler};
+pub use source::{
+    open_source, CompanionAudio, DecodeTiming, FrameSource, SourceFrame, SourceMetadata,
+    SourceOptions,
+};
+pub use temporal::TemporalMode;
+#[cfg(feature = "opencv")]
 pub use video::VideoDecoder;
-pub use video::{RenderTarget, ScaleMode};
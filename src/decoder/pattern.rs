@@ -0,0 +1,739 @@
+use super::filter::FilterChain;
+use super::frame_data::FrameData;
+use super::pump::{spawn_frame_pump, FrameReader, ReadOutcome};
+use super::scale::{FrameScaler, PixelOrder, RenderTarget, ScaleMode, Scaler, SourceImage};
+use super::source::{CompanionAudio, DecodeTiming, FrameSource, SourceFrame, SourceMetadata};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_channel::Sender;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub const PATTERN_PREFIX: &str = "pattern:";
+const DEFAULT_SIZE: (u32, u32) = (640, 360);
+const DEFAULT_FPS: f64 = 30.0;
+const DEFAULT_DURATION: Duration = Duration::from_secs(10);
+/// Largest canvas a pattern may ask for (8K UHD)
+const MAX_SIZE: (u32, u32) = (7680, 4320);
+/// Longest finite pattern; longer runs use `duration=0`
+const MAX_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+const BEEP_SAMPLE_RATE: u32 = 48_000;
+const BEEP_FREQUENCY: f64 = 1_000.0;
+/// Number of frame-index bits burned into the top of the counter pattern
+pub(crate) const COUNTER_BITS: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    /// SMPTE-style color bars with PLUGE
+    Bars,
+    /// Static luma ramp over a scrolling hue sweep
+    Gradient,
+    /// Colored boxes bouncing around a dark field
+    Boxes,
+    /// Frame index as a binary strip plus large digits
+    Counter,
+    /// Full-field black/white flip and an inverting checkerboard
+    Flicker,
+    /// One white flash per second paired with a beep on the audio track
+    Sync,
+}
+
+impl PatternKind {
+    const NAMES: &'static [(&'static str, PatternKind)] = &[
+        ("bars", PatternKind::Bars),
+        ("gradient", PatternKind::Gradient),
+        ("boxes", PatternKind::Boxes),
+        ("counter", PatternKind::Counter),
+        ("flicker", PatternKind::Flicker),
+        ("sync", PatternKind::Sync),
+    ];
+
+    fn parse(name: &str) -> Result<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, kind)| *kind)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::NAMES.iter().map(|(name, _)| *name).collect();
+                anyhow!(
+                    "Unknown pattern '{}' (expected one of: {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Parsed `pattern:<kind>[,size=WxH][,fps=N][,duration=S]`
+///
+/// `duration=0` generates frames until playback is stopped. Sizes are
+/// limited to `MAX_SIZE` and durations to `MAX_DURATION`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternSpec {
+    pub kind: PatternKind,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    pub duration: Option<Duration>,
+}
+
+impl PatternSpec {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let body = spec
+            .strip_prefix(PATTERN_PREFIX)
+            .ok_or_else(|| anyhow!("Pattern specs start with '{}'", PATTERN_PREFIX))?;
+        let mut parts = body.split(',').map(str::trim);
+        let kind = PatternKind::parse(parts.next().unwrap_or_default())?;
+
+        let mut parsed = Self {
+            kind,
+            width: DEFAULT_SIZE.0,
+            height: DEFAULT_SIZE.1,
+            fps: DEFAULT_FPS,
+            duration: Some(DEFAULT_DURATION),
+        };
+        for part in parts.filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Pattern option '{}' is not key=value", part))?;
+            match key {
+                "size" => {
+                    let (width, height) = value
+                        .split_once(['x', 'X'])
+                        .ok_or_else(|| anyhow!("Pattern size must be WxH, got '{}'", value))?;
+                    parsed.width = width.parse().context("Invalid pattern width")?;
+                    parsed.height = height.parse().context("Invalid pattern height")?;
+                }
+                "fps" => parsed.fps = value.parse().context("Invalid pattern fps")?,
+                "duration" => {
+                    let seconds: f64 = value.parse().context("Invalid pattern duration")?;
+                    parsed.duration = (seconds > 0.0)
+                        .then(|| Duration::try_from_secs_f64(seconds))
+                        .transpose()
+                        .context("Invalid pattern duration")?;
+                }
+                other => bail!(
+                    "Unknown pattern option '{}' (expected size, fps or duration)",
+                    other
+                ),
+            }
+        }
+
+        if parsed.width == 0 || parsed.height == 0 {
+            bail!("Pattern size must be non-zero");
+        }
+        if parsed.width > MAX_SIZE.0 || parsed.height > MAX_SIZE.1 {
+            bail!(
+                "Pattern size {}x{} exceeds the {}x{} limit",
+                parsed.width,
+                parsed.height,
+                MAX_SIZE.0,
+                MAX_SIZE.1
+            );
+        }
+        if parsed
+            .duration
+            .is_some_and(|duration| duration > MAX_DURATION)
+        {
+            bail!(
+                "Pattern duration must be at most {}s; use duration=0 to run until stopped",
+                MAX_DURATION.as_secs()
+            );
+        }
+        if !parsed.fps.is_finite() || parsed.fps <= 0.0 {
+            bail!("Pattern fps must be positive");
+        }
+        Ok(parsed)
+    }
+
+    fn frame_count(&self) -> Option<u64> {
+        self.duration
+            .map(|duration| (duration.as_secs_f64() * self.fps).round() as u64)
+    }
+}
+
+/// Built-in generator used for calibration, benchmarks and tests
+pub struct PatternSource {
+    spec: PatternSpec,
+    next: u64,
+    canvas: Vec<u8>,
+    scale_mode: ScaleMode,
+    target: Arc<RwLock<RenderTarget>>,
+    scaler: FrameScaler,
+}
+
+impl PatternSource {
+    /// Returns `None` unless `path` is a `pattern:` spec.
+    pub fn open(
+        path: &Path,
+        target: Arc<RwLock<RenderTarget>>,
+        scale_mode: ScaleMode,
+    ) -> Result<Option<Self>> {
+        let Some(spec) = path
+            .to_str()
+            .filter(|spec| spec.starts_with(PATTERN_PREFIX))
+        else {
+            return Ok(None);
+        };
+        let spec = PatternSpec::parse(spec)?;
+
+        crate::utils::logger::info(&format!(
+            "pattern source: kind={:?} size={}x{} fps={:.2} frames={}",
+            spec.kind,
+            spec.width,
+            spec.height,
+            spec.fps,
+            spec.frame_count()
+                .map(|count| count.to_string())
+                .unwrap_or_else(|| "endless".to_string())
+        ));
+
+        Ok(Some(Self::new(spec, target, scale_mode)))
+    }
+
+    fn new(spec: PatternSpec, target: Arc<RwLock<RenderTarget>>, scale_mode: ScaleMode) -> Self {
+        Self {
+            spec,
+            next: 0,
+            canvas: vec![0; spec.width as usize * spec.height as usize * 3],
+            scale_mode,
+            target,
+            scaler: FrameScaler::new(),
+        }
+    }
+
+    fn render_next(&mut self, buffer: &mut Vec<u8>) -> Result<Option<RenderTarget>> {
+        if self
+            .spec
+            .frame_count()
+            .is_some_and(|count| self.next >= count)
+        {
+            return Ok(None);
+        }
+
+        render_pattern(
+            self.spec.kind,
+            self.next,
+            self.spec.fps,
+            self.spec.width,
+            self.spec.height,
+            &mut self.canvas,
+        );
+        self.next += 1;
+
+        let target = *self
+            .target
+            .read()
+            .map_err(|_| anyhow!("render target lock poisoned"))?;
+        self.scaler.scale_into(
+            SourceImage {
+                bytes: &self.canvas,
+                width: self.spec.width,
+                height: self.spec.height,
+                order: PixelOrder::Rgb,
+            },
+            target,
+            self.scale_mode,
+            buffer,
+        )?;
+        Ok(Some(target))
+    }
+}
+
+impl FrameSource for PatternSource {
+    fn fps(&self) -> f64 {
+        self.spec.fps
+    }
+
+    fn source_aspect_ratio(&self) -> f64 {
+        self.spec.width as f64 / self.spec.height as f64
+    }
+
+    fn metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            width: self.spec.width,
+            height: self.spec.height,
+            fps: self.spec.fps,
+            frame_count: self.spec.frame_count(),
+            duration: self.spec.duration,
+            title: Some(format!("pattern {:?}", self.spec.kind).to_lowercase()),
+        }
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        self.next = (position.as_secs_f64() * self.spec.fps).round() as u64;
+        Ok(())
+    }
+
+    fn set_scaler(&mut self, scaler: Scaler) {
+        self.scaler.set_scaler(scaler);
+    }
+
+    fn set_filters(&mut self, filters: FilterChain) {
+        self.scaler.set_filters(filters);
+    }
+
+    fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<SourceFrame>> {
+        Ok(self.render_next(buffer)?.map(|target| SourceFrame {
+            target,
+            timestamp: None,
+            scale: self.scaler.last_report(),
+        }))
+    }
+
+    fn companion_audio(&self) -> Result<Option<CompanionAudio>> {
+        match (self.spec.kind, self.spec.duration) {
+            (PatternKind::Sync, Some(duration)) => {
+                write_beep_track(self.spec.fps, duration).map(Some)
+            }
+            (PatternKind::Sync, None) => {
+                crate::utils::logger::info(
+                    "sync pattern without duration: beep track disabled, flashes only",
+                );
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn spawn_decoding_thread(
+        self: Box<Self>,
+        sender: Sender<Result<FrameData>>,
+        timing: DecodeTiming,
+    ) -> std::thread::JoinHandle<Result<()>> {
+        let source_fps = self.fps();
+        spawn_frame_pump(*self, sender, source_fps, timing)
+    }
+}
+
+impl FrameReader for PatternSource {
+    fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome> {
+        Ok(match self.render_next(buffer)? {
+            Some(target) => ReadOutcome::Frame {
+                target,
+                timestamp: None,
+            },
+            None => ReadOutcome::Eof,
+        })
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+const BLACK: Rgb = (0, 0, 0);
+const WHITE: Rgb = (255, 255, 255);
+
+fn render_pattern(
+    kind: PatternKind,
+    frame: u64,
+    fps: f64,
+    width: u32,
+    height: u32,
+    out: &mut [u8],
+) {
+    let canvas = Canvas { width, height };
+    match kind {
+        PatternKind::Bars => canvas.fill_with(out, |x, y| smpte_bar(x, y, width, height)),
+        PatternKind::Gradient => canvas.fill_with(out, |x, y| {
+            if y < height / 2 {
+                let luma = (x as u64 * 255 / width.saturating_sub(1).max(1) as u64) as u8;
+                (luma, luma, luma)
+            } else {
+                let hue = ((x as u64 * 360 / width as u64) + frame * 4) % 360;
+                hue_to_rgb(hue as u32)
+            }
+        }),
+        PatternKind::Boxes => {
+            canvas.fill_with(out, |_, _| (32, 32, 32));
+            let size = (width.min(height) / 6).max(1);
+            let boxes = [
+                ((255, 64, 64), 7, 5),
+                ((64, 255, 64), 4, 9),
+                ((64, 128, 255), 11, 3),
+            ];
+            for (index, (color, speed_x, speed_y)) in boxes.into_iter().enumerate() {
+                let phase = frame + index as u64 * 37;
+                let x = bounce(phase * speed_x, width.saturating_sub(size));
+                let y = bounce(phase * speed_y, height.saturating_sub(size));
+                canvas.fill_rect(out, x, y, size, size, color);
+            }
+        }
+        PatternKind::Counter => {
+            canvas.fill_with(out, |_, _| BLACK);
+            let strip_height = (height / 16).max(1);
+            let bit_width = (width / COUNTER_BITS).max(1);
+            for bit in 0..COUNTER_BITS {
+                let set = (frame >> (COUNTER_BITS - 1 - bit)) & 1 == 1;
+                let color = if set { WHITE } else { (40, 40, 40) };
+                canvas.fill_rect(out, bit * bit_width, 0, bit_width, strip_height, color);
+            }
+            draw_number(&canvas, out, frame, strip_height);
+        }
+        PatternKind::Flicker => {
+            let phase = frame.is_multiple_of(2);
+            canvas.fill_with(out, |x, y| {
+                let on = if x < width / 2 {
+                    phase
+                } else {
+                    ((x / 8 + y / 8) % 2 == 0) == phase
+                };
+                if on {
+                    WHITE
+                } else {
+                    BLACK
+                }
+            });
+        }
+        PatternKind::Sync => {
+            let color = if is_sync_flash(frame, fps) {
+                WHITE
+            } else {
+                BLACK
+            };
+            canvas.fill_with(out, |_, _| color);
+        }
+    }
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+}
+
+impl Canvas {
+    fn fill_with(&self, out: &mut [u8], mut color_at: impl FnMut(u32, u32) -> Rgb) {
+        for (index, pixel) in out.chunks_exact_mut(3).enumerate() {
+            let x = index as u32 % self.width;
+            let y = index as u32 / self.width;
+            let (r, g, b) = color_at(x, y);
+            pixel.copy_from_slice(&[r, g, b]);
+        }
+    }
+
+    fn fill_rect(&self, out: &mut [u8], x: u32, y: u32, width: u32, height: u32, color: Rgb) {
+        let x_end = (x + width).min(self.width);
+        let y_end = (y + height).min(self.height);
+        for row in y.min(y_end)..y_end {
+            for col in x.min(x_end)..x_end {
+                let offset = (row * self.width + col) as usize * 3;
+                out[offset..offset + 3].copy_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+    }
+}
+
+/// 75% bars, reverse castellations, then -I / white / +Q / black and PLUGE
+fn smpte_bar(x: u32, y: u32, width: u32, height: u32) -> Rgb {
+    const TOP: [Rgb; 7] = [
+        (191, 191, 191),
+        (191, 191, 0),
+        (0, 191, 191),
+        (0, 191, 0),
+        (191, 0, 191),
+        (191, 0, 0),
+        (0, 0, 191),
+    ];
+    const MIDDLE: [Rgb; 7] = [
+        (0, 0, 191),
+        (19, 19, 19),
+        (191, 0, 191),
+        (19, 19, 19),
+        (0, 191, 191),
+        (19, 19, 19),
+        (191, 191, 191),
+    ];
+    // Widths in 28ths of the frame
+    const BOTTOM: [(u32, Rgb); 8] = [
+        (5, (0, 33, 76)),
+        (5, (255, 255, 255)),
+        (5, (50, 0, 106)),
+        (5, (19, 19, 19)),
+        (2, (9, 9, 9)),
+        (2, (19, 19, 19)),
+        (2, (29, 29, 29)),
+        (2, (19, 19, 19)),
+    ];
+
+    let bar = ((x as u64 * 7) / width as u64) as usize;
+    if y < height * 2 / 3 {
+        return TOP[bar];
+    }
+    if y < height * 3 / 4 {
+        return MIDDLE[bar];
+    }
+
+    let unit = (x as u64 * 28 / width as u64) as u32;
+    let mut start = 0;
+    for (span, color) in BOTTOM {
+        start += span;
+        if unit < start {
+            return color;
+        }
+    }
+    BOTTOM[BOTTOM.len() - 1].1
+}
+
+fn hue_to_rgb(hue: u32) -> Rgb {
+    let sector = hue / 60;
+    let rising = ((hue % 60) * 255 / 60) as u8;
+    let falling = 255 - rising;
+    match sector {
+        0 => (255, rising, 0),
+        1 => (falling, 255, 0),
+        2 => (0, 255, rising),
+        3 => (0, falling, 255),
+        4 => (rising, 0, 255),
+        _ => (255, 0, falling),
+    }
+}
+
+/// Triangle wave over `0..=range`
+fn bounce(position: u64, range: u32) -> u32 {
+    if range == 0 {
+        return 0;
+    }
+    let period = range as u64 * 2;
+    let phase = position % period;
+    (if phase <= range as u64 {
+        phase
+    } else {
+        period - phase
+    }) as u32
+}
+
+fn is_sync_flash(frame: u64, fps: f64) -> bool {
+    let frames_per_second = (fps.round() as u64).max(1);
+    frame.is_multiple_of(frames_per_second)
+}
+
+/// 3x5 bitmap digits, one row per nibble (MSB = left column)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn draw_number(canvas: &Canvas, out: &mut [u8], value: u64, top: u32) {
+    let digits: Vec<usize> = value
+        .to_string()
+        .bytes()
+        .map(|digit| (digit - b'0') as usize)
+        .collect();
+    // Each glyph is 3 columns plus 1 column of spacing
+    let columns = digits.len() as u32 * 4 - 1;
+    let available_height = canvas.height.saturating_sub(top);
+    let scale = (canvas.width * 3 / 4 / columns)
+        .min(available_height * 3 / 4 / 5)
+        .max(1);
+    let origin_x = canvas.width.saturating_sub(columns * scale) / 2;
+    let origin_y = top + available_height.saturating_sub(5 * scale) / 2;
+
+    for (position, digit) in digits.into_iter().enumerate() {
+        let glyph_x = origin_x + position as u32 * 4 * scale;
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    canvas.fill_rect(
+                        out,
+                        glyph_x + col * scale,
+                        origin_y + row as u32 * scale,
+                        scale,
+                        scale,
+                        WHITE,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Writes a mono 16-bit WAV with a one-frame 1kHz beep on every flash into
+/// a temporary file that is removed when the returned guard is dropped.
+fn write_beep_track(fps: f64, duration: Duration) -> Result<CompanionAudio> {
+    let (samples, data_len) = beep_track_len(duration)?;
+    let path = std::env::temp_dir().join(format!(
+        "gascii-sync-{}-{}fps-{}ms.wav",
+        std::process::id(),
+        fps,
+        duration.as_millis()
+    ));
+    let file = std::fs::File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    // Guard first, so a failed write does not leave the file behind.
+    let track = CompanionAudio::temporary(path);
+    let frames_per_second = (fps.round() as u64).max(1);
+    let flash_interval = frames_per_second as f64 / fps;
+    let beep_length = 1.0 / fps;
+
+    let mut out = BufWriter::new(file);
+    out.write_all(&wav_header(data_len)?)?;
+    for sample in 0..samples {
+        let t = sample as f64 / BEEP_SAMPLE_RATE as f64;
+        let in_beep = t % flash_interval < beep_length;
+        let value = if in_beep {
+            ((t * BEEP_FREQUENCY * std::f64::consts::TAU).sin() * i16::MAX as f64 * 0.5) as i16
+        } else {
+            0
+        };
+        out.write_all(&value.to_le_bytes())?;
+    }
+    out.flush()
+        .with_context(|| format!("Failed to write {}", track.path().display()))?;
+    Ok(track)
+}
+
+/// Sample count and data size of a beep track, or an error when it would
+/// not fit the 32-bit sizes of a WAV file.
+fn beep_track_len(duration: Duration) -> Result<(u32, u32)> {
+    let too_long = || {
+        anyhow!(
+            "Sync pattern duration {:.0}s is too long for its beep track",
+            duration.as_secs_f64()
+        )
+    };
+    let samples = u64::try_from(duration.as_millis())
+        .ok()
+        .and_then(|millis| millis.checked_mul(BEEP_SAMPLE_RATE as u64))
+        .map(|scaled| scaled / 1000)
+        .and_then(|samples| u32::try_from(samples).ok())
+        .ok_or_else(too_long)?;
+    let data_len = samples.checked_mul(2).ok_or_else(too_long)?;
+    Ok((samples, data_len))
+}
+
+fn wav_header(data_len: u32) -> Result<Vec<u8>> {
+    let riff_len = data_len
+        .checked_add(36)
+        .ok_or_else(|| anyhow!("WAV data of {} bytes is too large", data_len))?;
+    let channels: u16 = 1;
+    let bits_per_sample: u16 = 16;
+    let block_align = channels * bits_per_sample / 8;
+    let byte_rate = BEEP_SAMPLE_RATE * block_align as u32;
+
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&riff_len.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&BEEP_SAMPLE_RATE.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&bits_per_sample.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    Ok(header)
+}
+
+/// Reads the frame index back out of a native-resolution counter frame.
+#[cfg(test)]
+pub(crate) fn decode_counter(rgb: &[u8], width: u32) -> u64 {
+    let bit_width = (width / COUNTER_BITS).max(1);
+    (0..COUNTER_BITS).fold(0, |value, bit| {
+        let x = bit * bit_width + bit_width / 2;
+        let set = rgb[x as usize * 3] > 128;
+        (value << 1) | set as u64
+    })
+}
+
+#[cfg(test)]
+pub(crate) fn test_source(spec: &str, target: RenderTarget) -> PatternSource {
+    PatternSource::new(
+        PatternSpec::parse(spec).unwrap(),
+        Arc::new(RwLock::new(target)),
+        ScaleMode::Fit,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_parses_options_and_rejects_unknown_ones() {
+        let spec = PatternSpec::parse("pattern:counter,size=320x180,fps=25,duration=2").unwrap();
+        assert_eq!(spec.kind, PatternKind::Counter);
+        assert_eq!((spec.width, spec.height), (320, 180));
+        assert_eq!(spec.frame_count(), Some(50));
+
+        let endless = PatternSpec::parse("pattern:bars,duration=0").unwrap();
+        assert_eq!(endless.frame_count(), None);
+        assert_eq!((endless.width, endless.height), DEFAULT_SIZE);
+
+        assert!(PatternSpec::parse("pattern:plaid").is_err());
+        assert!(PatternSpec::parse("pattern:bars,speed=2").is_err());
+        assert!(PatternSpec::parse("pattern:bars,fps=0").is_err());
+        assert!(PatternSpec::parse("pattern:bars,size=100000x100").is_err());
+        assert!(PatternSpec::parse("pattern:bars,duration=1e9").is_err());
+    }
+
+    #[test]
+    fn beep_track_sizes_are_checked_and_the_file_is_removed_with_its_guard() {
+        assert_eq!(
+            beep_track_len(Duration::from_secs(2)).unwrap(),
+            (96_000, 192_000)
+        );
+        // 48kHz 16-bit mono overflows the 32-bit WAV sizes after ~12.4h.
+        assert!(beep_track_len(Duration::from_secs(13 * 60 * 60)).is_err());
+        assert!(wav_header(u32::MAX - 10).is_err());
+
+        let track = write_beep_track(30.0, Duration::from_millis(100)).unwrap();
+        let path = track.path().to_path_buf();
+        let written = std::fs::read(&path).unwrap();
+        assert_eq!(written.len(), 44 + 4_800 * 2);
+        assert_eq!(&written[40..44], &9_600u32.to_le_bytes());
+        drop(track);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn counter_frames_carry_their_index() {
+        let mut source = test_source(
+            "pattern:counter,size=64x32,duration=1",
+            RenderTarget::new(64, 32),
+        );
+        let mut buffer = Vec::new();
+        for expected in 0..5 {
+            source.read_frame_into(&mut buffer).unwrap().unwrap();
+            assert_eq!(decode_counter(&buffer, 64), expected);
+        }
+    }
+
+    #[test]
+    fn finite_patterns_end_after_duration() {
+        let mut source = test_source(
+            "pattern:flicker,size=8x8,fps=4,duration=1",
+            RenderTarget::new(8, 8),
+        );
+        let mut buffer = Vec::new();
+        let mut frames = 0;
+        while source.read_frame_into(&mut buffer).unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+    }
+
+    #[test]
+    fn sync_flashes_once_per_second() {
+        let flashes: Vec<u64> = (0..90)
+            .filter(|frame| is_sync_flash(*frame, 30.0))
+            .collect();
+        assert_eq!(flashes, vec![0, 30, 60]);
+    }
+
+    #[test]
+    fn bars_start_with_gray_and_end_with_pluge_black() {
+        assert_eq!(smpte_bar(0, 0, 700, 300), (191, 191, 191));
+        assert_eq!(smpte_bar(699, 0, 700, 300), (0, 0, 191));
+        assert_eq!(smpte_bar(699, 299, 700, 300), (19, 19, 19));
+    }
+}
@@ -0,0 +1,93 @@
+use crossbeam_channel::{Receiver, Sender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Frame buffers handed back from the player to the decoder thread
+///
+/// Frames tagged with a pool return their buffer when dropped, whether
+/// they were rendered or discarded, so steady-state playback decodes into
+/// the same few allocations. A buffer only grows when the render target
+/// does.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    returned: Sender<Vec<u8>>,
+    available: Receiver<Vec<u8>>,
+    stats: Arc<PoolStats>,
+}
+
+#[derive(Debug, Default)]
+struct PoolStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl BufferPool {
+    /// Holds at most `capacity` idle buffers; extra ones are freed.
+    pub fn new(capacity: usize) -> Self {
+        let (returned, available) = crossbeam_channel::bounded(capacity.max(1));
+        Self {
+            returned,
+            available,
+            stats: Arc::default(),
+        }
+    }
+
+    /// A buffer for the next frame. It is a hit when a returned buffer can
+    /// hold `expected_len` bytes without reallocating.
+    pub fn take(&self, expected_len: usize) -> Vec<u8> {
+        match self.available.try_recv() {
+            Ok(buffer) if buffer.capacity() >= expected_len => {
+                self.stats.hits.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            Ok(buffer) => {
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            Err(_) => {
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn put(&self, buffer: Vec<u8>) {
+        if buffer.capacity() > 0 {
+            let _ = self.returned.try_send(buffer);
+        }
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.stats.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.stats.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::FrameData;
+    use std::time::Duration;
+
+    #[test]
+    fn dropped_frames_return_their_buffer() {
+        let pool = BufferPool::new(2);
+        assert!(pool.take(12).is_empty());
+
+        let frame = FrameData::new(vec![1; 12], 2, 2, Duration::ZERO).recycled_by(pool.clone());
+        let address = frame.buffer.as_ptr();
+        drop(frame);
+
+        let buffer = pool.take(12);
+        assert_eq!(buffer.as_ptr(), address);
+        assert_eq!((pool.hits(), pool.misses()), (1, 1));
+
+        // A bigger target cannot reuse the buffer without growing it.
+        pool.put(buffer);
+        pool.take(48);
+        assert_eq!((pool.hits(), pool.misses()), (1, 2));
+    }
+}
@@ -0,0 +1,395 @@
+use super::frame_data::FrameData;
+use super::pool::BufferPool;
+use super::scale::RenderTarget;
+use super::source::DecodeTiming;
+use super::temporal::FrameResampler;
+use crate::sync::PauseGate;
+use anyhow::{anyhow, Result};
+use crossbeam_channel::{SendTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+const SEND_TIMEOUT: Duration = Duration::from_millis(100);
+const DECODER_LEAD_TIME: Duration = Duration::from_millis(500);
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Longest single pacing sleep, so a pause or resume is noticed promptly
+const PACE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const POOL_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Result of asking a source for its next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReadOutcome {
+    /// A frame was written into the buffer.
+    ///
+    /// Sources with their own timing (GIF delays, still images) report a
+    /// timestamp; `None` means "frame index / playback fps".
+    Frame {
+        target: RenderTarget,
+        timestamp: Option<Duration>,
+    },
+    /// Nothing new yet, e.g. a still image waiting for the next resize.
+    ///
+    /// Readers that idle must still produce a frame now and then: a send is
+    /// the only way the pump notices that the player has gone away.
+    Idle,
+    Eof,
+}
+
+/// Anything that can fill RGB canvases for the decoder thread
+pub(crate) trait FrameReader: Send + 'static {
+    fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome>;
+
+    /// Advances past the next frame without converting it, for frames the
+    /// player would drop anyway. Only readers whose frames are timed by
+    /// index should skip; `false` (the default, and at EOF) makes the pump
+    /// read the frame normally.
+    fn skip_next(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+pub(crate) fn spawn_frame_pump<R: FrameReader>(
+    mut reader: R,
+    sender: Sender<Result<FrameData>>,
+    source_fps: f64,
+    timing: DecodeTiming,
+) -> std::thread::JoinHandle<Result<()>> {
+    std::thread::spawn(move || {
+        crate::utils::logger::debug("Decoder thread started");
+        let decode_started_at = Instant::now();
+        let playback_fps = timing.playback_fps;
+        let mut frame_counter: u64 = 0;
+        let mut resampler = FrameResampler::new(timing.temporal, source_fps, playback_fps);
+        if resampler.is_some() {
+            crate::utils::logger::info(&format!(
+                "temporal {:?}: {:.3} fps source resampled to {:.3} fps",
+                timing.temporal, source_fps, playback_fps
+            ));
+        }
+        let pool = timing.recycle;
+        let pause = timing.pause;
+        let mut pool_report = PoolReport::new(pool.as_ref());
+        let recycled = |frame: FrameData| match &pool {
+            Some(pool) => frame.recycled_by(pool.clone()),
+            None => frame,
+        };
+        // Resampled frames are paced and sent one by one; a full queue drops
+        // the frame, a dropped receiver stops the thread.
+        let mut send_resampled = |frame: FrameData| {
+            let frame = recycled(frame);
+            pace_decoding(decode_started_at, frame.timestamp, pause.as_ref());
+            !matches!(
+                send_frame(&sender, frame, pause.as_ref()),
+                Err(SendTimeoutError::Disconnected(_))
+            )
+        };
+
+        // Skipping source frames would break the resampler's timeline.
+        let skip_late = timing.skip_late.filter(|_| resampler.is_none());
+        let mut skipped: u64 = 0;
+
+        let mut buffer = Vec::new();
+        let mut frame_len = 0;
+        loop {
+            if let Some(report) = &mut pool_report {
+                report.log_if_due(false);
+            }
+            if let Some(deadline) = &skip_late {
+                if deadline.is_missed(counter_timestamp(frame_counter, playback_fps)) {
+                    match reader.skip_next() {
+                        Ok(true) => {
+                            frame_counter += 1;
+                            skipped += 1;
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(e) => crate::utils::logger::debug(&format!("Frame skip failed: {}", e)),
+                    }
+                }
+            }
+            if buffer.capacity() == 0 {
+                if let Some(pool) = &pool {
+                    buffer = pool.take(frame_len);
+                }
+            }
+            match reader.read_next(&mut buffer) {
+                Ok(ReadOutcome::Frame {
+                    target,
+                    timestamp: None,
+                }) if resampler.is_some() => {
+                    frame_len = buffer.len();
+                    let frame = recycled(FrameData::new(
+                        std::mem::take(&mut buffer),
+                        target.pixel_width,
+                        target.pixel_height,
+                        Duration::ZERO,
+                    ));
+                    let alive = resampler
+                        .as_mut()
+                        .is_some_and(|resampler| resampler.push(frame, &mut send_resampled));
+                    if !alive {
+                        crate::utils::logger::debug("Decoder sender error (receiver dropped)");
+                        break;
+                    }
+                }
+                Ok(ReadOutcome::Frame { target, timestamp }) => {
+                    let timestamp =
+                        timestamp.unwrap_or_else(|| counter_timestamp(frame_counter, playback_fps));
+                    frame_counter += 1;
+                    pace_decoding(decode_started_at, timestamp, pause.as_ref());
+
+                    frame_len = buffer.len();
+                    let frame = recycled(FrameData::new(
+                        std::mem::take(&mut buffer),
+                        target.pixel_width,
+                        target.pixel_height,
+                        timestamp,
+                    ));
+                    match send_frame(&sender, frame, pause.as_ref()) {
+                        Ok(()) => {}
+                        Err(SendTimeoutError::Timeout(value)) => {
+                            buffer = match value {
+                                Ok(mut frame) => std::mem::take(&mut frame.buffer),
+                                Err(_) => Vec::new(),
+                            };
+                            continue;
+                        }
+                        Err(SendTimeoutError::Disconnected(_)) => {
+                            crate::utils::logger::debug("Decoder sender error (receiver dropped)");
+                            break;
+                        }
+                    }
+                }
+                Ok(ReadOutcome::Idle) => std::thread::sleep(IDLE_POLL_INTERVAL),
+                Ok(ReadOutcome::Eof) => {
+                    if let Some(resampler) = &mut resampler {
+                        resampler.finish(&mut send_resampled);
+                    }
+                    crate::utils::logger::debug("Decoder EOF");
+                    break;
+                }
+                Err(e) => {
+                    crate::utils::logger::error(&format!("Decoding error: {}", e));
+                    let message = e.to_string();
+                    let _ = sender.send_timeout(Err(anyhow!(message.clone())), SEND_TIMEOUT);
+                    return Err(anyhow!(message));
+                }
+            }
+        }
+
+        if let Some(report) = &mut pool_report {
+            report.log_if_due(true);
+        }
+        if skipped > 0 {
+            crate::utils::logger::debug(&format!(
+                "Decoder skipped {} late frames without converting them",
+                skipped
+            ));
+        }
+        crate::utils::logger::debug("Decoder thread exiting");
+        Ok(())
+    })
+}
+
+/// Periodic pool hit/miss line for the memory profile
+struct PoolReport {
+    pool: BufferPool,
+    last: Instant,
+}
+
+impl PoolReport {
+    /// `None` unless memory profiling is on and frames are pooled.
+    fn new(pool: Option<&BufferPool>) -> Option<Self> {
+        let pool = pool.filter(|_| crate::utils::memory::profiling_enabled())?;
+        Some(Self {
+            pool: pool.clone(),
+            last: Instant::now(),
+        })
+    }
+
+    fn log_if_due(&mut self, force: bool) {
+        if !force && self.last.elapsed() < POOL_REPORT_INTERVAL {
+            return;
+        }
+        self.last = Instant::now();
+        let max_rss = crate::utils::memory::max_rss_bytes()
+            .map(crate::utils::memory::format_bytes)
+            .unwrap_or_else(|| "unknown".to_string());
+        crate::utils::logger::debug(&format!(
+            "BUFFER_POOL: hits={} misses={} max_rss={}",
+            self.pool.hits(),
+            self.pool.misses(),
+            max_rss
+        ));
+    }
+}
+
+fn counter_timestamp(frame_counter: u64, playback_fps: f64) -> Duration {
+    if !playback_fps.is_finite() || playback_fps <= 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(frame_counter as f64 / playback_fps)
+}
+
+/// Keeps the decoder at most `DECODER_LEAD_TIME` ahead of the frame
+/// timeline. Time spent paused does not advance the timeline.
+fn pace_decoding(started_at: Instant, frame_time: Duration, pause: Option<&PauseGate>) {
+    let Some(target_time) = frame_time.checked_sub(DECODER_LEAD_TIME) else {
+        return;
+    };
+    loop {
+        let paused = pause.map_or(Duration::ZERO, PauseGate::paused_total);
+        let elapsed = started_at.elapsed().saturating_sub(paused);
+        if target_time <= elapsed {
+            return;
+        }
+        std::thread::sleep((target_time - elapsed).min(PACE_POLL_INTERVAL));
+    }
+}
+
+/// Sends `frame`, waiting for room for as long as playback is paused. A
+/// full queue during playback hands the frame back: the player is behind
+/// and would drop it anyway.
+fn send_frame(
+    sender: &Sender<Result<FrameData>>,
+    frame: FrameData,
+    pause: Option<&PauseGate>,
+) -> std::result::Result<(), SendTimeoutError<Result<FrameData>>> {
+    let mut value = Ok(frame);
+    loop {
+        match sender.send_timeout(value, SEND_TIMEOUT) {
+            Err(SendTimeoutError::Timeout(returned)) if pause.is_some_and(PauseGate::is_paused) => {
+                value = returned;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CountingReader {
+        remaining: u32,
+        idle_once: bool,
+    }
+
+    impl FrameReader for CountingReader {
+        fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome> {
+            if self.idle_once {
+                self.idle_once = false;
+                return Ok(ReadOutcome::Idle);
+            }
+            if self.remaining == 0 {
+                return Ok(ReadOutcome::Eof);
+            }
+            self.remaining -= 1;
+            buffer.clear();
+            buffer.resize(2 * 2 * 3, 7);
+            Ok(ReadOutcome::Frame {
+                target: RenderTarget::new(2, 2),
+                timestamp: None,
+            })
+        }
+
+        fn skip_next(&mut self) -> Result<bool> {
+            if self.remaining == 0 {
+                return Ok(false);
+            }
+            self.remaining -= 1;
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn pump_stamps_frames_from_playback_fps_and_stops_at_eof() {
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        let handle = spawn_frame_pump(
+            CountingReader {
+                remaining: 3,
+                idle_once: true,
+            },
+            sender,
+            10.0,
+            DecodeTiming::retimed(10.0),
+        );
+        handle.join().unwrap().unwrap();
+
+        let timestamps: Vec<Duration> = receiver
+            .try_iter()
+            .map(|frame| frame.unwrap().timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200)
+            ]
+        );
+    }
+
+    #[test]
+    fn paused_pump_waits_for_queue_room_instead_of_dropping_frames() {
+        let pause = PauseGate::new();
+        pause.pause();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let handle = spawn_frame_pump(
+            CountingReader {
+                remaining: 3,
+                idle_once: false,
+            },
+            sender,
+            10.0,
+            DecodeTiming {
+                pause: Some(pause.clone()),
+                ..DecodeTiming::retimed(10.0)
+            },
+        );
+        // Several send timeouts pass while the queue is full.
+        std::thread::sleep(SEND_TIMEOUT * 3);
+
+        let timestamps: Vec<Duration> = receiver
+            .iter()
+            .map(|frame| frame.unwrap().timestamp)
+            .collect();
+        handle.join().unwrap().unwrap();
+        assert_eq!(
+            timestamps,
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200)
+            ]
+        );
+    }
+
+    #[test]
+    fn pump_skips_frames_due_before_the_drop_deadline() {
+        let deadline = crate::sync::DropDeadline::new();
+        deadline.publish(Duration::from_millis(250));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        let handle = spawn_frame_pump(
+            CountingReader {
+                remaining: 5,
+                idle_once: false,
+            },
+            sender,
+            10.0,
+            DecodeTiming {
+                skip_late: Some(deadline),
+                ..DecodeTiming::retimed(10.0)
+            },
+        );
+        handle.join().unwrap().unwrap();
+
+        let timestamps: Vec<Duration> = receiver
+            .try_iter()
+            .map(|frame| frame.unwrap().timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![Duration::from_millis(300), Duration::from_millis(400)]
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use super::filter::FilterChain;
+use super::frame_data::FrameData;
+use super::pump::{spawn_frame_pump, FrameReader, ReadOutcome};
+use super::scale::{FrameScaler, PixelOrder, RenderTarget, ScaleMode, Scaler, SourceImage};
+use super::source::{title_from_path, DecodeTiming, FrameSource, SourceFrame, SourceMetadata};
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_channel::Sender;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub const STDIN_PATH: &str = "-";
+const DEFAULT_RAW_FPS: f64 = 30.0;
+const Y4M_MAGIC: &str = "YUV4MPEG2";
+const Y4M_FRAME_MAGIC: &str = "FRAME";
+/// Y4M header lines are short; anything longer is not a Y4M stream.
+const MAX_Y4M_HEADER_LEN: usize = 1024;
+/// Largest frame accepted, in pixels (8K UHD), so a bogus size fails
+/// instead of aborting on the frame allocation
+const MAX_FRAME_PIXELS: u64 = 7680 * 4320;
+const PIPE_BUFFER_BYTES: usize = 1 << 20;
+
+/// Pixel layouts accepted in `rawvideo` mode, named after ffmpeg's `-pix_fmt`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum RawPixelFormat {
+    Rgb24,
+    Bgr24,
+    Gray,
+    Yuv420p,
+    Yuv444p,
+}
+
+impl RawPixelFormat {
+    fn frame_len(self, width: u32, height: u32) -> usize {
+        let luma = width as usize * height as usize;
+        match self {
+            Self::Rgb24 | Self::Bgr24 | Self::Yuv444p => luma * 3,
+            Self::Gray => luma,
+            Self::Yuv420p => luma + 2 * chroma_420_len(width, height),
+        }
+    }
+}
+
+/// Geometry of a headerless `rawvideo` stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawVideoFormat {
+    pub width: u32,
+    pub height: u32,
+    pub pixel_format: RawPixelFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Y4mHeader {
+    width: u32,
+    height: u32,
+    fps: Option<f64>,
+    pixel_aspect: Option<f64>,
+    pixel_format: RawPixelFormat,
+}
+
+enum StreamKind {
+    Y4m,
+    Raw,
+}
+
+/// Frame source for piped `yuv4mpegpipe` or `rawvideo` streams
+pub struct RawStreamDecoder {
+    reader: BufReader<Box<dyn Read + Send>>,
+    kind: StreamKind,
+    width: u32,
+    height: u32,
+    fps: f64,
+    aspect_ratio: f64,
+    pixel_format: RawPixelFormat,
+    frame: Vec<u8>,
+    rgb: Vec<u8>,
+    scale_mode: ScaleMode,
+    target: Arc<RwLock<RenderTarget>>,
+    scaler: FrameScaler,
+    frames_read: u64,
+    title: Option<String>,
+}
+
+impl RawStreamDecoder {
+    /// `-` reads stdin; `.y4m` files and explicit rawvideo formats are
+    /// accepted for regular paths and FIFOs. Returns `None` for anything else.
+    pub fn open(
+        path: &Path,
+        raw_format: Option<RawVideoFormat>,
+        target: Arc<RwLock<RenderTarget>>,
+        scale_mode: ScaleMode,
+        input_fps: Option<f64>,
+    ) -> Result<Option<Self>> {
+        let is_stdin = path.as_os_str() == STDIN_PATH;
+        let is_y4m_file = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("y4m"));
+        if !is_stdin && !is_y4m_file && raw_format.is_none() {
+            return Ok(None);
+        }
+
+        let reader: Box<dyn Read + Send> = if is_stdin {
+            Box::new(std::io::stdin())
+        } else {
+            Box::new(
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+            )
+        };
+        let mut decoder = Self::from_reader(reader, raw_format, target, scale_mode, input_fps)?;
+        decoder.title = if is_stdin {
+            Some("stdin".to_string())
+        } else {
+            title_from_path(path)
+        };
+
+        crate::utils::logger::info(&format!(
+            "raw stream opened: path={} kind={} size={}x{} format={:?} fps={:.3}",
+            path.display(),
+            match decoder.kind {
+                StreamKind::Y4m => "y4m",
+                StreamKind::Raw => "rawvideo",
+            },
+            decoder.width,
+            decoder.height,
+            decoder.pixel_format,
+            decoder.fps
+        ));
+        Ok(Some(decoder))
+    }
+
+    fn from_reader(
+        reader: Box<dyn Read + Send>,
+        raw_format: Option<RawVideoFormat>,
+        target: Arc<RwLock<RenderTarget>>,
+        scale_mode: ScaleMode,
+        input_fps: Option<f64>,
+    ) -> Result<Self> {
+        let mut reader = BufReader::with_capacity(PIPE_BUFFER_BYTES, reader);
+        let input_fps = input_fps.filter(|fps| fps.is_finite() && *fps > 0.0);
+
+        let (kind, width, height, fps, pixel_aspect, pixel_format) = match raw_format {
+            Some(format) => (
+                StreamKind::Raw,
+                format.width,
+                format.height,
+                input_fps.unwrap_or(DEFAULT_RAW_FPS),
+                None,
+                format.pixel_format,
+            ),
+            None => {
+                let line = read_header_line(&mut reader)?
+                    .ok_or_else(|| anyhow!("Empty input: expected a Y4M stream"))?;
+                let header = parse_y4m_header(&line)?;
+                (
+                    StreamKind::Y4m,
+                    header.width,
+                    header.height,
+                    input_fps.or(header.fps).unwrap_or(DEFAULT_RAW_FPS),
+                    header.pixel_aspect,
+                    header.pixel_format,
+                )
+            }
+        };
+        if width == 0 || height == 0 {
+            bail!("Raw frame size must be non-zero, got {}x{}", width, height);
+        }
+        if u64::from(width) * u64::from(height) > MAX_FRAME_PIXELS {
+            bail!(
+                "Raw frame size {}x{} is too large (at most {} pixels)",
+                width,
+                height,
+                MAX_FRAME_PIXELS
+            );
+        }
+
+        Ok(Self {
+            reader,
+            kind,
+            width,
+            height,
+            fps,
+            aspect_ratio: width as f64 / height as f64 * pixel_aspect.unwrap_or(1.0),
+            pixel_format,
+            frame: vec![0; pixel_format.frame_len(width, height)],
+            rgb: Vec::new(),
+            scale_mode,
+            target,
+            scaler: FrameScaler::new(),
+            frames_read: 0,
+            title: None,
+        })
+    }
+
+    fn decode_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<RenderTarget>> {
+        if matches!(self.kind, StreamKind::Y4m) {
+            let Some(line) = read_header_line(&mut self.reader)? else {
+                return Ok(None);
+            };
+            if !line.starts_with(Y4M_FRAME_MAGIC) {
+                bail!(
+                    "Malformed Y4M stream after frame {}: expected FRAME, got {:?}",
+                    self.frames_read,
+                    truncate_for_log(&line)
+                );
+            }
+        }
+
+        let filled = read_full(&mut self.reader, &mut self.frame)?;
+        if filled < self.frame.len() {
+            if filled > 0 || matches!(self.kind, StreamKind::Y4m) {
+                crate::utils::logger::info(&format!(
+                    "raw stream ended mid-frame: frame={} got={} expected={} bytes (dropped)",
+                    self.frames_read,
+                    filled,
+                    self.frame.len()
+                ));
+            }
+            return Ok(None);
+        }
+        self.frames_read += 1;
+
+        let target = *self
+            .target
+            .read()
+            .map_err(|_| anyhow!("render target lock poisoned"))?;
+        let (bytes, order) = match self.pixel_format {
+            RawPixelFormat::Rgb24 => (self.frame.as_slice(), PixelOrder::Rgb),
+            RawPixelFormat::Bgr24 => (self.frame.as_slice(), PixelOrder::Bgr),
+            RawPixelFormat::Gray => {
+                gray_to_rgb(&self.frame, &mut self.rgb);
+                (self.rgb.as_slice(), PixelOrder::Rgb)
+            }
+            RawPixelFormat::Yuv420p => {
+                yuv420p_to_rgb(&self.frame, self.width, self.height, &mut self.rgb);
+                (self.rgb.as_slice(), PixelOrder::Rgb)
+            }
+            RawPixelFormat::Yuv444p => {
+                yuv444p_to_rgb(&self.frame, self.width, self.height, &mut self.rgb);
+                (self.rgb.as_slice(), PixelOrder::Rgb)
+            }
+        };
+        self.scaler.scale_into(
+            SourceImage {
+                bytes,
+                width: self.width,
+                height: self.height,
+                order,
+            },
+            target,
+            self.scale_mode,
+            buffer,
+        )?;
+
+        Ok(Some(target))
+    }
+}
+
+impl FrameSource for RawStreamDecoder {
+    fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    fn source_aspect_ratio(&self) -> f64 {
+        self.aspect_ratio
+    }
+
+    fn metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            width: self.width,
+            height: self.height,
+            fps: self.fps,
+            frame_count: None,
+            duration: None,
+            title: self.title.clone(),
+        }
+    }
+
+    fn seek(&mut self, _position: Duration) -> Result<()> {
+        bail!("Piped frame streams cannot seek")
+    }
+
+    fn set_scaler(&mut self, scaler: Scaler) {
+        self.scaler.set_scaler(scaler);
+    }
+
+    fn set_filters(&mut self, filters: FilterChain) {
+        self.scaler.set_filters(filters);
+    }
+
+    fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<SourceFrame>> {
+        Ok(self.decode_into(buffer)?.map(|target| SourceFrame {
+            target,
+            timestamp: None,
+            scale: self.scaler.last_report(),
+        }))
+    }
+
+    fn spawn_decoding_thread(
+        self: Box<Self>,
+        sender: Sender<Result<FrameData>>,
+        timing: DecodeTiming,
+    ) -> std::thread::JoinHandle<Result<()>> {
+        let source_fps = self.fps();
+        spawn_frame_pump(*self, sender, source_fps, timing)
+    }
+}
+
+impl FrameReader for RawStreamDecoder {
+    fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome> {
+        Ok(match self.decode_into(buffer)? {
+            Some(target) => ReadOutcome::Frame {
+                target,
+                timestamp: None,
+            },
+            None => ReadOutcome::Eof,
+        })
+    }
+}
+
+fn parse_y4m_header(line: &str) -> Result<Y4mHeader> {
+    let mut tokens = line.split_ascii_whitespace();
+    if tokens.next() != Some(Y4M_MAGIC) {
+        bail!(
+            "Not a Y4M stream (expected {}); use --raw-width/--raw-height/--raw-pix-fmt for rawvideo",
+            Y4M_MAGIC
+        );
+    }
+
+    let mut width = None;
+    let mut height = None;
+    let mut fps = None;
+    let mut pixel_aspect = None;
+    let mut pixel_format = RawPixelFormat::Yuv420p;
+    for token in tokens {
+        let Some(tag) = token.chars().next() else {
+            continue;
+        };
+        if !tag.is_ascii() {
+            bail!(
+                "Malformed Y4M header: unexpected {:?}",
+                truncate_for_log(token)
+            );
+        }
+        let value = &token[tag.len_utf8()..];
+        match tag {
+            'W' => width = Some(value.parse::<u32>().context("Invalid Y4M width")?),
+            'H' => height = Some(value.parse::<u32>().context("Invalid Y4M height")?),
+            'F' => fps = parse_ratio(value).filter(|fps| *fps > 0.0),
+            'A' => pixel_aspect = parse_ratio(value).filter(|aspect| *aspect > 0.0),
+            'C' => pixel_format = parse_y4m_colorspace(value)?,
+            _ => {}
+        }
+    }
+
+    Ok(Y4mHeader {
+        width: width.ok_or_else(|| anyhow!("Y4M header is missing W"))?,
+        height: height.ok_or_else(|| anyhow!("Y4M header is missing H"))?,
+        fps,
+        pixel_aspect,
+        pixel_format,
+    })
+}
+
+fn parse_y4m_colorspace(value: &str) -> Result<RawPixelFormat> {
+    match value {
+        "420" | "420jpeg" | "420paldv" | "420mpeg2" => Ok(RawPixelFormat::Yuv420p),
+        "444" => Ok(RawPixelFormat::Yuv444p),
+        "mono" => Ok(RawPixelFormat::Gray),
+        other => bail!(
+            "Unsupported Y4M colorspace C{} (supported: 420, 444, mono at 8 bits)",
+            other
+        ),
+    }
+}
+
+fn parse_ratio(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once(':')?;
+    let num: f64 = num.parse().ok()?;
+    let den: f64 = den.parse().ok()?;
+    (den != 0.0).then(|| num / den)
+}
+
+/// Reads one `\n`-terminated header line; `None` on a clean EOF.
+fn read_header_line(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = reader
+        .take(MAX_Y4M_HEADER_LEN as u64)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.last() != Some(&b'\n') {
+        if read < MAX_Y4M_HEADER_LEN {
+            // Stream ended inside a header: treat like a truncated frame
+            return Ok(None);
+        }
+        bail!("Y4M header line exceeds {} bytes", MAX_Y4M_HEADER_LEN);
+    }
+    line.pop();
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+/// Like `read_exact`, but reports how much was read before EOF.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(filled)
+}
+
+fn truncate_for_log(line: &str) -> String {
+    line.chars().take(32).collect()
+}
+
+fn chroma_420_len(width: u32, height: u32) -> usize {
+    width.div_ceil(2) as usize * height.div_ceil(2) as usize
+}
+
+fn gray_to_rgb(gray: &[u8], rgb: &mut Vec<u8>) {
+    rgb.clear();
+    rgb.extend(gray.iter().flat_map(|&y| [y, y, y]));
+}
+
+fn yuv420p_to_rgb(frame: &[u8], width: u32, height: u32, rgb: &mut Vec<u8>) {
+    let (width, height) = (width as usize, height as usize);
+    let chroma_width = width.div_ceil(2);
+    let (luma, chroma) = frame.split_at(width * height);
+    let (u_plane, v_plane) = chroma.split_at(chroma.len() / 2);
+
+    rgb.clear();
+    rgb.reserve(width * height * 3);
+    for y in 0..height {
+        let chroma_row = (y / 2) * chroma_width;
+        for x in 0..width {
+            let chroma_index = chroma_row + x / 2;
+            rgb.extend_from_slice(&bt601_to_rgb(
+                luma[y * width + x],
+                u_plane[chroma_index],
+                v_plane[chroma_index],
+            ));
+        }
+    }
+}
+
+fn yuv444p_to_rgb(frame: &[u8], width: u32, height: u32, rgb: &mut Vec<u8>) {
+    let plane_len = width as usize * height as usize;
+    let (luma, chroma) = frame.split_at(plane_len);
+    let (u_plane, v_plane) = chroma.split_at(plane_len);
+
+    rgb.clear();
+    rgb.reserve(plane_len * 3);
+    for ((&y, &u), &v) in luma.iter().zip(u_plane).zip(v_plane) {
+        rgb.extend_from_slice(&bt601_to_rgb(y, u, v));
+    }
+}
+
+/// Limited-range BT.601 in 8.8 fixed point, which is what ffmpeg emits by
+/// default for SD and web content.
+fn bt601_to_rgb(y: u8, u: u8, v: u8) -> [u8; 3] {
+    let c = 298 * (y as i32 - 16);
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+    [
+        ((c + 409 * e + 128) >> 8).clamp(0, 255) as u8,
+        ((c - 100 * d - 208 * e + 128) >> 8).clamp(0, 255) as u8,
+        ((c + 516 * d + 128) >> 8).clamp(0, 255) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn decoder_for(bytes: Vec<u8>, raw_format: Option<RawVideoFormat>) -> RawStreamDecoder {
+        RawStreamDecoder::from_reader(
+            Box::new(Cursor::new(bytes)),
+            raw_format,
+            Arc::new(RwLock::new(RenderTarget::new(2, 2))),
+            ScaleMode::Fit,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn y4m_header_reads_size_rate_aspect_and_colorspace() {
+        let header =
+            parse_y4m_header("YUV4MPEG2 W640 H360 F30000:1001 Ip A4:3 C444 XYSCSS=444").unwrap();
+
+        assert_eq!((header.width, header.height), (640, 360));
+        assert!((header.fps.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(header.pixel_aspect, Some(4.0 / 3.0));
+        assert_eq!(header.pixel_format, RawPixelFormat::Yuv444p);
+        assert!(parse_y4m_header("YUV4MPEG2 W2 H2 C422").is_err());
+        assert!(parse_y4m_header("RIFF").is_err());
+    }
+
+    #[test]
+    fn malformed_y4m_headers_are_errors_not_panics() {
+        assert!(parse_y4m_header("YUV4MPEG2 W2 H2 \u{FFFD}C420").is_err());
+
+        let open = |header: &str| {
+            RawStreamDecoder::from_reader(
+                Box::new(Cursor::new(header.as_bytes().to_vec())),
+                None,
+                Arc::new(RwLock::new(RenderTarget::new(2, 2))),
+                ScaleMode::Fit,
+                None,
+            )
+        };
+        assert!(open("YUV4MPEG2 W7680 H4320\n").is_ok());
+        assert!(open("YUV4MPEG2 W100000 H100000\n").is_err());
+    }
+
+    #[test]
+    fn bt601_maps_video_range_to_full_range() {
+        assert_eq!(bt601_to_rgb(16, 128, 128), [0, 0, 0]);
+        assert_eq!(bt601_to_rgb(235, 128, 128), [255, 255, 255]);
+        let red = bt601_to_rgb(81, 90, 240);
+        assert!(red[0] > 250 && red[1] < 5 && red[2] < 5);
+    }
+
+    #[test]
+    fn y4m_stream_yields_whole_frames_and_drops_a_trailing_partial_one() {
+        let mut stream = b"YUV4MPEG2 W2 H2 F10:1 C420jpeg\n".to_vec();
+        for luma in [235u8, 16] {
+            stream.extend_from_slice(b"FRAME\n");
+            stream.extend_from_slice(&[luma, luma, luma, luma, 128, 128]);
+        }
+        stream.extend_from_slice(b"FRAME\n");
+        stream.extend_from_slice(&[235, 235]);
+
+        let mut decoder = decoder_for(stream, None);
+        assert_eq!(decoder.fps(), 10.0);
+
+        let mut buffer = Vec::new();
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_some());
+        assert_eq!(&buffer[..3], &[255, 255, 255]);
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_some());
+        assert_eq!(&buffer[..3], &[0, 0, 0]);
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_none());
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_none());
+    }
+
+    #[test]
+    fn rawvideo_bgr_frames_are_swapped_to_rgb() {
+        let format = RawVideoFormat {
+            width: 2,
+            height: 2,
+            pixel_format: RawPixelFormat::Bgr24,
+        };
+        let mut decoder = decoder_for([30u8, 20, 10].repeat(4), Some(format));
+
+        let mut buffer = Vec::new();
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_some());
+        assert_eq!(&buffer[..3], &[10, 20, 30]);
+        assert!(decoder.read_frame_into(&mut buffer).unwrap().is_none());
+    }
+}
@@ -0,0 +1,547 @@
+use super::filter::{CanvasArea, FilterChain};
+use anyhow::{anyhow, Result};
+use fast_image_resize as fr;
+use fr::images::{Image, ImageRef};
+use fr::{FilterType, ResizeAlg, ResizeOptions};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderTarget {
+    pub pixel_width: u32,
+    pub pixel_height: u32,
+    /// Region of the source that is scaled onto the canvas
+    pub crop: CropRect,
+}
+
+impl RenderTarget {
+    pub fn new(pixel_width: u32, pixel_height: u32) -> Self {
+        Self {
+            pixel_width: pixel_width.max(1),
+            pixel_height: pixel_height.max(2),
+            crop: CropRect::FULL,
+        }
+    }
+
+    /// Same crop at a new canvas size.
+    pub fn resized(self, pixel_width: u32, pixel_height: u32) -> Self {
+        Self {
+            crop: self.crop,
+            ..Self::new(pixel_width, pixel_height)
+        }
+    }
+}
+
+/// A region of the source in fractions of its size, stored as parts per
+/// `CropRect::UNIT` so targets stay comparable with `==`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRect {
+    pub const UNIT: u32 = 10_000;
+    pub const FULL: Self = Self {
+        x: 0,
+        y: 0,
+        width: Self::UNIT,
+        height: Self::UNIT,
+    };
+
+    /// Builds a crop from fractions of the source, clamped inside it.
+    pub fn from_fractions(x: f64, y: f64, width: f64, height: f64) -> Self {
+        let unit = f64::from(Self::UNIT);
+        let width = (width.clamp(0.0, 1.0) * unit).round().max(1.0) as u32;
+        let height = (height.clamp(0.0, 1.0) * unit).round().max(1.0) as u32;
+        let x = ((x.max(0.0) * unit).round() as u32).min(Self::UNIT - width);
+        let y = ((y.max(0.0) * unit).round() as u32).min(Self::UNIT - height);
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_full(self) -> bool {
+        self == Self::FULL
+    }
+
+    /// `(left, top, width, height)` in source pixels.
+    pub(crate) fn in_pixels(self, source_width: u32, source_height: u32) -> (f64, f64, f64, f64) {
+        let unit = f64::from(Self::UNIT);
+        let (w, h) = (f64::from(source_width), f64::from(source_height));
+        (
+            w * f64::from(self.x) / unit,
+            h * f64::from(self.y) / unit,
+            (w * f64::from(self.width) / unit).max(1.0),
+            (h * f64::from(self.height) / unit).max(1.0),
+        )
+    }
+}
+
+/// Resampling filter used to fit frames onto the canvas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scaler {
+    /// Blocky but exact; keeps pixel art crisp
+    Nearest,
+    Bilinear,
+    /// Bilinear cost with close to bicubic quality on downscales
+    #[default]
+    Hamming,
+    CatmullRom,
+    Lanczos3,
+    /// Supersampled box average; smooth, stable edges for threshold modes
+    Area,
+}
+
+/// Source pixels per destination pixel taken by `Scaler::Area` before its
+/// box filter
+const AREA_SUPERSAMPLING: u8 = 4;
+
+impl Scaler {
+    pub const ALL: [Self; 6] = [
+        Self::Nearest,
+        Self::Bilinear,
+        Self::Hamming,
+        Self::CatmullRom,
+        Self::Lanczos3,
+        Self::Area,
+    ];
+
+    /// A scaler by its `label`, ignoring case.
+    pub fn parse(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scaler| scaler.label().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown scaler '{}'; use nearest, bilinear, hamming, catmull-rom, lanczos3 or area",
+                    name
+                )
+            })
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Bilinear => "bilinear",
+            Self::Hamming => "hamming",
+            Self::CatmullRom => "catmull-rom",
+            Self::Lanczos3 => "lanczos3",
+            Self::Area => "area",
+        }
+    }
+
+    fn resize_alg(self) -> ResizeAlg {
+        match self {
+            Self::Nearest => ResizeAlg::Nearest,
+            Self::Bilinear => ResizeAlg::Convolution(FilterType::Bilinear),
+            Self::Hamming => ResizeAlg::Convolution(FilterType::Hamming),
+            Self::CatmullRom => ResizeAlg::Convolution(FilterType::CatmullRom),
+            Self::Lanczos3 => ResizeAlg::Convolution(FilterType::Lanczos3),
+            Self::Area => ResizeAlg::SuperSampling(FilterType::Box, AREA_SUPERSAMPLING),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    CropToFill,
+    Fit,
+}
+
+/// Channel order of a packed 3-byte source frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PixelOrder {
+    Bgr,
+    Rgb,
+}
+
+/// Borrowed, tightly packed 3-channel source pixels
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SourceImage<'a> {
+    pub bytes: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub order: PixelOrder,
+}
+
+/// Time spent fitting one decoded frame onto the render target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScaleReport {
+    pub resize_time: Duration,
+    pub letterbox_time: Duration,
+    pub filter_time: Duration,
+    pub resizer_internal_bytes: u64,
+}
+
+/// SIMD resize + letterbox/crop onto an RGB canvas of the current render target.
+///
+/// Shared by every frame source so all inputs scale identically.
+pub(crate) struct FrameScaler {
+    resizer: fr::Resizer,
+    resize_options: ResizeOptions,
+    resized_image: Option<Image<'static>>,
+    filters: FilterChain,
+    filter_scratch: Vec<u8>,
+    last_report: ScaleReport,
+}
+
+impl FrameScaler {
+    pub(crate) fn new() -> Self {
+        Self {
+            resizer: fr::Resizer::new(),
+            resize_options: ResizeOptions::new().resize_alg(Scaler::default().resize_alg()),
+            resized_image: None,
+            filters: FilterChain::default(),
+            filter_scratch: Vec::new(),
+            last_report: ScaleReport::default(),
+        }
+    }
+
+    pub(crate) fn set_scaler(&mut self, scaler: Scaler) {
+        self.resize_options = self.resize_options.resize_alg(scaler.resize_alg());
+        self.resizer.reset_internal_buffers();
+    }
+
+    /// Filters run over the visible part of every canvas from now on.
+    pub(crate) fn set_filters(&mut self, filters: FilterChain) {
+        self.filters = filters;
+    }
+
+    /// Report of the most recent `scale_into` call.
+    pub(crate) fn last_report(&self) -> ScaleReport {
+        self.last_report
+    }
+
+    pub(crate) fn scale_into(
+        &mut self,
+        source: SourceImage<'_>,
+        target: RenderTarget,
+        scale_mode: ScaleMode,
+        buffer: &mut Vec<u8>,
+    ) -> Result<ScaleReport> {
+        let start_resize = Instant::now();
+        let (new_w, new_h) = scaled_dimensions(source.width, source.height, target, scale_mode);
+        let (crop_left, crop_top, crop_width, crop_height) =
+            target.crop.in_pixels(source.width, source.height);
+
+        let src_image = ImageRef::new(
+            source.width,
+            source.height,
+            source.bytes,
+            fr::PixelType::U8x3,
+        )?;
+        let recreate_resized = self
+            .resized_image
+            .as_ref()
+            .map(|image| image.width() != new_w || image.height() != new_h)
+            .unwrap_or(true);
+        if recreate_resized {
+            self.resizer.reset_internal_buffers();
+            self.resized_image = Some(Image::new(new_w, new_h, fr::PixelType::U8x3));
+        }
+        let dst_image = self
+            .resized_image
+            .as_mut()
+            .ok_or_else(|| anyhow!("resize buffer was not initialized"))?;
+        let options = if target.crop.is_full() {
+            self.resize_options
+        } else {
+            self.resize_options
+                .crop(crop_left, crop_top, crop_width, crop_height)
+        };
+        self.resizer.resize(&src_image, dst_image, Some(&options))?;
+        let resize_time = start_resize.elapsed();
+        let resizer_internal_bytes = self.resizer.size_of_internal_buffers() as u64;
+
+        let start_letterbox = Instant::now();
+        let canvas_len = (target.pixel_width * target.pixel_height * 3) as usize;
+        buffer.clear();
+        buffer.resize(canvas_len, 0);
+
+        let visible = blit_resized_to_canvas(
+            dst_image.buffer(),
+            new_w,
+            new_h,
+            source.order,
+            target,
+            buffer,
+        );
+        let letterbox_time = start_letterbox.elapsed();
+
+        let start_filter = Instant::now();
+        if !self.filters.is_empty() {
+            self.filters.apply(
+                buffer,
+                target.pixel_width,
+                visible,
+                &mut self.filter_scratch,
+            );
+        }
+
+        self.last_report = ScaleReport {
+            resize_time,
+            letterbox_time,
+            filter_time: start_filter.elapsed(),
+            resizer_internal_bytes,
+        };
+        Ok(self.last_report)
+    }
+}
+
+/// Size the source's crop region is resized to before it is centered on
+/// the canvas; larger than the canvas when cropping to fill.
+pub(crate) fn scaled_dimensions(
+    orig_w: u32,
+    orig_h: u32,
+    target: RenderTarget,
+    scale_mode: ScaleMode,
+) -> (u32, u32) {
+    let (_, _, crop_w, crop_h) = target.crop.in_pixels(orig_w.max(1), orig_h.max(1));
+    let orig_w = (crop_w.round() as u32).max(1);
+    let orig_h = (crop_h.round() as u32).max(1);
+    let scale_w = target.pixel_width as f64 / orig_w as f64;
+    let scale_h = target.pixel_height as f64 / orig_h as f64;
+    let scale = match scale_mode {
+        ScaleMode::CropToFill => scale_w.max(scale_h),
+        ScaleMode::Fit => scale_w.min(scale_h),
+    };
+
+    match scale_mode {
+        ScaleMode::CropToFill => (
+            ((orig_w as f64 * scale).ceil() as u32)
+                .max(target.pixel_width)
+                .max(1),
+            ((orig_h as f64 * scale).ceil() as u32)
+                .max(target.pixel_height)
+                .max(1),
+        ),
+        ScaleMode::Fit => (
+            ((orig_w as f64 * scale).floor() as u32)
+                .min(target.pixel_width)
+                .max(1),
+            ((orig_h as f64 * scale).floor() as u32)
+                .min(target.pixel_height)
+                .max(1),
+        ),
+    }
+}
+
+/// Centers the resized image on the canvas and returns the area it covers.
+fn blit_resized_to_canvas(
+    src: &[u8],
+    src_width: u32,
+    src_height: u32,
+    order: PixelOrder,
+    target: RenderTarget,
+    dst: &mut [u8],
+) -> CanvasArea {
+    let visible_width = src_width.min(target.pixel_width);
+    let visible_height = src_height.min(target.pixel_height);
+    let src_x = src_width.saturating_sub(visible_width) / 2;
+    let src_y = src_height.saturating_sub(visible_height) / 2;
+    let dst_x = target.pixel_width.saturating_sub(visible_width) / 2;
+    let dst_y = target.pixel_height.saturating_sub(visible_height) / 2;
+    let visible = CanvasArea {
+        x: dst_x,
+        y: dst_y,
+        width: visible_width,
+        height: visible_height,
+    };
+    if visible_width == 0 || visible_height == 0 {
+        return visible;
+    }
+
+    let copy_len = visible_width as usize * 3;
+
+    for row in 0..visible_height {
+        let src_offset = ((src_y + row) * src_width + src_x) as usize * 3;
+        let dst_offset = ((dst_y + row) * target.pixel_width + dst_x) as usize * 3;
+
+        if src_offset + copy_len <= src.len() && dst_offset + copy_len <= dst.len() {
+            let src_row = &src[src_offset..src_offset + copy_len];
+            let dst_row = &mut dst[dst_offset..dst_offset + copy_len];
+            match order {
+                PixelOrder::Bgr => crate::utils::simd::bgr_to_rgb(src_row, dst_row),
+                PixelOrder::Rgb => dst_row.copy_from_slice(src_row),
+            }
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalers_parse_by_label_and_name_the_choices_otherwise() {
+        for scaler in Scaler::ALL {
+            assert_eq!(
+                Scaler::parse(&scaler.label().to_uppercase()).unwrap(),
+                scaler
+            );
+        }
+        let error = Scaler::parse("lanczos").unwrap_err().to_string();
+        assert!(
+            error.contains("lanczos3") && error.contains("area"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn crop_to_fill_scaled_dimensions_cover_target_after_rounding() {
+        let target = RenderTarget::new(101, 58);
+        let (width, height) = scaled_dimensions(1920, 1080, target, ScaleMode::CropToFill);
+
+        assert!(width >= target.pixel_width);
+        assert!(height >= target.pixel_height);
+    }
+
+    #[test]
+    fn fit_scaled_dimensions_never_exceed_target_after_rounding() {
+        let target = RenderTarget::new(101, 58);
+        let (width, height) = scaled_dimensions(1920, 1080, target, ScaleMode::Fit);
+
+        assert!(width <= target.pixel_width);
+        assert!(height <= target.pixel_height);
+    }
+
+    #[test]
+    fn crop_scales_only_the_selected_region() {
+        let mut target = RenderTarget::new(4, 2);
+        target.crop = CropRect::from_fractions(0.5, 0.5, 0.5, 0.5);
+        assert_eq!(scaled_dimensions(400, 200, target, ScaleMode::Fit), (4, 2));
+
+        // Left half red, right half blue; a crop of the right half is blue.
+        let mut source = Vec::new();
+        for _ in 0..4 {
+            for x in 0..8 {
+                source.extend_from_slice(if x < 4 { &[255, 0, 0] } else { &[0, 0, 255] });
+            }
+        }
+        let mut target = RenderTarget::new(2, 2);
+        target.crop = CropRect::from_fractions(0.5, 0.0, 0.5, 1.0);
+        let mut buffer = Vec::new();
+        FrameScaler::new()
+            .scale_into(
+                SourceImage {
+                    bytes: &source,
+                    width: 8,
+                    height: 4,
+                    order: PixelOrder::Rgb,
+                },
+                target,
+                ScaleMode::Fit,
+                &mut buffer,
+            )
+            .unwrap();
+
+        // The resize filter may pull a little from just outside the crop.
+        assert_eq!(buffer.len(), 2 * 2 * 3);
+        assert!(buffer
+            .chunks_exact(3)
+            .all(|pixel| pixel[2] > 200 && pixel[0] < 50));
+    }
+
+    #[test]
+    fn crop_fractions_are_clamped_inside_the_source() {
+        let crop = CropRect::from_fractions(0.9, -0.2, 0.5, 2.0);
+        assert_eq!(
+            crop,
+            CropRect {
+                x: 5_000,
+                y: 0,
+                width: 5_000,
+                height: 10_000
+            }
+        );
+        assert!(RenderTarget::new(8, 4).resized(2, 2).crop.is_full());
+    }
+
+    #[test]
+    fn rgb_sources_are_copied_without_channel_swap() {
+        let mut scaler = FrameScaler::new();
+        let source = [10u8, 20, 30, 10, 20, 30, 10, 20, 30, 10, 20, 30];
+        let mut buffer = Vec::new();
+        scaler
+            .scale_into(
+                SourceImage {
+                    bytes: &source,
+                    width: 2,
+                    height: 2,
+                    order: PixelOrder::Rgb,
+                },
+                RenderTarget::new(2, 2),
+                ScaleMode::Fit,
+                &mut buffer,
+            )
+            .unwrap();
+
+        assert_eq!(&buffer[..3], &[10, 20, 30]);
+    }
+
+    #[test]
+    fn bgr_sources_are_swapped_to_rgb() {
+        let mut scaler = FrameScaler::new();
+        let source = [30u8, 20, 10, 30, 20, 10, 30, 20, 10, 30, 20, 10];
+        let mut buffer = Vec::new();
+        scaler
+            .scale_into(
+                SourceImage {
+                    bytes: &source,
+                    width: 2,
+                    height: 2,
+                    order: PixelOrder::Bgr,
+                },
+                RenderTarget::new(2, 2),
+                ScaleMode::Fit,
+                &mut buffer,
+            )
+            .unwrap();
+
+        assert_eq!(&buffer[..3], &[10, 20, 30]);
+    }
+
+    #[test]
+    fn nearest_keeps_source_colors_and_area_averages_them() {
+        // 1-pixel black/white stripes, 8x8 down to 2x2.
+        let mut source = Vec::new();
+        for _ in 0..8 {
+            for x in 0..8 {
+                source.extend_from_slice(&[if x % 2 == 0 { 0 } else { 255 }; 3]);
+            }
+        }
+        let scale = |scaler: Scaler| {
+            let mut scaler_state = FrameScaler::new();
+            scaler_state.set_scaler(scaler);
+            let mut buffer = Vec::new();
+            scaler_state
+                .scale_into(
+                    SourceImage {
+                        bytes: &source,
+                        width: 8,
+                        height: 8,
+                        order: PixelOrder::Rgb,
+                    },
+                    RenderTarget::new(2, 2),
+                    ScaleMode::Fit,
+                    &mut buffer,
+                )
+                .unwrap();
+            buffer
+        };
+
+        assert!(scale(Scaler::Nearest)
+            .iter()
+            .all(|value| *value == 0 || *value == 255));
+        assert!(scale(Scaler::Area)
+            .iter()
+            .all(|value| value.abs_diff(128) <= 2));
+    }
+}
@@ -0,0 +1,261 @@
+use super::filter::FilterChain;
+use super::frame_data::FrameData;
+use super::image_sequence::ImageSequenceDecoder;
+use super::pattern::PatternSource;
+use super::pool::BufferPool;
+use super::raw::{RawStreamDecoder, RawVideoFormat};
+use super::scale::{RenderTarget, ScaleMode, ScaleReport, Scaler};
+use super::temporal::TemporalMode;
+use crate::sync::{DropDeadline, PauseGate};
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Input hints that only apply to some kinds of source
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceOptions {
+    /// Frame rate for image sequences and rawvideo; ignored for video files
+    pub input_fps: Option<f64>,
+    /// Geometry for headerless rawvideo; `None` expects Y4M on pipes
+    pub raw_format: Option<RawVideoFormat>,
+    /// Resampling filter; `None` uses the default
+    pub scaler: Option<Scaler>,
+    /// Applied to every canvas after scaling
+    pub filters: FilterChain,
+}
+
+/// How the decoder thread times the frames it sends, and what the player
+/// feeds back to it
+#[derive(Debug, Clone)]
+pub struct DecodeTiming {
+    pub playback_fps: f64,
+    /// Conversion to `playback_fps` for sources timed by frame index
+    pub temporal: TemporalMode,
+    /// The player's drop deadline; frames due before it are skipped
+    /// without decoding when the source can do that
+    pub skip_late: Option<DropDeadline>,
+    /// Where sent frames return their buffers for reuse
+    pub recycle: Option<BufferPool>,
+    /// The player's pause state; paused time does not count when pacing
+    pub pause: Option<PauseGate>,
+}
+
+impl DecodeTiming {
+    /// Frames re-timed to `playback_fps` without conversion.
+    pub fn retimed(playback_fps: f64) -> Self {
+        Self {
+            playback_fps,
+            temporal: TemporalMode::Off,
+            skip_late: None,
+            recycle: None,
+            pause: None,
+        }
+    }
+
+    /// Whether frames keep the source's speed at a different rate.
+    pub fn converts_rate(&self, source_fps: f64) -> bool {
+        self.temporal != TemporalMode::Off
+            && source_fps > 0.0
+            && (source_fps - self.playback_fps).abs() >= 1e-6
+    }
+}
+
+/// What a source knows about itself before decoding starts
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SourceMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    /// `None` for pipes and containers that do not report it
+    pub frame_count: Option<u64>,
+    pub duration: Option<Duration>,
+    pub title: Option<String>,
+}
+
+impl SourceMetadata {
+    pub(crate) fn duration_from_frames(frame_count: u64, fps: f64) -> Option<Duration> {
+        (fps.is_finite() && fps > 0.0).then(|| Duration::from_secs_f64(frame_count as f64 / fps))
+    }
+}
+
+/// Audio a source generated for playback, deleted when dropped
+#[derive(Debug)]
+pub struct CompanionAudio {
+    path: PathBuf,
+}
+
+impl CompanionAudio {
+    /// Takes ownership of the temporary file at `path`.
+    pub fn temporary(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for CompanionAudio {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.path) {
+            crate::utils::logger::debug(&format!(
+                "failed to remove {}: {}",
+                self.path.display(),
+                error
+            ));
+        }
+    }
+}
+
+/// One synchronously decoded canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceFrame {
+    pub target: RenderTarget,
+    /// Presentation time when the source has its own timing (GIF delays);
+    /// `None` means "frame index / fps".
+    pub timestamp: Option<Duration>,
+    /// Resize and letterbox share of the read; the rest is decoding.
+    pub scale: ScaleReport,
+}
+
+/// A decoder that fills RGB canvases sized to the shared `RenderTarget`
+///
+/// Object safe so the player and export can hold a `Box<dyn FrameSource>`
+/// without knowing whether frames come from OpenCV, images or a pipe.
+pub trait FrameSource: Send {
+    fn fps(&self) -> f64;
+
+    fn source_aspect_ratio(&self) -> f64;
+
+    fn metadata(&self) -> SourceMetadata;
+
+    /// Moves the read position; sources that cannot seek return an error and
+    /// stay where they were.
+    fn seek(&mut self, position: Duration) -> Result<()>;
+
+    fn set_scaler(&mut self, scaler: Scaler);
+
+    /// Replaces the filters run over each canvas after it is scaled.
+    fn set_filters(&mut self, filters: FilterChain);
+
+    /// Synchronous read used outside the decoder thread (export, tests).
+    fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<SourceFrame>>;
+
+    /// Audio generated alongside the frames, used when no `-a` is given.
+    /// Written on demand; keep the returned file alive while it plays.
+    fn companion_audio(&self) -> Result<Option<CompanionAudio>> {
+        Ok(None)
+    }
+
+    /// Moves the source onto its own decoder thread, paced against
+    /// `timing` and feeding `sender` until EOF or until the receiver is
+    /// dropped.
+    fn spawn_decoding_thread(
+        self: Box<Self>,
+        sender: Sender<Result<FrameData>>,
+        timing: DecodeTiming,
+    ) -> std::thread::JoinHandle<Result<()>>;
+}
+
+/// Opens whatever `-v` pointed at.
+///
+/// `pattern:` specs, pipes (`-`), Y4M files and explicit rawvideo come
+/// first, then image directories, globs, stills and GIFs; anything else is
+/// handed to OpenCV.
+pub fn open_source(
+    path: &Path,
+    target: Arc<RwLock<RenderTarget>>,
+    scale_mode: ScaleMode,
+    options: SourceOptions,
+) -> Result<Box<dyn FrameSource>> {
+    let mut source = open_unfiltered(path, target, scale_mode, &options)?;
+    source.set_scaler(options.scaler.unwrap_or_default());
+    source.set_filters(options.filters);
+    Ok(source)
+}
+
+fn open_unfiltered(
+    path: &Path,
+    target: Arc<RwLock<RenderTarget>>,
+    scale_mode: ScaleMode,
+    options: &SourceOptions,
+) -> Result<Box<dyn FrameSource>> {
+    if let Some(pattern) = PatternSource::open(path, target.clone(), scale_mode)? {
+        return Ok(Box::new(pattern));
+    }
+    if let Some(raw) = RawStreamDecoder::open(
+        path,
+        options.raw_format,
+        target.clone(),
+        scale_mode,
+        options.input_fps,
+    )? {
+        return Ok(Box::new(raw));
+    }
+    if let Some(images) =
+        ImageSequenceDecoder::open(path, target.clone(), scale_mode, options.input_fps)?
+    {
+        return Ok(Box::new(images));
+    }
+
+    open_video(path, target, scale_mode)
+}
+
+#[cfg(feature = "opencv")]
+fn open_video(
+    path: &Path,
+    target: Arc<RwLock<RenderTarget>>,
+    scale_mode: ScaleMode,
+) -> Result<Box<dyn FrameSource>> {
+    let decoder =
+        super::video::VideoDecoder::new(path.to_string_lossy().as_ref(), target, scale_mode)?;
+    Ok(Box::new(decoder))
+}
+
+#[cfg(not(feature = "opencv"))]
+fn open_video(
+    path: &Path,
+    _target: Arc<RwLock<RenderTarget>>,
+    _scale_mode: ScaleMode,
+) -> Result<Box<dyn FrameSource>> {
+    anyhow::bail!(
+        "{} needs OpenCV, but this build has the `opencv` feature disabled; \
+         use images, a GIF, a .y4m file, a '-' pipe or a pattern: spec instead",
+        path.display()
+    )
+}
+
+pub(crate) fn title_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_from_frames_needs_a_usable_fps() {
+        assert_eq!(
+            SourceMetadata::duration_from_frames(48, 24.0),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(SourceMetadata::duration_from_frames(48, 0.0), None);
+    }
+
+    #[test]
+    fn missing_image_input_is_an_error_not_a_video_fallback() {
+        let target = Arc::new(RwLock::new(RenderTarget::new(4, 2)));
+        let result = open_source(
+            Path::new("definitely-missing-frame.png"),
+            target,
+            ScaleMode::Fit,
+            SourceOptions::default(),
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use super::frame_data::FrameData;
+use std::time::Duration;
+
+/// How frames are converted when the playback rate differs from the source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TemporalMode {
+    /// Re-time source frames, so playback runs faster or slower
+    #[default]
+    Off,
+    /// Cross-fade neighbouring frames; average them when slowing down
+    Blend,
+    /// Block-motion-compensated in-betweens; average when slowing down
+    Motion,
+}
+
+/// Side of the square blocks motion is estimated for
+const BLOCK: usize = 8;
+/// Furthest a block may move between two source frames, in pixels
+const SEARCH_RADIUS: i32 = 6;
+
+/// Resamples index-timed source frames onto the playback frame rate
+///
+/// Above the source rate each output frame is built from the two source
+/// frames around it; below it, every output frame is the average of the
+/// source frames it covers. Timestamps are on the source timeline, so
+/// playback keeps its normal speed.
+pub(crate) struct FrameResampler {
+    mode: TemporalMode,
+    source_fps: f64,
+    playback_fps: f64,
+    source_index: u64,
+    next_output: u64,
+    previous: Option<FrameData>,
+    sum: Vec<u32>,
+    summed: u32,
+    sum_size: (u32, u32),
+}
+
+impl FrameResampler {
+    /// `None` when the mode is off or the rates already match.
+    pub(crate) fn new(mode: TemporalMode, source_fps: f64, playback_fps: f64) -> Option<Self> {
+        let usable = |fps: f64| fps.is_finite() && fps > 0.0;
+        if mode == TemporalMode::Off
+            || !usable(source_fps)
+            || !usable(playback_fps)
+            || (source_fps - playback_fps).abs() < 1e-6
+        {
+            return None;
+        }
+        Some(Self {
+            mode,
+            source_fps,
+            playback_fps,
+            source_index: 0,
+            next_output: 0,
+            previous: None,
+            sum: Vec::new(),
+            summed: 0,
+            sum_size: (0, 0),
+        })
+    }
+
+    /// Feeds the next source frame; finished output frames go to `emit`,
+    /// which returns `false` once nobody is listening.
+    pub(crate) fn push(
+        &mut self,
+        frame: FrameData,
+        emit: &mut impl FnMut(FrameData) -> bool,
+    ) -> bool {
+        let index = self.source_index;
+        self.source_index += 1;
+        if self.playback_fps > self.source_fps {
+            self.interpolate(index, frame, emit)
+        } else {
+            self.average(index, frame, emit)
+        }
+    }
+
+    /// Emits whatever is still being averaged at end of stream.
+    pub(crate) fn finish(&mut self, emit: &mut impl FnMut(FrameData) -> bool) -> bool {
+        if self.summed == 0 {
+            return true;
+        }
+        let frame = self.take_average();
+        emit(frame)
+    }
+
+    fn output_time(&self, output: u64) -> Duration {
+        Duration::from_secs_f64(output as f64 / self.playback_fps)
+    }
+
+    /// Source position of an output frame, in source frames.
+    fn source_position(&self, output: u64) -> f64 {
+        output as f64 * self.source_fps / self.playback_fps
+    }
+
+    fn interpolate(
+        &mut self,
+        index: u64,
+        frame: FrameData,
+        emit: &mut impl FnMut(FrameData) -> bool,
+    ) -> bool {
+        let previous = self
+            .previous
+            .take()
+            .filter(|previous| (previous.width, previous.height) == (frame.width, frame.height));
+        while self.source_position(self.next_output) <= index as f64 + 1e-9 {
+            let timestamp = self.output_time(self.next_output);
+            let fraction = self.source_position(self.next_output) - (index as f64 - 1.0);
+            let buffer = match &previous {
+                Some(previous) if fraction < 1.0 - 1e-9 => match self.mode {
+                    TemporalMode::Motion => motion_interpolate(
+                        &previous.buffer,
+                        &frame.buffer,
+                        frame.width as usize,
+                        frame.height as usize,
+                        fraction,
+                    ),
+                    _ => blend(&previous.buffer, &frame.buffer, fraction),
+                },
+                _ => frame.buffer.clone(),
+            };
+            self.next_output += 1;
+            if !emit(FrameData::new(buffer, frame.width, frame.height, timestamp)) {
+                return false;
+            }
+        }
+        self.previous = Some(frame);
+        true
+    }
+
+    fn average(
+        &mut self,
+        index: u64,
+        frame: FrameData,
+        emit: &mut impl FnMut(FrameData) -> bool,
+    ) -> bool {
+        let mut alive = true;
+        // Source frame `index` belongs to the last output that starts at or
+        // before it.
+        let belongs_to = (index as f64 * self.playback_fps / self.source_fps + 1e-9).floor() as u64;
+        let resized = self.sum_size != (frame.width, frame.height);
+        if self.summed > 0 && (belongs_to >= self.next_output || resized) {
+            let finished = self.take_average();
+            alive = emit(finished);
+        }
+        if self.summed == 0 {
+            self.next_output = self.next_output.max(belongs_to) + 1;
+            self.sum_size = (frame.width, frame.height);
+            self.sum.clear();
+            self.sum.resize(frame.buffer.len(), 0);
+        }
+        for (total, value) in self.sum.iter_mut().zip(&frame.buffer) {
+            *total += u32::from(*value);
+        }
+        self.summed += 1;
+        alive
+    }
+
+    fn take_average(&mut self) -> FrameData {
+        let count = self.summed.max(1);
+        let buffer = self
+            .sum
+            .iter()
+            .map(|total| ((total + count / 2) / count) as u8)
+            .collect();
+        self.summed = 0;
+        let (width, height) = self.sum_size;
+        FrameData::new(
+            buffer,
+            width,
+            height,
+            self.output_time(self.next_output - 1),
+        )
+    }
+}
+
+/// `previous` faded into `next` by `fraction`.
+fn blend(previous: &[u8], next: &[u8], fraction: f64) -> Vec<u8> {
+    let weight = (fraction.clamp(0.0, 1.0) * 256.0).round() as u32;
+    previous
+        .iter()
+        .zip(next)
+        .map(|(a, b)| ((u32::from(*a) * (256 - weight) + u32::from(*b) * weight + 128) >> 8) as u8)
+        .collect()
+}
+
+/// In-between frame at `fraction` assuming each block moved in a straight
+/// line.
+///
+/// Motion is first estimated for every block of `previous` against `next`.
+/// Each output block then takes whichever of the nearby vectors (or none)
+/// best matches its start in `previous` with its end in `next`, so flat
+/// areas uncovered by a moving object follow the object rather than being
+/// cross-faded with it.
+fn motion_interpolate(
+    previous: &[u8],
+    next: &[u8],
+    width: usize,
+    height: usize,
+    fraction: f64,
+) -> Vec<u8> {
+    if width == 0
+        || height == 0
+        || previous.len() < width * height * 3
+        || next.len() < width * height * 3
+    {
+        return blend(previous, next, fraction);
+    }
+    let previous_luma = luma_plane(previous);
+    let next_luma = luma_plane(next);
+    let columns = width.div_ceil(BLOCK);
+    let rows = height.div_ceil(BLOCK);
+    let block_area = |column: usize, row: usize| {
+        let (x, y) = (column * BLOCK, row * BLOCK);
+        (x..(x + BLOCK).min(width), y..(y + BLOCK).min(height))
+    };
+    // Sum of differences between `previous` moved by `back` and `next`
+    // moved by `ahead` over one block; `None` once it exceeds `limit`.
+    let sad = |column: usize, row: usize, back: (isize, isize), ahead: (isize, isize), limit| {
+        let (xs, ys) = block_area(column, row);
+        let mut total = 0u32;
+        for y in ys {
+            for x in xs.clone() {
+                let a = previous_luma[clamped(x, y, back, width, height)];
+                let b = next_luma[clamped(x, y, ahead, width, height)];
+                total += u32::from(a.abs_diff(b));
+            }
+            if total >= limit {
+                return None;
+            }
+        }
+        Some(total)
+    };
+
+    let mut vectors = vec![(0isize, 0isize); columns * rows];
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut best = (0, 0);
+            let mut best_sad = sad(column, row, (0, 0), (0, 0), u32::MAX).unwrap_or(0);
+            for dy in -SEARCH_RADIUS..=SEARCH_RADIUS {
+                for dx in -SEARCH_RADIUS..=SEARCH_RADIUS {
+                    let candidate = (dx as isize, dy as isize);
+                    if best_sad == 0 {
+                        break;
+                    }
+                    if let Some(total) = sad(column, row, (0, 0), candidate, best_sad) {
+                        best_sad = total;
+                        best = candidate;
+                    }
+                }
+            }
+            vectors[row * columns + column] = best;
+        }
+    }
+
+    let split = |vector: (isize, isize)| {
+        let back = (
+            -(vector.0 as f64 * fraction).round() as isize,
+            -(vector.1 as f64 * fraction).round() as isize,
+        );
+        (back, (vector.0 + back.0, vector.1 + back.1))
+    };
+    let weight = (fraction.clamp(0.0, 1.0) * 256.0).round() as u32;
+    let mut out = vec![0u8; width * height * 3];
+    for row in 0..rows {
+        for column in 0..columns {
+            let (mut best, mut best_sad) = ((0, 0), u32::MAX);
+            let neighbours = (row.saturating_sub(1)..(row + 2).min(rows)).flat_map(|y| {
+                (column.saturating_sub(1)..(column + 2).min(columns)).map(move |x| (x, y))
+            });
+            for vector in
+                std::iter::once((0, 0)).chain(neighbours.map(|(x, y)| vectors[y * columns + x]))
+            {
+                let (back, ahead) = split(vector);
+                if let Some(total) = sad(column, row, back, ahead, best_sad) {
+                    best_sad = total;
+                    best = vector;
+                }
+            }
+
+            let (back, ahead) = split(best);
+            let (xs, ys) = block_area(column, row);
+            for y in ys {
+                for x in xs.clone() {
+                    let from = clamped(x, y, back, width, height) * 3;
+                    let to = clamped(x, y, ahead, width, height) * 3;
+                    let at = (y * width + x) * 3;
+                    for channel in 0..3 {
+                        let a = u32::from(previous[from + channel]);
+                        let b = u32::from(next[to + channel]);
+                        out[at + channel] = ((a * (256 - weight) + b * weight + 128) >> 8) as u8;
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn clamped(x: usize, y: usize, offset: (isize, isize), width: usize, height: usize) -> usize {
+    let x = x.saturating_add_signed(offset.0).min(width - 1);
+    let y = y.saturating_add_signed(offset.1).min(height - 1);
+    y * width + x
+}
+
+fn luma_plane(rgb: &[u8]) -> Vec<u8> {
+    rgb.chunks_exact(3)
+        .map(|pixel| {
+            ((77 * u32::from(pixel[0]) + 150 * u32::from(pixel[1]) + 29 * u32::from(pixel[2])) >> 8)
+                as u8
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(value: u8) -> FrameData {
+        FrameData::new(vec![value; 2 * 2 * 3], 2, 2, Duration::ZERO)
+    }
+
+    fn run(mut resampler: FrameResampler, frames: Vec<FrameData>) -> Vec<FrameData> {
+        let mut out = Vec::new();
+        let mut emit = |frame| {
+            out.push(frame);
+            true
+        };
+        for frame in frames {
+            resampler.push(frame, &mut emit);
+        }
+        resampler.finish(&mut emit);
+        out
+    }
+
+    #[test]
+    fn doubling_the_rate_adds_cross_faded_in_betweens() {
+        let resampler = FrameResampler::new(TemporalMode::Blend, 10.0, 20.0).unwrap();
+        let out = run(resampler, vec![solid(0), solid(100), solid(200)]);
+
+        let values: Vec<u8> = out.iter().map(|frame| frame.buffer[0]).collect();
+        assert_eq!(values, [0, 50, 100, 150, 200]);
+        assert_eq!(out[3].timestamp, Duration::from_millis(150));
+    }
+
+    #[test]
+    fn halving_the_rate_averages_pairs() {
+        let resampler = FrameResampler::new(TemporalMode::Blend, 30.0, 15.0).unwrap();
+        let out = run(
+            resampler,
+            vec![solid(0), solid(100), solid(200), solid(250), solid(40)],
+        );
+
+        let values: Vec<u8> = out.iter().map(|frame| frame.buffer[0]).collect();
+        assert_eq!(values, [50, 225, 40]);
+        let times: Vec<Duration> = out.iter().map(|frame| frame.timestamp).collect();
+        assert_eq!(
+            times,
+            [
+                Duration::ZERO,
+                Duration::from_secs_f64(1.0 / 15.0),
+                Duration::from_secs_f64(2.0 / 15.0)
+            ]
+        );
+        assert!(FrameResampler::new(TemporalMode::Off, 30.0, 15.0).is_none());
+        assert!(FrameResampler::new(TemporalMode::Blend, 30.0, 30.0).is_none());
+    }
+
+    #[test]
+    fn motion_interpolation_moves_a_block_halfway() {
+        // A 4x4 white square on black moves 6 pixels right; the halfway
+        // frame should show it shifted by 3 rather than two faded copies.
+        let (width, height) = (32, 16);
+        let square_at = |left: usize| {
+            let mut rgb = vec![0u8; width * height * 3];
+            for y in 6..10 {
+                for x in left..left + 4 {
+                    rgb[(y * width + x) * 3..(y * width + x) * 3 + 3].fill(255);
+                }
+            }
+            rgb
+        };
+        let middle = motion_interpolate(&square_at(8), &square_at(14), width, height, 0.5);
+
+        assert_eq!(middle, square_at(11));
+    }
+}
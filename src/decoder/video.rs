@@ -1,42 +1,20 @@
+use super::filter::FilterChain;
 use super::frame_data::FrameData;
+use super::pump::{spawn_frame_pump, FrameReader, ReadOutcome};
+use super::scale::{FrameScaler, PixelOrder, RenderTarget, ScaleMode, Scaler, SourceImage};
+use super::source::{title_from_path, DecodeTiming, FrameSource, SourceFrame, SourceMetadata};
 use crate::shared::constants;
 use anyhow::{anyhow, Result};
 use crossbeam_channel::Sender;
-use fast_image_resize as fr;
-use fr::images::{Image, ImageRef};
-use fr::{FilterType, ResizeAlg, ResizeOptions};
 use opencv::{core, prelude::*, videoio};
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
-const SEND_TIMEOUT: Duration = Duration::from_millis(100);
 const SLOW_FRAME_THRESHOLD: Duration = Duration::from_millis(10);
 const SLOW_FRAME_LOG_INTERVAL: Duration = Duration::from_secs(1);
-const DECODER_LEAD_TIME: Duration = Duration::from_millis(500);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct RenderTarget {
-    pub pixel_width: u32,
-    pub pixel_height: u32,
-}
-
-impl RenderTarget {
-    pub fn new(pixel_width: u32, pixel_height: u32) -> Self {
-        Self {
-            pixel_width: pixel_width.max(1),
-            pixel_height: pixel_height.max(2),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ScaleMode {
-    CropToFill,
-    Fit,
-}
 
 pub struct VideoDecoder {
     capture: videoio::VideoCapture,
@@ -46,12 +24,12 @@ pub struct VideoDecoder {
     scale_mode: ScaleMode,
     target: Arc<RwLock<RenderTarget>>,
     frame: Mat,
-    resizer: fr::Resizer,
-    resize_options: ResizeOptions,
-    resized_image: Option<Image<'static>>,
+    scaler: FrameScaler,
     debug_log_path: PathBuf,
     slow_frame_stats: SlowFrameStats,
     memory_profile_enabled: bool,
+    frame_count: Option<u64>,
+    title: Option<String>,
 }
 
 impl VideoDecoder {
@@ -91,6 +69,12 @@ impl VideoDecoder {
         let orig_width = capture.get(videoio::CAP_PROP_FRAME_WIDTH)? as u32;
         let orig_height = capture.get(videoio::CAP_PROP_FRAME_HEIGHT)? as u32;
 
+        let frame_count = capture
+            .get(videoio::CAP_PROP_FRAME_COUNT)
+            .ok()
+            .filter(|count| count.is_finite() && *count > 0.0)
+            .map(|count| count as u64);
+
         writeln!(log_file, "SUCCESS: OpenCV VideoCapture opened")?;
         writeln!(log_file, "  Original: {}x{}", orig_width, orig_height)?;
         writeln!(log_file, "  FPS: {}", fps)?;
@@ -103,85 +87,16 @@ impl VideoDecoder {
             scale_mode,
             target,
             frame: Mat::default(),
-            resizer: fr::Resizer::new(),
-            resize_options: ResizeOptions::new()
-                .resize_alg(ResizeAlg::Convolution(FilterType::Hamming)),
-            resized_image: None,
+            scaler: FrameScaler::new(),
             debug_log_path: log_path,
             slow_frame_stats: SlowFrameStats::new(Instant::now()),
             memory_profile_enabled: crate::utils::memory::profiling_enabled(),
+            frame_count,
+            title: title_from_path(Path::new(path)),
         })
     }
 
-    pub fn get_fps(&self) -> f64 {
-        self.fps
-    }
-
-    pub fn source_aspect_ratio(&self) -> f64 {
-        self.source_width as f64 / self.source_height as f64
-    }
-
-    pub fn spawn_decoding_thread(
-        mut self,
-        sender: Sender<Result<FrameData>>,
-        playback_fps: f64,
-    ) -> std::thread::JoinHandle<Result<()>> {
-        std::thread::spawn(move || {
-            crate::utils::logger::debug("Decoder thread started");
-            let decode_started_at = Instant::now();
-            let mut frame_counter: u64 = 0;
-
-            let mut buffer = Vec::new();
-            loop {
-                pace_decoding(decode_started_at, frame_counter, playback_fps);
-                match self.read_frame_into(&mut buffer) {
-                    Ok(Some(target)) => {
-                        let timestamp =
-                            std::time::Duration::from_secs_f64(frame_counter as f64 / playback_fps);
-                        frame_counter += 1;
-
-                        let frame = FrameData::new(
-                            std::mem::take(&mut buffer),
-                            target.pixel_width,
-                            target.pixel_height,
-                            timestamp,
-                        );
-                        match sender.send_timeout(Ok(frame), SEND_TIMEOUT) {
-                            Ok(()) => {}
-                            Err(crossbeam_channel::SendTimeoutError::Timeout(value)) => {
-                                buffer = match value {
-                                    Ok(frame) => frame.buffer,
-                                    Err(_) => Vec::new(),
-                                };
-                                continue;
-                            }
-                            Err(crossbeam_channel::SendTimeoutError::Disconnected(_)) => {
-                                crate::utils::logger::debug(
-                                    "Decoder sender error (receiver dropped)",
-                                );
-                                break;
-                            }
-                        }
-                    }
-                    Ok(None) => {
-                        crate::utils::logger::debug("Decoder EOF");
-                        break;
-                    }
-                    Err(e) => {
-                        crate::utils::logger::error(&format!("Decoding error: {}", e));
-                        let message = e.to_string();
-                        let _ = sender.send_timeout(Err(anyhow!(message.clone())), SEND_TIMEOUT);
-                        return Err(anyhow!(message));
-                    }
-                }
-            }
-
-            crate::utils::logger::debug("Decoder thread exiting");
-            Ok(())
-        })
-    }
-
-    pub fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<RenderTarget>> {
+    fn decode_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<RenderTarget>> {
         let start_total = std::time::Instant::now();
 
         let start_decode = std::time::Instant::now();
@@ -199,53 +114,29 @@ impl VideoDecoder {
             .read()
             .map_err(|_| anyhow!("render target lock poisoned"))?;
 
-        let start_resize = std::time::Instant::now();
-        let orig_w = self.frame.cols() as u32;
-        let orig_h = self.frame.rows() as u32;
-
-        let (new_w, new_h) = scaled_dimensions(orig_w, orig_h, target, self.scale_mode);
-
         if !self.frame.is_continuous() {
             return Err(anyhow!("Frame is not continuous"));
         }
-        let bgr_bytes = self.frame.data_bytes()?;
-        let src_image = ImageRef::new(orig_w, orig_h, bgr_bytes, fr::PixelType::U8x3)?;
-        let recreate_resized = self
-            .resized_image
-            .as_ref()
-            .map(|image| image.width() != new_w || image.height() != new_h)
-            .unwrap_or(true);
-        if recreate_resized {
-            self.resizer.reset_internal_buffers();
-            self.resized_image = Some(Image::new(new_w, new_h, fr::PixelType::U8x3));
-        }
-        let dst_image = self
-            .resized_image
-            .as_mut()
-            .ok_or_else(|| anyhow!("resize buffer was not initialized"))?;
-        self.resizer
-            .resize(&src_image, dst_image, Some(&self.resize_options))?;
-        let resize_time = start_resize.elapsed();
-        let resizer_internal_bytes = self.resizer.size_of_internal_buffers() as u64;
-
-        let start_letterbox = std::time::Instant::now();
-        let canvas_len = (target.pixel_width * target.pixel_height * 3) as usize;
-        buffer.clear();
-        buffer.resize(canvas_len, 0);
-
-        blit_resized_to_canvas(dst_image.buffer(), new_w, new_h, target, buffer);
-
-        let letterbox_time = start_letterbox.elapsed();
+        let source = SourceImage {
+            bytes: self.frame.data_bytes()?,
+            width: self.frame.cols() as u32,
+            height: self.frame.rows() as u32,
+            order: PixelOrder::Bgr,
+        };
+        let report = self
+            .scaler
+            .scale_into(source, target, self.scale_mode, buffer)?;
+        let canvas_len = buffer.len();
 
         let total_time = start_total.elapsed();
         self.record_slow_frame(
             target,
             canvas_len as u64,
-            resizer_internal_bytes,
+            report.resizer_internal_bytes,
             total_time,
             decode_time,
-            resize_time,
-            letterbox_time,
+            report.resize_time,
+            report.letterbox_time,
         );
 
         Ok(Some(target))
@@ -288,21 +179,6 @@ impl VideoDecoder {
     }
 }
 
-fn pace_decoding(started_at: Instant, frame_counter: u64, playback_fps: f64) {
-    if !playback_fps.is_finite() || playback_fps <= 0.0 {
-        return;
-    }
-
-    let frame_time = Duration::from_secs_f64(frame_counter as f64 / playback_fps);
-    let Some(target_time) = frame_time.checked_sub(DECODER_LEAD_TIME) else {
-        return;
-    };
-    let elapsed = started_at.elapsed();
-    if target_time > elapsed {
-        std::thread::sleep(target_time - elapsed);
-    }
-}
-
 fn open_capture(path: &str, log_file: &mut std::fs::File) -> Result<videoio::VideoCapture> {
     let mut params = core::Vector::<i32>::new();
     params.push(videoio::CAP_PROP_HW_ACCELERATION);
@@ -324,78 +200,83 @@ fn open_capture(path: &str, log_file: &mut std::fs::File) -> Result<videoio::Vid
     }
 }
 
-fn scaled_dimensions(
-    orig_w: u32,
-    orig_h: u32,
-    target: RenderTarget,
-    scale_mode: ScaleMode,
-) -> (u32, u32) {
-    let orig_w = orig_w.max(1);
-    let orig_h = orig_h.max(1);
-    let scale_w = target.pixel_width as f64 / orig_w as f64;
-    let scale_h = target.pixel_height as f64 / orig_h as f64;
-    let scale = match scale_mode {
-        ScaleMode::CropToFill => scale_w.max(scale_h),
-        ScaleMode::Fit => scale_w.min(scale_h),
-    };
-
-    match scale_mode {
-        ScaleMode::CropToFill => (
-            ((orig_w as f64 * scale).ceil() as u32)
-                .max(target.pixel_width)
-                .max(1),
-            ((orig_h as f64 * scale).ceil() as u32)
-                .max(target.pixel_height)
-                .max(1),
-        ),
-        ScaleMode::Fit => (
-            ((orig_w as f64 * scale).floor() as u32)
-                .min(target.pixel_width)
-                .max(1),
-            ((orig_h as f64 * scale).floor() as u32)
-                .min(target.pixel_height)
-                .max(1),
-        ),
+impl FrameSource for VideoDecoder {
+    fn fps(&self) -> f64 {
+        self.fps
     }
-}
 
-fn blit_resized_to_canvas(
-    src: &[u8],
-    src_width: u32,
-    src_height: u32,
-    target: RenderTarget,
-    dst: &mut [u8],
-) {
-    let visible_width = src_width.min(target.pixel_width);
-    let visible_height = src_height.min(target.pixel_height);
-    if visible_width == 0 || visible_height == 0 {
-        return;
+    fn source_aspect_ratio(&self) -> f64 {
+        self.source_width as f64 / self.source_height as f64
     }
 
-    let src_x = src_width.saturating_sub(visible_width) / 2;
-    let src_y = src_height.saturating_sub(visible_height) / 2;
-    let dst_x = target.pixel_width.saturating_sub(visible_width) / 2;
-    let dst_y = target.pixel_height.saturating_sub(visible_height) / 2;
-    let copy_len = visible_width as usize * 3;
-
-    for row in 0..visible_height {
-        let src_offset = ((src_y + row) * src_width + src_x) as usize * 3;
-        let dst_offset = ((dst_y + row) * target.pixel_width + dst_x) as usize * 3;
-
-        if src_offset + copy_len <= src.len() && dst_offset + copy_len <= dst.len() {
-            copy_bgr_to_rgb(
-                &src[src_offset..src_offset + copy_len],
-                &mut dst[dst_offset..dst_offset + copy_len],
-            );
+    fn metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            width: self.source_width,
+            height: self.source_height,
+            fps: self.fps,
+            frame_count: self.frame_count,
+            duration: self
+                .frame_count
+                .and_then(|count| SourceMetadata::duration_from_frames(count, self.fps)),
+            title: self.title.clone(),
         }
     }
+
+    /// Seeks by frame index, which OpenCV backends honour more reliably than
+    /// `CAP_PROP_POS_MSEC`.
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        let frame = (position.as_secs_f64() * self.fps).round();
+        if !self.capture.set(videoio::CAP_PROP_POS_FRAMES, frame)? {
+            return Err(anyhow!("OpenCV backend cannot seek this video"));
+        }
+        Ok(())
+    }
+
+    fn set_scaler(&mut self, scaler: Scaler) {
+        self.scaler.set_scaler(scaler);
+    }
+
+    fn set_filters(&mut self, filters: FilterChain) {
+        self.scaler.set_filters(filters);
+    }
+
+    fn read_frame_into(&mut self, buffer: &mut Vec<u8>) -> Result<Option<SourceFrame>> {
+        Ok(self.decode_into(buffer)?.map(|target| SourceFrame {
+            target,
+            timestamp: None,
+            scale: self.scaler.last_report(),
+        }))
+    }
+
+    fn spawn_decoding_thread(
+        self: Box<Self>,
+        sender: Sender<Result<FrameData>>,
+        timing: DecodeTiming,
+    ) -> std::thread::JoinHandle<Result<()>> {
+        let source_fps = self.fps();
+        spawn_frame_pump(*self, sender, source_fps, timing)
+    }
 }
 
-fn copy_bgr_to_rgb(src: &[u8], dst: &mut [u8]) {
-    for (src_pixel, dst_pixel) in src.chunks_exact(3).zip(dst.chunks_exact_mut(3)) {
-        dst_pixel[0] = src_pixel[2];
-        dst_pixel[1] = src_pixel[1];
-        dst_pixel[2] = src_pixel[0];
+impl FrameReader for VideoDecoder {
+    fn read_next(&mut self, buffer: &mut Vec<u8>) -> Result<ReadOutcome> {
+        Ok(match self.decode_into(buffer)? {
+            Some(target) => ReadOutcome::Frame {
+                target,
+                timestamp: None,
+            },
+            None => ReadOutcome::Eof,
+        })
+    }
+
+    /// `grab()` without `retrieve()`: the packet is decoded but never
+    /// converted to BGR or resized.
+    fn skip_next(&mut self) -> Result<bool> {
+        let grabbed = self.capture.grab()?;
+        if grabbed {
+            self.slow_frame_stats.record_skip();
+        }
+        Ok(grabbed)
     }
 }
 
@@ -408,6 +289,8 @@ struct SlowFrameStats {
     max_decode_us: u128,
     max_resize_us: u128,
     max_letterbox_us: u128,
+    /// Late frames grabbed without retrieving them
+    skipped: u64,
 }
 
 impl SlowFrameStats {
@@ -420,9 +303,14 @@ impl SlowFrameStats {
             max_decode_us: 0,
             max_resize_us: 0,
             max_letterbox_us: 0,
+            skipped: 0,
         }
     }
 
+    fn record_skip(&mut self) {
+        self.skipped += 1;
+    }
+
     fn record(
         &mut self,
         total_time: Duration,
@@ -443,15 +331,16 @@ impl SlowFrameStats {
             return None;
         }
 
-        let line = (self.count > 0).then(|| {
+        let line = (self.count > 0 || self.skipped > 0).then(|| {
             format!(
-                "SIMD_FRAME_SUMMARY: count={} avg_total={}us max_total={}us max_decode={}us max_resize={}us max_letterbox={}us",
+                "SIMD_FRAME_SUMMARY: count={} avg_total={}us max_total={}us max_decode={}us max_resize={}us max_letterbox={}us skipped={}",
                 self.count,
-                self.total_us / self.count as u128,
+                self.total_us / self.count.max(1) as u128,
                 self.max_total_us,
                 self.max_decode_us,
                 self.max_resize_us,
-                self.max_letterbox_us
+                self.max_letterbox_us,
+                self.skipped
             )
         });
 
@@ -493,28 +382,24 @@ mod tests {
     }
 
     #[test]
-    fn slow_frame_stats_without_records_flushes_nothing() {
+    fn slow_frame_stats_reports_skipped_frames() {
         let start = Instant::now();
         let mut stats = SlowFrameStats::new(start);
+        stats.record_skip();
+        stats.record_skip();
 
-        assert!(stats.flush_if_due(start + Duration::from_secs(1)).is_none());
-    }
-
-    #[test]
-    fn crop_to_fill_scaled_dimensions_cover_target_after_rounding() {
-        let target = RenderTarget::new(101, 58);
-        let (width, height) = scaled_dimensions(1920, 1080, target, ScaleMode::CropToFill);
-
-        assert!(width >= target.pixel_width);
-        assert!(height >= target.pixel_height);
+        let line = stats
+            .flush_if_due(start + Duration::from_secs(1))
+            .expect("skips alone should flush");
+        assert!(line.contains("count=0"));
+        assert!(line.contains("skipped=2"));
     }
 
     #[test]
-    fn fit_scaled_dimensions_never_exceed_target_after_rounding() {
-        let target = RenderTarget::new(101, 58);
-        let (width, height) = scaled_dimensions(1920, 1080, target, ScaleMode::Fit);
+    fn slow_frame_stats_without_records_flushes_nothing() {
+        let start = Instant::now();
+        let mut stats = SlowFrameStats::new(start);
 
-        assert!(width <= target.pixel_width);
-        assert!(height <= target.pixel_height);
+        assert!(stats.flush_if_due(start + Duration::from_secs(1)).is_none());
     }
 }
@@ -0,0 +1,121 @@
+use super::{numbered_frame_path, ExportFrame, FrameExporter};
+use crate::renderer::cell::{color_runs, CellData};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Writes every exported frame as its own `.ans` or `.txt` file.
+///
+/// Unlike the live renderer this never relies on cursor movement or on the
+/// previous frame: each file repaints the whole grid row by row, so it can be
+/// `cat`-ed into any terminal.
+pub struct AnsiExporter {
+    output_dir: PathBuf,
+    extension: &'static str,
+    colored: bool,
+}
+
+impl AnsiExporter {
+    pub fn new(output_dir: PathBuf, colored: bool) -> Result<Self> {
+        fs::create_dir_all(&output_dir)
+            .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+        Ok(Self {
+            output_dir,
+            extension: if colored { "ans" } else { "txt" },
+            colored,
+        })
+    }
+}
+
+impl FrameExporter for AnsiExporter {
+    fn write_frame(&mut self, frame: &ExportFrame<'_>) -> Result<()> {
+        let path = numbered_frame_path(&self.output_dir, frame.index, self.extension);
+        let bytes = if self.colored {
+            encode_absolute_ansi(frame.cells, frame.width)
+        } else {
+            encode_plain_text(frame.cells, frame.width)
+        };
+        fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Encodes the grid with one truecolor SGR per color run and a reset at the
+/// end of every row.
+pub fn encode_absolute_ansi(cells: &[CellData], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(cells.len() * 8);
+    if width == 0 {
+        return out;
+    }
+
+    for row in cells.chunks(width) {
+        for run in color_runs(row) {
+            let _ = write!(
+                out,
+                "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                run.fg.0, run.fg.1, run.fg.2, run.bg.0, run.bg.1, run.bg.2
+            );
+            push_chars(&mut out, &row[run.start..run.start + run.len]);
+        }
+        out.extend_from_slice(b"\x1b[0m\n");
+    }
+
+    out
+}
+
+pub fn encode_plain_text(cells: &[CellData], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(cells.len() + cells.len() / width.max(1));
+    if width == 0 {
+        return out;
+    }
+
+    for row in cells.chunks(width) {
+        push_chars(&mut out, row);
+        out.push(b'\n');
+    }
+
+    out
+}
+
+fn push_chars(out: &mut Vec<u8>, cells: &[CellData]) {
+    let mut bytes = [0u8; 4];
+    for cell in cells {
+        out.extend_from_slice(cell.char.encode_utf8(&mut bytes).as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_ansi_sets_colors_per_run_and_resets_each_row() {
+        let cell = CellData {
+            char: '▀',
+            fg: (1, 2, 3),
+            bg: (4, 5, 6),
+        };
+        let encoded =
+            String::from_utf8(encode_absolute_ansi(&[cell, cell, cell, cell], 2)).unwrap();
+
+        assert_eq!(
+            encoded,
+            "\x1b[38;2;1;2;3;48;2;4;5;6m▀▀\x1b[0m\n\x1b[38;2;1;2;3;48;2;4;5;6m▀▀\x1b[0m\n"
+        );
+        assert!(!encoded.contains('H'));
+    }
+
+    #[test]
+    fn plain_text_has_no_escape_sequences() {
+        let cell = CellData {
+            char: '#',
+            fg: (255, 255, 255),
+            bg: (0, 0, 0),
+        };
+        assert_eq!(encode_plain_text(&[cell, cell], 1), b"#\n#\n");
+    }
+}
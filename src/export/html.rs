@@ -0,0 +1,234 @@
+use super::{ExportFrame, FrameExporter};
+use crate::renderer::cell::{color_runs, CellData};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SCREEN_ID: &str = "gascii-screen";
+
+/// Collects encoded frames and writes one self-contained HTML page with a
+/// timestamp-driven JS player.
+pub struct HtmlExporter {
+    output_path: PathBuf,
+    title: String,
+    frame_interval: Duration,
+    frames: Vec<(u64, String)>,
+}
+
+impl HtmlExporter {
+    pub fn new(output_path: PathBuf, title: String, fps: f64) -> Self {
+        let frame_interval = if fps.is_finite() && fps > 0.0 {
+            Duration::from_secs_f64(1.0 / fps)
+        } else {
+            Duration::ZERO
+        };
+
+        Self {
+            output_path,
+            title,
+            frame_interval,
+            frames: Vec::new(),
+        }
+    }
+
+    fn document(&self) -> Result<String> {
+        let duration_ms = self
+            .frames
+            .last()
+            .map(|(timestamp_ms, _)| timestamp_ms + self.frame_interval.as_millis() as u64)
+            .unwrap_or(0);
+        let first_frame = self
+            .frames
+            .first()
+            .map(|(_, html)| html.as_str())
+            .unwrap_or("");
+        // `</` must not appear verbatim inside an inline script.
+        let frames_json = serde_json::to_string(&self.frames)?.replace("</", "<\\/");
+
+        Ok(format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background: #000; }}
+#{id} {{ margin: 0; font-family: Menlo, Consolas, "DejaVu Sans Mono", monospace; font-size: 8px; line-height: 1; letter-spacing: 0; color: #fff; background: #000; }}
+</style>
+</head>
+<body>
+<pre id="{id}">{first_frame}</pre>
+<script>
+(function () {{
+  const frames = {frames_json};
+  const durationMs = {duration_ms};
+  const screen = document.getElementById("{id}");
+  let start = null;
+  let shown = -1;
+
+  function frameAt(elapsedMs) {{
+    let low = 0;
+    let high = frames.length - 1;
+    let found = 0;
+    while (low <= high) {{
+      const mid = (low + high) >> 1;
+      if (frames[mid][0] <= elapsedMs) {{
+        found = mid;
+        low = mid + 1;
+      }} else {{
+        high = mid - 1;
+      }}
+    }}
+    return found;
+  }}
+
+  function tick(now) {{
+    if (start === null) {{
+      start = now;
+    }}
+    const elapsedMs = durationMs > 0 ? (now - start) % durationMs : 0;
+    const index = frameAt(elapsedMs);
+    if (index !== shown) {{
+      shown = index;
+      screen.innerHTML = frames[index][1];
+    }}
+    window.requestAnimationFrame(tick);
+  }}
+
+  if (frames.length > 1) {{
+    window.requestAnimationFrame(tick);
+  }}
+}})();
+</script>
+</body>
+</html>
+"#,
+            title = escape_html(&self.title),
+            id = SCREEN_ID,
+            first_frame = first_frame,
+            frames_json = frames_json,
+            duration_ms = duration_ms,
+        ))
+    }
+}
+
+impl FrameExporter for HtmlExporter {
+    fn write_frame(&mut self, frame: &ExportFrame<'_>) -> Result<()> {
+        self.frames.push((
+            frame.timestamp.as_millis() as u64,
+            encode_frame(frame.cells, frame.width),
+        ));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        let document = self.document()?;
+        fs::write(&self.output_path, document)
+            .with_context(|| format!("Failed to write {}", self.output_path.display()))
+    }
+}
+
+/// Encodes a cell grid as `<pre>` content, one `<span>` per color run.
+pub fn encode_frame(cells: &[CellData], width: usize) -> String {
+    let mut html = String::with_capacity(cells.len() * 4);
+    if width == 0 {
+        return html;
+    }
+
+    for row in cells.chunks(width) {
+        for run in color_runs(row) {
+            let _ = write!(
+                html,
+                "<span style=\"color:{};background:{}\">",
+                hex_color(run.fg),
+                hex_color(run.bg)
+            );
+            for cell in &row[run.start..run.start + run.len] {
+                push_escaped_char(&mut html, cell.char);
+            }
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+
+    html
+}
+
+pub(super) fn hex_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+pub(super) fn push_escaped_char(out: &mut String, ch: char) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(ch),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        push_escaped_char(&mut escaped, ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_frame_coalesces_runs_into_single_spans() {
+        let red = CellData {
+            char: '▀',
+            fg: (255, 0, 0),
+            bg: (0, 0, 0),
+        };
+        let blue = CellData {
+            char: '▀',
+            fg: (0, 0, 255),
+            bg: (0, 0, 0),
+        };
+        let html = encode_frame(&[red, red, blue, blue], 2);
+
+        assert_eq!(html.matches("<span").count(), 2);
+        assert!(html.starts_with("<span style=\"color:#ff0000;background:#000000\">▀▀</span>\n"));
+    }
+
+    #[test]
+    fn encode_frame_escapes_markup_characters() {
+        let cell = CellData {
+            char: '<',
+            fg: (255, 255, 255),
+            bg: (0, 0, 0),
+        };
+        assert!(encode_frame(&[cell], 1).contains("&lt;"));
+    }
+
+    #[test]
+    fn document_embeds_frame_timestamps_and_first_frame() {
+        let mut exporter = HtmlExporter::new(PathBuf::from("out.html"), "clip".to_string(), 10.0);
+        let cells = [CellData::default()];
+        for index in 0..2 {
+            exporter
+                .write_frame(&ExportFrame {
+                    index,
+                    timestamp: Duration::from_millis(index * 100),
+                    cells: &cells,
+                    width: 1,
+                })
+                .unwrap();
+        }
+
+        let document = exporter.document().unwrap();
+        assert!(document.contains("const durationMs = 200;"));
+        assert!(document.contains("[[0,"));
+        assert!(document.contains("[100,"));
+        assert!(document.contains("<\\/span>"));
+    }
+}
@@ -0,0 +1,323 @@
+pub mod ansi;
+pub mod html;
+pub mod svg;
+
+use crate::core::player::with_default_scaler;
+use crate::core::viewport::{corrected_pixel_aspect, make_even};
+use crate::decoder::{open_source, FrameSource, RenderTarget, ScaleMode, SourceOptions};
+use crate::renderer::cell::CellData;
+use crate::renderer::display::ascii_char_for;
+use crate::renderer::{ActiveRenderBackend, DisplayManager, DisplayMode, FrameProcessor, MonoMode};
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub use ansi::AnsiExporter;
+pub use html::HtmlExporter;
+pub use svg::SvgExporter;
+
+const ASCII_FOREGROUND: (u8, u8, u8) = (255, 255, 255);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Html,
+    Svg,
+    Ans,
+    Txt,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportConfig {
+    pub video_path: PathBuf,
+    pub output_path: PathBuf,
+    pub format: ExportFormat,
+    pub width: u32,
+    pub height: Option<u32>,
+    pub fps: Option<u32>,
+    pub source_options: SourceOptions,
+    pub display_mode: DisplayMode,
+    pub fill: bool,
+    pub selection: FrameSelection,
+}
+
+/// Which decoded frames end up in the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameSelection {
+    pub every: u64,
+    pub start: Option<Duration>,
+    pub end: Option<Duration>,
+}
+
+impl Default for FrameSelection {
+    fn default() -> Self {
+        Self {
+            every: 1,
+            start: None,
+            end: None,
+        }
+    }
+}
+
+impl FrameSelection {
+    pub fn in_range(&self, timestamp: Duration) -> bool {
+        self.start.is_none_or(|start| timestamp >= start) && !self.is_past_end(timestamp)
+    }
+
+    pub fn is_past_end(&self, timestamp: Duration) -> bool {
+        self.end.is_some_and(|end| timestamp >= end)
+    }
+
+    /// `nth_in_range` counts frames from the start of the range, so `every`
+    /// always keeps the first frame of the range.
+    pub fn keeps(&self, nth_in_range: u64) -> bool {
+        nth_in_range.is_multiple_of(self.every.max(1))
+    }
+
+    fn rebase(&self, timestamp: Duration) -> Duration {
+        timestamp.saturating_sub(self.start.unwrap_or(Duration::ZERO))
+    }
+}
+
+/// One processed frame handed to an exporter
+pub struct ExportFrame<'a> {
+    pub index: u64,
+    pub timestamp: Duration,
+    pub cells: &'a [CellData],
+    pub width: usize,
+}
+
+pub trait FrameExporter {
+    fn write_frame(&mut self, frame: &ExportFrame<'_>) -> Result<()>;
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+pub fn run(config: ExportConfig) -> Result<()> {
+    let target = Arc::new(RwLock::new(RenderTarget::new(1, 2)));
+    let scale_mode = if config.fill {
+        ScaleMode::CropToFill
+    } else {
+        ScaleMode::Fit
+    };
+    let mut decoder = open_source(
+        &config.video_path,
+        target.clone(),
+        scale_mode,
+        with_default_scaler(&config.source_options, config.display_mode, MonoMode::Off)?,
+    )?;
+
+    let backend = ActiveRenderBackend::for_mode(config.display_mode);
+    let (pixel_width, pixel_height) = export_dimensions(
+        config.width,
+        config.height,
+        decoder.source_aspect_ratio(),
+        DisplayManager::render_pixel_aspect_correction(backend),
+    );
+    {
+        let mut guard = target
+            .write()
+            .map_err(|_| anyhow!("render target lock poisoned"))?;
+        *guard = RenderTarget::new(pixel_width, pixel_height);
+    }
+
+    let fps = config
+        .fps
+        .filter(|value| *value > 0)
+        .map(|value| value as f64)
+        .unwrap_or_else(|| decoder.fps());
+    let title = decoder
+        .metadata()
+        .title
+        .unwrap_or_else(|| crate::shared::constants::APP_NAME.to_string());
+
+    let mut exporter: Box<dyn FrameExporter> = match config.format {
+        ExportFormat::Html => Box::new(HtmlExporter::new(config.output_path.clone(), title, fps)),
+        ExportFormat::Svg => Box::new(SvgExporter::new(config.output_path.clone())?),
+        ExportFormat::Ans => Box::new(AnsiExporter::new(config.output_path.clone(), true)?),
+        ExportFormat::Txt => Box::new(AnsiExporter::new(config.output_path.clone(), false)?),
+    };
+    let asciify = config.display_mode == DisplayMode::Ascii || config.format == ExportFormat::Txt;
+    // ANSI art keeps each glyph in the colour of the cell it replaces.
+    let glyph_colors = config.format == ExportFormat::Ans;
+
+    let processor = FrameProcessor::new(pixel_width as usize, pixel_height as usize);
+    let mut cells = vec![CellData::default(); pixel_width as usize * (pixel_height as usize / 2)];
+    let mut buffer = Vec::new();
+    let mut index = skip_to_start(decoder.as_mut(), &config, fps);
+    let mut in_range: u64 = 0;
+    let mut exported: u64 = 0;
+
+    while let Some(frame) = decoder.read_frame_into(&mut buffer)? {
+        let frame_index = index;
+        let timestamp = frame
+            .timestamp
+            .unwrap_or_else(|| Duration::from_secs_f64(frame_index as f64 / fps));
+        index += 1;
+
+        if config.selection.is_past_end(timestamp) {
+            break;
+        }
+        if !config.selection.in_range(timestamp) {
+            continue;
+        }
+        let keep = config.selection.keeps(in_range);
+        in_range += 1;
+        if !keep {
+            continue;
+        }
+
+        processor.process_frame_into(&buffer, &mut cells);
+        if asciify {
+            asciify_cells(&mut cells, glyph_colors);
+        }
+
+        exporter.write_frame(&ExportFrame {
+            index: frame_index,
+            timestamp: config.selection.rebase(timestamp),
+            cells: &cells,
+            width: pixel_width as usize,
+        })?;
+        exported += 1;
+    }
+    exporter.finish()?;
+
+    crate::utils::logger::info(&format!(
+        "export completed: format={:?} frames={} size={}x{} output={}",
+        config.format,
+        exported,
+        pixel_width,
+        pixel_height / 2,
+        config.output_path.display()
+    ));
+    println!("내보내기 완료: {}", config.output_path.display());
+    println!("프레임: {}", exported);
+    Ok(())
+}
+
+/// Seeks straight to `--start` when the source supports it and the export
+/// timeline is the source's own; otherwise frames are decoded and skipped.
+/// Returns the index of the next frame to be read.
+fn skip_to_start(decoder: &mut dyn FrameSource, config: &ExportConfig, fps: f64) -> u64 {
+    let Some(start) = config.selection.start else {
+        return 0;
+    };
+    if config.fps.is_some() {
+        return 0;
+    }
+    match decoder.seek(start) {
+        Ok(()) => (start.as_secs_f64() * fps).round() as u64,
+        Err(error) => {
+            crate::utils::logger::debug(&format!(
+                "export seek unavailable, decoding up to start: {}",
+                error
+            ));
+            0
+        }
+    }
+}
+
+/// Zero-padded names keep `file_utils::list_files` ordering stable.
+pub fn numbered_frame_path(dir: &Path, index: u64, extension: &str) -> PathBuf {
+    dir.join(format!("frame_{:06}.{}", index + 1, extension))
+}
+
+fn export_dimensions(
+    width: u32,
+    height: Option<u32>,
+    source_aspect: f64,
+    pixel_aspect_correction: f64,
+) -> (u32, u32) {
+    let width = width.max(1);
+    let height = height.unwrap_or_else(|| {
+        let pixel_aspect = corrected_pixel_aspect(source_aspect, pixel_aspect_correction);
+        (width as f64 / pixel_aspect).round() as u32
+    });
+    (width, make_even(height))
+}
+
+/// Replaces half blocks with brightness glyphs on black, drawn in the
+/// average colour of the two pixels when `glyph_colors` is set and in
+/// `ASCII_FOREGROUND` otherwise.
+fn asciify_cells(cells: &mut [CellData], glyph_colors: bool) {
+    for cell in cells {
+        let fg = if glyph_colors {
+            average_color(cell.fg, cell.bg)
+        } else {
+            ASCII_FOREGROUND
+        };
+        *cell = CellData {
+            char: ascii_char_for(cell),
+            fg,
+            bg: (0, 0, 0),
+        };
+    }
+}
+
+fn average_color(a: (u8, u8, u8), b: (u8, u8, u8)) -> (u8, u8, u8) {
+    let mid = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    (mid(a.0, b.0), mid(a.1, b.1), mid(a.2, b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_dimensions_follow_source_aspect_when_height_is_omitted() {
+        assert_eq!(export_dimensions(160, None, 16.0 / 9.0, 1.0), (160, 90));
+        assert_eq!(export_dimensions(160, Some(51), 16.0 / 9.0, 1.0), (160, 50));
+    }
+
+    #[test]
+    fn frame_selection_keeps_every_nth_frame_from_range_start() {
+        let selection = FrameSelection {
+            every: 3,
+            start: Some(Duration::from_secs(1)),
+            end: Some(Duration::from_secs(2)),
+        };
+
+        assert!(!selection.in_range(Duration::from_millis(999)));
+        assert!(selection.in_range(Duration::from_secs(1)));
+        assert!(selection.is_past_end(Duration::from_secs(2)));
+        assert!(selection.keeps(0));
+        assert!(!selection.keeps(1));
+        assert!(selection.keeps(3));
+        assert_eq!(
+            selection.rebase(Duration::from_millis(1500)),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn ascii_glyphs_keep_the_cell_colour_only_when_asked() {
+        let half_block = CellData {
+            char: '▀',
+            fg: (200, 100, 0),
+            bg: (100, 50, 20),
+        };
+        let mut colored = [half_block];
+        asciify_cells(&mut colored, true);
+        let mut plain = [half_block];
+        asciify_cells(&mut plain, false);
+
+        assert_eq!(colored[0].char, plain[0].char);
+        assert_ne!(colored[0].char, '▀');
+        assert_eq!(colored[0].fg, (150, 75, 10));
+        assert_eq!(plain[0].fg, ASCII_FOREGROUND);
+        assert_eq!(colored[0].bg, (0, 0, 0));
+    }
+
+    #[test]
+    fn numbered_frame_paths_sort_in_playback_order() {
+        let dir = Path::new("frames");
+        let mut paths = [
+            numbered_frame_path(dir, 10, "svg"),
+            numbered_frame_path(dir, 9, "svg"),
+            numbered_frame_path(dir, 0, "svg"),
+        ];
+        paths.sort();
+
+        assert_eq!(paths[0], dir.join("frame_000001.svg"));
+        assert_eq!(paths[2], dir.join("frame_000011.svg"));
+    }
+}
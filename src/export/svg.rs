@@ -0,0 +1,143 @@
+use super::html::{hex_color, push_escaped_char};
+use super::{numbered_frame_path, ExportFrame, FrameExporter};
+use crate::renderer::cell::{color_runs, CellData};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+const CELL_WIDTH: usize = 8;
+const CELL_HEIGHT: usize = 16;
+const HALF_BLOCK: char = '▀';
+
+/// Writes one SVG snapshot per exported frame into an output directory.
+pub struct SvgExporter {
+    output_dir: PathBuf,
+}
+
+impl SvgExporter {
+    pub fn new(output_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&output_dir)
+            .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+        Ok(Self { output_dir })
+    }
+}
+
+impl FrameExporter for SvgExporter {
+    fn write_frame(&mut self, frame: &ExportFrame<'_>) -> Result<()> {
+        let path = numbered_frame_path(&self.output_dir, frame.index, "svg");
+        fs::write(&path, encode_frame(frame.cells, frame.width))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders a cell grid as SVG.
+///
+/// Half-block cells become two rectangles per color run so the snapshot is
+/// pixel exact regardless of the viewer's fonts; any other glyph is emitted as
+/// text on top of its run background.
+pub fn encode_frame(cells: &[CellData], width: usize) -> String {
+    let rows = cells.len().checked_div(width).unwrap_or(0);
+    let mut svg = String::with_capacity(cells.len() * 16);
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">",
+        w = width * CELL_WIDTH,
+        h = rows * CELL_HEIGHT
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>\n");
+
+    for (y, row) in cells.chunks(width.max(1)).take(rows).enumerate() {
+        let top = y * CELL_HEIGHT;
+        for run in color_runs(row) {
+            let x = run.start * CELL_WIDTH;
+            let run_width = run.len * CELL_WIDTH;
+            let glyphs = &row[run.start..run.start + run.len];
+
+            if glyphs.iter().all(|cell| cell.char == HALF_BLOCK) {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x,
+                    top,
+                    run_width,
+                    CELL_HEIGHT / 2,
+                    hex_color(run.fg)
+                );
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x,
+                    top + CELL_HEIGHT / 2,
+                    run_width,
+                    CELL_HEIGHT / 2,
+                    hex_color(run.bg)
+                );
+                continue;
+            }
+
+            if run.bg != (0, 0, 0) {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x,
+                    top,
+                    run_width,
+                    CELL_HEIGHT,
+                    hex_color(run.bg)
+                );
+            }
+            let _ = write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" textLength=\"{}\" fill=\"{}\" font-family=\"monospace\" font-size=\"{}\" xml:space=\"preserve\">",
+                x,
+                top + CELL_HEIGHT - CELL_HEIGHT / 4,
+                run_width,
+                hex_color(run.fg),
+                CELL_HEIGHT
+            );
+            for cell in glyphs {
+                push_escaped_char(&mut svg, cell.char);
+            }
+            svg.push_str("</text>\n");
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_block_runs_become_two_rectangles() {
+        let cell = CellData {
+            char: HALF_BLOCK,
+            fg: (255, 0, 0),
+            bg: (0, 0, 255),
+        };
+        let svg = encode_frame(&[cell, cell, cell], 3);
+
+        assert!(svg.contains("width=\"24\" height=\"8\" fill=\"#ff0000\""));
+        assert!(svg.contains("y=\"8\" width=\"24\" height=\"8\" fill=\"#0000ff\""));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn ascii_runs_become_text() {
+        let cell = CellData {
+            char: '@',
+            fg: (255, 255, 255),
+            bg: (0, 0, 0),
+        };
+        let svg = encode_frame(&[cell, cell], 2);
+
+        assert!(svg.contains(">@@</text>"));
+    }
+}
@@ -1,18 +1,26 @@
+mod bench;
 mod core;
 mod decoder;
+mod export;
 mod renderer;
 mod shared;
+mod subtitle;
 mod sync;
 mod ui;
 mod utils;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::io::IsTerminal;
 
 use crate::core::extractor;
-use crate::core::player::RenderQuality;
-use crate::renderer::{DisplayMode, TruecolorPolicy};
+use crate::core::player::{RenderQuality, SidecarSource};
+use crate::decoder::{
+    FilterChain, RawPixelFormat, RawVideoFormat, Scaler, SourceOptions, TemporalMode,
+};
+use crate::export::ExportFormat;
+use crate::renderer::grading::Look;
+use crate::renderer::{DisplayMode, MonoMode, TruecolorPolicy};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -21,6 +29,61 @@ struct Cli {
     command: Option<Commands>,
 }
 
+/// Input flags shared by every command that opens a frame source
+#[derive(Args)]
+struct SourceArgs {
+    #[arg(
+        long,
+        help = "Frame rate for image directories/globs and rawvideo; GIFs and Y4M carry their own"
+    )]
+    input_fps: Option<f64>,
+    #[arg(long, help = "Frame width of a headerless rawvideo stream")]
+    raw_width: Option<u32>,
+    #[arg(long, help = "Frame height of a headerless rawvideo stream")]
+    raw_height: Option<u32>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Pixel format of a headerless rawvideo stream; without it pipes are read as Y4M"
+    )]
+    raw_pix_fmt: Option<RawPixelFormat>,
+    #[arg(
+        long,
+        help = "Filters applied after scaling, in order: brightness=F, contrast=F, gamma=F, saturation=F, sharpen[=F], edges, threshold[=N|otsu], invert; e.g. 'contrast=1.3,sharpen'. 'scaler=NAME' picks the resampling filter. '@FILE' reads them from a file"
+    )]
+    filters: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Resampling filter. First found wins: --scaler, then scaler= in --filters, then GASCII_SCALER, then the mode default (area for ascii and --mono on, hamming otherwise)"
+    )]
+    scaler: Option<Scaler>,
+}
+
+impl SourceArgs {
+    fn to_options(&self) -> Result<SourceOptions> {
+        let raw_format = match (self.raw_width, self.raw_height, self.raw_pix_fmt) {
+            (None, None, None) => None,
+            (Some(width), Some(height), Some(pixel_format)) => Some(RawVideoFormat {
+                width,
+                height,
+                pixel_format,
+            }),
+            _ => anyhow::bail!("rawvideo input needs --raw-width, --raw-height and --raw-pix-fmt"),
+        };
+        let filters = match &self.filters {
+            Some(spec) => FilterChain::from_arg(spec)?,
+            None => FilterChain::default(),
+        };
+        Ok(SourceOptions {
+            input_fps: self.input_fps,
+            raw_format,
+            scaler: self.scaler.or(filters.scaler()),
+            filters,
+        })
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Extract frames from video
@@ -49,7 +112,11 @@ enum Commands {
     },
     /// Play video directly (real-time, no extraction)
     PlayLive {
-        #[arg(short = 'v', long)]
+        #[arg(
+            short = 'v',
+            long,
+            help = "Video file, image directory, glob like 'frames/*.png', still image, GIF, .y4m file, '-' for stdin, or pattern:bars|gradient|boxes|counter|flicker|sync[,size=WxH][,fps=N][,duration=S]"
+        )]
         video: String,
         #[arg(short = 'a', long)]
         audio: Option<String>,
@@ -67,6 +134,8 @@ enum Commands {
         height: Option<u32>,
         #[arg(short = 'p', long, default_value_t = 0)]
         fps: u32,
+        #[command(flatten)]
+        source: SourceArgs,
         #[arg(short = 'm', long, value_enum, default_value_t = DisplayMode::Rgb)]
         mode: DisplayMode,
         #[arg(
@@ -91,6 +160,174 @@ enum Commands {
             help = "Use fullscreen viewport: preserve source aspect ratio and fit the largest possible image into the terminal"
         )]
         fill: bool,
+        #[arg(
+            long,
+            value_name = "MODE",
+            value_parser = parse_viewport_mode,
+            conflicts_with = "fill",
+            help = "Frame shape: original, cinemascope, fill, zoom (+/- zoom, arrows pan, 0 resets) or a ratio such as 4:3, 16:9, 1.85, 21:9"
+        )]
+        aspect: Option<crate::core::player::ViewportMode>,
+        #[arg(
+            long,
+            value_name = "COLSxROWS",
+            value_parser = parse_headless_size,
+            help = "Run without a terminal: render into a virtual COLSxROWS screen and print output stats (CI, benchmarks)"
+        )]
+        headless: Option<(u16, u16)>,
+        #[arg(
+            long,
+            requires = "headless",
+            help = "File that receives the encoded ANSI stream in headless mode; discarded when omitted"
+        )]
+        headless_output: Option<String>,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Reserve the bottom row for elapsed/total time and a progress bar; hides after 3s without input (space pauses)"
+        )]
+        status_line: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Subtitle file (.srt, .vtt, .ass, .ssa); defaults to a sibling of the video with the same name"
+        )]
+        subs: Option<String>,
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with = "subs",
+            help = "Do not auto-load sibling subtitle files"
+        )]
+        no_subs: bool,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 0.0,
+            allow_hyphen_values = true,
+            value_parser = parse_seconds_offset,
+            help = "Shift subtitles in time; positive shows them later, negative earlier ('s' toggles subtitles)"
+        )]
+        sub_delay: f64,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Synced lyrics (.lrc); defaults to one named like the audio file, then the video. Shown beside the video when there is letterbox room"
+        )]
+        lyrics: Option<String>,
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with = "lyrics",
+            help = "Do not auto-load .lrc lyrics"
+        )]
+        no_lyrics: bool,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Use the letterbox rows above and below the video for title, metadata, timeline and subtitles"
+        )]
+        info_panels: bool,
+        #[arg(
+            long,
+            help = "Colour look to start with: sepia, phosphor, amber, duotone, posterize or a .cube LUT file. 'c' cycles looks during playback"
+        )]
+        grade: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = MonoMode::Auto,
+            help = "1-bit half-block rendering in two fixed colours for black-and-white sources (rgb mode only); auto switches it on while the source stays two-tone"
+        )]
+        mono: MonoMode,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = TemporalMode::Off,
+            help = "Frame-rate conversion when --fps differs from the source: off re-times frames (changing speed), blend cross-fades or averages them, motion interpolates moving blocks"
+        )]
+        interpolate: TemporalMode,
+    },
+    /// Export rendered frames as an HTML player page, SVG snapshots or ANSI/text files
+    Export {
+        #[arg(short = 'v', long)]
+        video: String,
+        #[arg(
+            short = 'o',
+            long,
+            help = "Output file for html, output directory for svg/ans/txt"
+        )]
+        output: String,
+        #[arg(short = 'f', long, value_enum, default_value_t = ExportFormat::Html)]
+        format: ExportFormat,
+        #[arg(short = 'w', long, default_value_t = 160, help = "Width in cells")]
+        width: u32,
+        #[arg(
+            short = 'H',
+            long,
+            help = "Height in pixels (two per cell row). Defaults to the source aspect ratio"
+        )]
+        height: Option<u32>,
+        #[arg(short = 'p', long, default_value_t = 0)]
+        fps: u32,
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(short = 'm', long, value_enum, default_value_t = DisplayMode::Rgb)]
+        mode: DisplayMode,
+        #[arg(short = 'F', long, default_value_t = false)]
+        fill: bool,
+        #[arg(long, default_value_t = 1, help = "Only export every Nth frame")]
+        every: u64,
+        #[arg(long, help = "Start of the exported range in seconds")]
+        start: Option<f64>,
+        #[arg(long, help = "End of the exported range in seconds")]
+        end: Option<f64>,
+    },
+    /// Run the render pipeline headless as fast as possible and report per-stage timings
+    Bench {
+        #[arg(
+            short = 'v',
+            long,
+            default_value = "pattern:bars,duration=0",
+            help = "Video file or any other play-live input, including pattern: specs"
+        )]
+        video: String,
+        #[arg(
+            short = 's',
+            long,
+            default_value = "120x40",
+            value_name = "COLSxROWS",
+            value_parser = parse_headless_size,
+            help = "Virtual terminal size"
+        )]
+        size: (u16, u16),
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 300,
+            help = "Maximum number of frames"
+        )]
+        frames: u64,
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(short = 'm', long, value_enum, default_value_t = DisplayMode::Rgb)]
+        mode: DisplayMode,
+        #[arg(short = 'q', long, value_enum, default_value_t = RenderQuality::Full)]
+        quality: RenderQuality,
+        #[arg(short = 'F', long, default_value_t = false)]
+        fill: bool,
+        #[arg(long, value_name = "MODE", value_parser = parse_viewport_mode, conflicts_with = "fill")]
+        aspect: Option<crate::core::player::ViewportMode>,
+        #[arg(long, value_enum, default_value_t = MonoMode::Auto)]
+        mono: MonoMode,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Repeat the run with every --scaler and compare their cost"
+        )]
+        compare_scalers: bool,
+        #[arg(long, default_value_t = false, help = "Print the report as JSON")]
+        json: bool,
     },
     /// Detect platform info
     Detect,
@@ -140,10 +377,24 @@ fn main() -> Result<()> {
             width,
             height,
             fps,
+            source,
             mode,
             quality,
             truecolor_policy,
             fill,
+            aspect,
+            headless,
+            headless_output,
+            status_line,
+            subs,
+            no_subs,
+            sub_delay,
+            lyrics,
+            no_lyrics,
+            info_panels,
+            grade,
+            mono,
+            interpolate,
         } => {
             crate::core::player::play(crate::core::player::PlaybackConfig {
                 video_path: std::path::PathBuf::from(video),
@@ -151,14 +402,90 @@ fn main() -> Result<()> {
                 requested_width: *width,
                 requested_height: *height,
                 requested_fps: if *fps > 0 { Some(*fps) } else { None },
+                source_options: source.to_options()?,
                 display_mode: *mode,
-                viewport_mode: if *fill {
-                    crate::core::player::ViewportMode::Fullscreen
-                } else {
-                    crate::core::player::ViewportMode::CinemaScope
-                },
+                viewport_mode: viewport_mode(*fill, *aspect),
                 quality: *quality,
                 truecolor_policy: *truecolor_policy,
+                headless: headless.map(|(cols, rows)| crate::renderer::HeadlessTerminal {
+                    cols,
+                    rows,
+                    output: headless_output.as_ref().map(std::path::PathBuf::from),
+                }),
+                status_line: *status_line,
+                subtitles: match subs {
+                    Some(path) => SidecarSource::File(std::path::PathBuf::from(path)),
+                    None if *no_subs => SidecarSource::Off,
+                    None => SidecarSource::Sibling,
+                },
+                sub_delay: *sub_delay,
+                lyrics: match lyrics {
+                    Some(path) => SidecarSource::File(std::path::PathBuf::from(path)),
+                    None if *no_lyrics => SidecarSource::Off,
+                    None => SidecarSource::Sibling,
+                },
+                info_panels: *info_panels,
+                grade: grade.as_deref().map(Look::parse).transpose()?,
+                mono: *mono,
+                temporal: *interpolate,
+            })?;
+        }
+        Commands::Export {
+            video,
+            output,
+            format,
+            width,
+            height,
+            fps,
+            source,
+            mode,
+            fill,
+            every,
+            start,
+            end,
+        } => {
+            crate::export::run(crate::export::ExportConfig {
+                video_path: std::path::PathBuf::from(video),
+                output_path: std::path::PathBuf::from(output),
+                format: *format,
+                width: *width,
+                height: *height,
+                fps: if *fps > 0 { Some(*fps) } else { None },
+                source_options: source.to_options()?,
+                display_mode: *mode,
+                fill: *fill,
+                selection: crate::export::FrameSelection {
+                    every: (*every).max(1),
+                    start: start.map(seconds_to_duration).transpose()?,
+                    end: end.map(seconds_to_duration).transpose()?,
+                },
+            })?;
+        }
+        Commands::Bench {
+            video,
+            size,
+            frames,
+            source,
+            mode,
+            quality,
+            fill,
+            aspect,
+            mono,
+            compare_scalers,
+            json,
+        } => {
+            crate::bench::run(crate::bench::BenchConfig {
+                video_path: std::path::PathBuf::from(video),
+                terminal_cols: size.0,
+                terminal_rows: size.1,
+                max_frames: (*frames).max(1),
+                source_options: source.to_options()?,
+                display_mode: *mode,
+                viewport_mode: viewport_mode(*fill, *aspect),
+                quality: *quality,
+                mono: *mono,
+                compare_scalers: *compare_scalers,
+                json: *json,
             })?;
         }
         Commands::Detect => {
@@ -180,3 +507,37 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+fn parse_viewport_mode(
+    value: &str,
+) -> std::result::Result<crate::core::player::ViewportMode, String> {
+    crate::core::player::ViewportMode::parse(value).map_err(|error| error.to_string())
+}
+
+fn viewport_mode(
+    fill: bool,
+    aspect: Option<crate::core::player::ViewportMode>,
+) -> crate::core::player::ViewportMode {
+    match aspect {
+        Some(mode) => mode,
+        None if fill => crate::core::player::ViewportMode::Fullscreen,
+        None => crate::core::player::ViewportMode::CinemaScope,
+    }
+}
+
+fn parse_headless_size(value: &str) -> std::result::Result<(u16, u16), String> {
+    crate::renderer::display::parse_terminal_size(value).map_err(|error| error.to_string())
+}
+
+fn parse_seconds_offset(value: &str) -> std::result::Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite())
+        .ok_or_else(|| format!("invalid time in seconds: {}", value))
+}
+
+fn seconds_to_duration(seconds: f64) -> Result<std::time::Duration> {
+    std::time::Duration::try_from_secs_f64(seconds)
+        .map_err(|_| anyhow::anyhow!("invalid time in seconds: {}", seconds))
+}
@@ -17,6 +17,13 @@ impl ActiveRenderBackend {
     pub fn requires_cell_buffer(self) -> bool {
         matches!(self, Self::AnsiRgb)
     }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::AnsiAscii => "ansi-ascii",
+            Self::AnsiRgb => "ansi-rgb",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,29 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Stands in for the cell covered by the right half of a double-width glyph.
+///
+/// Encoders print nothing for it: the terminal already advanced past it
+/// when it drew the glyph.
+pub const WIDE_CONTINUATION: char = '\0';
+
+/// Terminal columns a glyph occupies; 0 for combining marks and controls.
+pub fn char_width(c: char) -> usize {
+    match c {
+        ' '..='~' | '▀' => 1,
+        _ => c.width().unwrap_or(0),
+    }
+}
+
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
 /// Represents a single character cell on the terminal
 ///
-/// Uses TrueColor (RGB) for maximum quality
+/// Uses TrueColor (RGB) for maximum quality. The C layout lets the frame
+/// processor write whole cells with vector stores.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
 pub struct CellData {
     pub char: char,
     pub fg: (u8, u8, u8), // RGB
@@ -17,3 +39,78 @@ impl Default for CellData {
         }
     }
 }
+
+/// A horizontal run of cells sharing the same colors
+///
+/// Mirrors how the ANSI encoder only emits a new SGR sequence when the
+/// foreground or background changes between adjacent cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRun {
+    pub start: usize,
+    pub len: usize,
+    pub fg: (u8, u8, u8),
+    pub bg: (u8, u8, u8),
+}
+
+pub fn color_runs(row: &[CellData]) -> impl Iterator<Item = CellRun> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let first = row.get(start)?;
+        let len = row[start..]
+            .iter()
+            .take_while(|cell| cell.fg == first.fg && cell.bg == first.bg)
+            .count();
+        let run = CellRun {
+            start,
+            len,
+            fg: first.fg,
+            bg: first.bg,
+        };
+        start += len;
+        Some(run)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(fg: (u8, u8, u8), bg: (u8, u8, u8)) -> CellData {
+        CellData {
+            char: '▀', fg, bg
+        }
+    }
+
+    #[test]
+    fn color_runs_merge_adjacent_cells_with_identical_colors() {
+        let red = (255, 0, 0);
+        let black = (0, 0, 0);
+        let row = [
+            cell(red, black),
+            cell(red, black),
+            cell(black, black),
+            cell(red, black),
+        ];
+
+        let runs: Vec<CellRun> = color_runs(&row).collect();
+        assert_eq!(runs.len(), 3);
+        assert_eq!((runs[0].start, runs[0].len), (0, 2));
+        assert_eq!((runs[1].start, runs[1].len), (2, 1));
+        assert_eq!((runs[2].start, runs[2].len), (3, 1));
+    }
+
+    #[test]
+    fn cjk_glyphs_take_two_columns() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('▀'), 1);
+        assert_eq!(char_width('한'), 2);
+        assert_eq!(char_width('字'), 2);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(text_width("안녕 hi"), 7);
+    }
+
+    #[test]
+    fn color_runs_of_empty_row_is_empty() {
+        assert_eq!(color_runs(&[]).count(), 0);
+    }
+}
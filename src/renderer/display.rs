@@ -5,12 +5,24 @@ use crossterm::{
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use std::io::{BufWriter, Stdout, Write};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use super::backend::ActiveRenderBackend;
-use super::cell::CellData;
+use super::cell::{char_width, CellData, WIDE_CONTINUATION};
+use super::overlay::CellOverlay;
+use super::pipeline::RenderThread;
 use crate::utils::platform::TerminalCapabilities;
 
+/// RGB frames with at least this many cells are encoded in parallel bands
+const PARALLEL_ENCODE_THRESHOLD: usize = 8_192;
+/// Cell rows per band; fixed so the output does not depend on core count
+const ENCODE_BAND_ROWS: usize = 8;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 pub enum DisplayMode {
     Ascii,
@@ -36,14 +48,111 @@ pub struct RenderViewport {
 
 impl RenderViewport {}
 
+/// Fixed-size virtual terminal used instead of the real one (CI, benchmarks)
+///
+/// Frames are still encoded exactly as for a terminal but go to `output`,
+/// or nowhere when it is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessTerminal {
+    pub cols: u16,
+    pub rows: u16,
+    pub output: Option<PathBuf>,
+}
+
+/// Parses a `COLSxROWS` terminal size such as `120x40`.
+pub fn parse_terminal_size(value: &str) -> Result<(u16, u16)> {
+    let (cols, rows) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| anyhow!("terminal size must look like COLSxROWS, got {:?}", value))?;
+    let cols: u16 = cols
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("invalid column count in {:?}", value))?;
+    let rows: u16 = rows
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("invalid row count in {:?}", value))?;
+    if cols == 0 || rows == 0 {
+        return Err(anyhow!(
+            "terminal size must be at least 1x1, got {:?}",
+            value
+        ));
+    }
+    Ok((cols, rows))
+}
+
+/// Bytes and time spent turning frames into terminal output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputStats {
+    pub frames: u64,
+    pub bytes: u64,
+    pub encode_time: Duration,
+    pub write_time: Duration,
+    /// Frames skipped because a newer one was already queued behind them
+    pub stale_frames: u64,
+}
+
+impl OutputStats {
+    pub fn bytes_per_frame(&self) -> u64 {
+        self.bytes.checked_div(self.frames).unwrap_or(0)
+    }
+}
+
 pub struct DisplayManager {
-    stdout: BufWriter<Stdout>,
+    stage: OutputStage,
+    active_backend: ActiveRenderBackend,
+    supports_sync_output: bool,
+    headless_size: Option<(u16, u16)>,
+    output_stats: Arc<Mutex<OutputStats>>,
+    overlay: CellOverlay,
+    /// Last spans drawn outside the viewport, keyed by `(row, col)`
+    text_rows: BTreeMap<(u16, u16), Vec<TextSpan>>,
+}
+
+/// Where frames are encoded and written
+enum OutputStage {
+    /// On the caller's thread, so headless output is complete and the same
+    /// on every run
+    Inline {
+        encoder: FrameEncoder,
+        output: BufWriter<Box<dyn Write + Send>>,
+    },
+    /// On the render and writer threads, so a slow terminal cannot stall
+    /// the player; frames it cannot keep up with are skipped
+    Pipelined(RenderThread),
+}
+
+/// Turns frames into the bytes that update the previous one on screen
+pub(crate) struct FrameEncoder {
     active_backend: ActiveRenderBackend,
     supports_sync_output: bool,
     last_cells: Option<Vec<CellData>>,
     last_ascii: Option<Vec<char>>,
     render_buffer: Vec<u8>,
+    /// Per-band output of the parallel RGB encoder, kept between frames
+    band_buffers: Vec<Vec<u8>>,
+    /// Luma of the two pixel rows behind the ASCII cell row being encoded
+    luma_rows: Vec<u8>,
     clear_next_frame: bool,
+    /// Repaint every cell of the next frame without clearing the screen
+    redraw_next_frame: bool,
+}
+
+/// What one `FrameEncoder::encode` call wrote
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EncodedFrame {
+    /// The screen was cleared first, erasing text drawn outside the viewport
+    pub cleared: bool,
+    /// Every cell was written, so the frame does not build on earlier ones
+    pub keyframe: bool,
+}
+
+/// A run of text in one color, drawn outside the video viewport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSpan {
+    pub text: String,
+    pub fg: (u8, u8, u8),
+    pub bg: (u8, u8, u8),
 }
 
 fn resolve_backend_with_policy(
@@ -104,8 +213,7 @@ fn ascii_char_for_brightness(brightness: u32) -> char {
     ASCII_CHARS[char_idx]
 }
 
-#[cfg(test)]
-fn ascii_char_for(cell: &CellData) -> char {
+pub(crate) fn ascii_char_for(cell: &CellData) -> char {
     let top = (cell.fg.0 as u32 * 299 + cell.fg.1 as u32 * 587 + cell.fg.2 as u32 * 114) / 1000;
     let bottom = (cell.bg.0 as u32 * 299 + cell.bg.1 as u32 * 587 + cell.bg.2 as u32 * 114) / 1000;
     ascii_char_for_brightness((top + bottom) / 2)
@@ -143,21 +251,145 @@ impl DisplayManager {
             ));
         }
 
-        let stdout = BufWriter::with_capacity(4 * 1024 * 1024, std::io::stdout());
-        let mut dm = Self {
-            stdout,
+        let supports_sync_output = capabilities.supports_sync_output;
+        initialize_terminal(&mut std::io::stdout(), supports_sync_output)?;
+        let output_stats = Arc::default();
+        let render_thread = match RenderThread::spawn(
+            FrameEncoder::new(active_backend, supports_sync_output),
+            Box::new(std::io::stdout()),
+            Arc::clone(&output_stats),
+        ) {
+            Ok(render_thread) => render_thread,
+            Err(error) => {
+                restore_terminal(&mut std::io::stdout());
+                return Err(error);
+            }
+        };
+        Ok(Self::with_stage(
+            OutputStage::Pipelined(render_thread),
             active_backend,
-            supports_sync_output: capabilities.supports_sync_output,
-            last_cells: None,
-            last_ascii: None,
-            render_buffer: Vec::with_capacity(4 * 1024 * 1024),
-            clear_next_frame: true,
+            supports_sync_output,
+            output_stats,
+        ))
+    }
+
+    /// Runs the same encoder against a virtual terminal without touching the
+    /// real one: no raw mode, no alternate screen, no capability probing.
+    pub fn headless(
+        mode: DisplayMode,
+        requested_backend: ActiveRenderBackend,
+        terminal: &HeadlessTerminal,
+    ) -> Result<Self> {
+        let active_backend = if mode == DisplayMode::Rgb {
+            requested_backend
+        } else {
+            ActiveRenderBackend::AnsiAscii
+        };
+        let sink: Box<dyn Write + Send> = match &terminal.output {
+            Some(path) => Box::new(
+                std::fs::File::create(path)
+                    .map_err(|error| anyhow!("failed to create {}: {}", path.display(), error))?,
+            ),
+            None => Box::new(std::io::sink()),
         };
 
-        dm.initialize_terminal()?;
+        let stage = OutputStage::Inline {
+            encoder: FrameEncoder::new(active_backend, true),
+            output: BufWriter::with_capacity(4 * 1024 * 1024, sink),
+        };
+        let mut dm = Self::with_stage(stage, active_backend, true, Arc::default());
+        dm.headless_size = Some(normalize_terminal_size(terminal.cols, terminal.rows));
         Ok(dm)
     }
 
+    fn with_stage(
+        stage: OutputStage,
+        active_backend: ActiveRenderBackend,
+        supports_sync_output: bool,
+        output_stats: Arc<Mutex<OutputStats>>,
+    ) -> Self {
+        Self {
+            stage,
+            active_backend,
+            supports_sync_output,
+            headless_size: None,
+            output_stats,
+            overlay: CellOverlay::new(),
+            text_rows: BTreeMap::new(),
+        }
+    }
+
+    /// A virtual terminal fed through the render and writer threads.
+    #[cfg(test)]
+    pub(crate) fn headless_pipelined(
+        mode: DisplayMode,
+        sink: Box<dyn Write + Send>,
+        cols: u16,
+        rows: u16,
+    ) -> Result<Self> {
+        let active_backend = ActiveRenderBackend::for_mode(mode);
+        let output_stats = Arc::default();
+        let render_thread = RenderThread::spawn(
+            FrameEncoder::new(active_backend, true),
+            sink,
+            Arc::clone(&output_stats),
+        )?;
+        let mut dm = Self::with_stage(
+            OutputStage::Pipelined(render_thread),
+            active_backend,
+            true,
+            output_stats,
+        );
+        dm.headless_size = Some((cols, rows));
+        Ok(dm)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless_size.is_some()
+    }
+
+    /// Size of the terminal frames are laid out for; fixed when headless.
+    pub fn terminal_size_chars(&self) -> Result<(u16, u16)> {
+        match self.headless_size {
+            Some(size) => Ok(size),
+            None => Self::current_terminal_size_chars(),
+        }
+    }
+
+    /// Waits until everything queued has reached the terminal, so the
+    /// output stats are final. Frames rendered afterwards are an error.
+    pub fn finish_output(&mut self) -> Result<()> {
+        match &mut self.stage {
+            OutputStage::Inline { output, .. } => output.flush()?,
+            OutputStage::Pipelined(render_thread) => render_thread.finish(),
+        }
+        Ok(())
+    }
+
+    pub fn output_stats(&self) -> OutputStats {
+        self.output_stats
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default()
+    }
+
+    /// Cells composited over the next rendered frames (HUD, captions).
+    ///
+    /// Call `begin` with the viewport's cell grid before drawing; an overlay
+    /// sized for another grid is ignored.
+    pub fn overlay_mut(&mut self) -> &mut CellOverlay {
+        &mut self.overlay
+    }
+
+    /// Bytes produced for the most recent frame; empty when pipelined.
+    #[cfg(test)]
+    pub(crate) fn last_output(&self) -> &[u8] {
+        match &self.stage {
+            OutputStage::Inline { encoder, .. } => encoder.output(),
+            OutputStage::Pipelined(_) => &[],
+        }
+    }
+
     pub fn active_backend(&self) -> ActiveRenderBackend {
         self.active_backend
     }
@@ -181,30 +413,102 @@ impl DisplayManager {
         }
     }
 
-    fn initialize_terminal(&mut self) -> Result<()> {
-        terminal::enable_raw_mode()?;
-        self.stdout.execute(EnterAlternateScreen)?;
-        self.stdout.execute(cursor::Hide)?;
-        self.stdout.execute(Print("\x1b[?7l"))?;
-        self.stdout
-            .execute(Print(std::str::from_utf8(sync_begin_sequence(
-                self.supports_sync_output,
-            ))?))?;
-
-        self.stdout.execute(Print("\x1b[?12l"))?;
-        self.stdout.flush()?;
-        Ok(())
-    }
-
     pub fn current_terminal_size_chars() -> Result<(u16, u16)> {
         let (term_cols, term_rows) = terminal::size()?;
         Ok(normalize_terminal_size(term_cols, term_rows))
     }
 
     pub fn invalidate_cache(&mut self) {
-        self.last_cells = None;
-        self.last_ascii = None;
-        self.clear_next_frame = true;
+        self.text_rows.clear();
+        match &mut self.stage {
+            OutputStage::Inline { encoder, .. } => encoder.invalidate(),
+            OutputStage::Pipelined(render_thread) => render_thread.invalidate(),
+        }
+    }
+
+    /// Draws a whole terminal row outside the video viewport, e.g. the
+    /// status line.
+    ///
+    /// Rows are cached, so calling this every loop iteration only writes
+    /// when the content changed or the screen was cleared since.
+    pub fn draw_text_row(
+        &mut self,
+        row: u16,
+        text: &str,
+        fg: (u8, u8, u8),
+        bg: (u8, u8, u8),
+    ) -> Result<()> {
+        self.draw_text_spans(
+            row,
+            0,
+            &[TextSpan {
+                text: text.to_string(),
+                fg,
+                bg,
+            }],
+        )
+    }
+
+    /// Draws `spans` back to back from `(row, col)`, cached like
+    /// `draw_text_row`. Callers keep the total width fixed per position so
+    /// a shorter update still overwrites the previous one.
+    pub fn draw_text_spans(&mut self, row: u16, col: u16, spans: &[TextSpan]) -> Result<()> {
+        if self
+            .text_rows
+            .get(&(row, col))
+            .is_some_and(|last| last.as_slice() == spans)
+        {
+            return Ok(());
+        }
+
+        let text_len: usize = spans.iter().map(|span| span.text.len()).sum();
+        let mut buffer = Vec::with_capacity(text_len + 64 * spans.len());
+        buffer.extend_from_slice(sync_begin_sequence(self.supports_sync_output));
+        buffer.extend_from_slice(b"\x1b[");
+        Self::write_u16_fast(&mut buffer, row.saturating_add(1));
+        buffer.push(b';');
+        Self::write_u16_fast(&mut buffer, col.saturating_add(1));
+        buffer.push(b'H');
+        for span in spans {
+            if self.active_backend == ActiveRenderBackend::AnsiRgb {
+                buffer.extend_from_slice(b"\x1b[38;2;");
+                Self::write_u8_fast(&mut buffer, span.fg.0);
+                buffer.push(b';');
+                Self::write_u8_fast(&mut buffer, span.fg.1);
+                buffer.push(b';');
+                Self::write_u8_fast(&mut buffer, span.fg.2);
+                buffer.extend_from_slice(b"m\x1b[48;2;");
+                Self::write_u8_fast(&mut buffer, span.bg.0);
+                buffer.push(b';');
+                Self::write_u8_fast(&mut buffer, span.bg.1);
+                buffer.push(b';');
+                Self::write_u8_fast(&mut buffer, span.bg.2);
+                buffer.push(b'm');
+            }
+            buffer.extend_from_slice(span.text.as_bytes());
+        }
+        buffer.extend_from_slice(b"\x1b[0m");
+        buffer.extend_from_slice(sync_end_sequence(self.supports_sync_output));
+
+        let len = buffer.len() as u64;
+        let write_time = match &mut self.stage {
+            OutputStage::Inline { output, .. } => {
+                let write_started = Instant::now();
+                output.write_all(&buffer)?;
+                output.flush()?;
+                write_started.elapsed()
+            }
+            OutputStage::Pipelined(render_thread) => {
+                render_thread.text(buffer)?;
+                Duration::ZERO
+            }
+        };
+        if let Ok(mut stats) = self.output_stats.lock() {
+            stats.write_time += write_time;
+            stats.bytes += len;
+        }
+        self.text_rows.insert((row, col), spans.to_vec());
+        Ok(())
     }
 
     #[inline(always)]
@@ -263,70 +567,188 @@ impl DisplayManager {
         }
     }
 
+    /// Encodes and writes a frame, or when pipelined, queues a copy of it
+    /// for the render thread and returns right away.
     pub fn render(
         &mut self,
         rgb_buffer: &[u8],
         rgb_cells: Option<&[CellData]>,
         viewport: RenderViewport,
     ) -> Result<()> {
+        let (encoder, output) = match &mut self.stage {
+            OutputStage::Inline { encoder, output } => (encoder, output),
+            OutputStage::Pipelined(render_thread) => {
+                let mut job = render_thread.frame_job();
+                job.pixels.clear();
+                job.cells.clear();
+                match self.active_backend {
+                    ActiveRenderBackend::AnsiAscii => job.pixels.extend_from_slice(rgb_buffer),
+                    ActiveRenderBackend::AnsiRgb => {
+                        job.cells.extend_from_slice(rgb_cells.unwrap_or(&[]))
+                    }
+                }
+                job.overlay.clone_from(&self.overlay);
+                job.viewport = Some(viewport);
+                return render_thread.render(job);
+            }
+        };
+
+        let encode_started = Instant::now();
+        let encoded = encoder.encode(rgb_buffer, rgb_cells, &mut self.overlay, viewport);
+        let encode_time = encode_started.elapsed();
+        if encoded.cleared {
+            self.text_rows.clear();
+        }
+
+        let bytes = encoder.output();
+        let write_started = Instant::now();
+        if !bytes.is_empty() {
+            output.write_all(bytes)?;
+            output.flush()?;
+        }
+        let write_time = write_started.elapsed();
+        if let Ok(mut stats) = self.output_stats.lock() {
+            stats.encode_time += encode_time;
+            if !bytes.is_empty() {
+                stats.write_time += write_time;
+                stats.frames += 1;
+                stats.bytes += bytes.len() as u64;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FrameEncoder {
+    pub(crate) fn new(active_backend: ActiveRenderBackend, supports_sync_output: bool) -> Self {
+        Self {
+            active_backend,
+            supports_sync_output,
+            last_cells: None,
+            last_ascii: None,
+            render_buffer: Vec::with_capacity(4 * 1024 * 1024),
+            band_buffers: Vec::new(),
+            luma_rows: Vec::new(),
+            clear_next_frame: true,
+            redraw_next_frame: false,
+        }
+    }
+
+    /// Encodes `viewport` into `output`, writing only the cells that
+    /// differ from the previous frame unless a redraw is pending.
+    pub(crate) fn encode(
+        &mut self,
+        rgb_buffer: &[u8],
+        rgb_cells: Option<&[CellData]>,
+        overlay: &mut CellOverlay,
+        viewport: RenderViewport,
+    ) -> EncodedFrame {
+        self.render_buffer.clear();
         match self.active_backend {
-            ActiveRenderBackend::AnsiAscii => self.render_ascii(rgb_buffer, viewport),
+            ActiveRenderBackend::AnsiAscii => self.encode_ascii(rgb_buffer, overlay, viewport),
             ActiveRenderBackend::AnsiRgb => {
-                self.render_rgb_diff(rgb_cells.unwrap_or(&[]), viewport)
+                self.encode_rgb_diff(rgb_cells.unwrap_or(&[]), overlay, viewport)
             }
         }
     }
 
-    fn render_ascii(&mut self, rgb_buffer: &[u8], viewport: RenderViewport) -> Result<()> {
+    /// Bytes produced by the last `encode`.
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.render_buffer
+    }
+
+    /// Takes the last output, encoding into `replacement` from now on.
+    pub(crate) fn take_output(&mut self, replacement: Vec<u8>) -> Vec<u8> {
+        std::mem::replace(&mut self.render_buffer, replacement)
+    }
+
+    /// Clears the screen with the next frame and repaints it in full.
+    pub(crate) fn invalidate(&mut self) {
+        self.last_cells = None;
+        self.last_ascii = None;
+        self.clear_next_frame = true;
+    }
+
+    /// Appends the screen clear to `bytes` now rather than with the next
+    /// frame, so text written after it survives. That frame is a keyframe.
+    pub(crate) fn clear_into(&mut self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(b"\x1b[2J");
+        self.last_cells = None;
+        self.last_ascii = None;
+        self.clear_next_frame = false;
+        self.redraw_next_frame = true;
+    }
+
+    /// Repaints every cell of the next frame, e.g. after the writer had to
+    /// skip one, without clearing the screen.
+    pub(crate) fn redraw(&mut self) {
+        self.redraw_next_frame = true;
+    }
+
+    fn encode_ascii(
+        &mut self,
+        rgb_buffer: &[u8],
+        overlay: &mut CellOverlay,
+        viewport: RenderViewport,
+    ) -> EncodedFrame {
         let width = viewport.pixel_width as usize;
         let height = viewport.pixel_height as usize;
         let cell_count = width * (height / 2);
 
         if rgb_buffer.len() < width * height * 3 {
-            return Ok(());
+            return EncodedFrame::default();
         }
 
-        self.render_buffer.clear();
+        let overlay_cells: &[(usize, CellData)] = if overlay.fits(width, height / 2) {
+            overlay.sorted_cells()
+        } else {
+            &[]
+        };
+        let mut overlay_cells = overlay_cells.iter().peekable();
         let buffer = &mut self.render_buffer;
 
         buffer.extend_from_slice(sync_begin_sequence(self.supports_sync_output));
 
+        let mut force_redraw = std::mem::take(&mut self.redraw_next_frame);
         let last_ascii = self
             .last_ascii
             .get_or_insert_with(|| vec!['\0'; cell_count]);
-        let mut force_redraw = false;
         if last_ascii.len() != cell_count {
             *last_ascii = vec!['\0'; cell_count];
             force_redraw = true;
         }
-        if self.clear_next_frame {
+        let cleared = std::mem::take(&mut self.clear_next_frame);
+        if cleared {
             buffer.extend_from_slice(b"\x1b[2J");
             force_redraw = true;
-            self.clear_next_frame = false;
         }
 
         let (term_cols, term_rows) =
             normalize_terminal_size(viewport.terminal_cols, viewport.terminal_rows);
         let mut cursor_x: i32 = -1;
         let mut cursor_y: i32 = -1;
+        let luma = &mut self.luma_rows;
+        luma.resize(width * 2, 0);
 
         for cell_index in 0..cell_count {
             let cx = cell_index % width;
             let cy = cell_index / width;
-            let top_offset = (cy * 2 * width + cx) * 3;
-            let bottom_offset = ((cy * 2 + 1) * width + cx) * 3;
-
-            let top = (rgb_buffer[top_offset] as u32 * 299
-                + rgb_buffer[top_offset + 1] as u32 * 587
-                + rgb_buffer[top_offset + 2] as u32 * 114)
-                / 1000;
-            let bottom = (rgb_buffer[bottom_offset] as u32 * 299
-                + rgb_buffer[bottom_offset + 1] as u32 * 587
-                + rgb_buffer[bottom_offset + 2] as u32 * 114)
-                / 1000;
-            let ascii_char = ascii_char_for_brightness((top + bottom) / 2);
+            if cx == 0 {
+                let rows = &rgb_buffer[cy * 2 * width * 3..(cy * 2 + 2) * width * 3];
+                crate::utils::simd::luma_bt601(rows, luma);
+            }
+            let top = luma[cx] as u32;
+            let bottom = luma[width + cx] as u32;
+            let ascii_char = match overlay_cells.next_if(|(index, _)| *index == cell_index) {
+                Some((_, cell)) => cell.char,
+                None => ascii_char_for_brightness((top + bottom) / 2),
+            };
 
             if force_redraw || last_ascii[cell_index] != ascii_char {
+                if ascii_char == WIDE_CONTINUATION {
+                    last_ascii[cell_index] = ascii_char;
+                    continue;
+                }
                 let target_x = viewport.offset_x + cx as u16;
                 let target_y = viewport.offset_y + cy as u16;
 
@@ -337,9 +759,9 @@ impl DisplayManager {
 
                 if cursor_x != target_x as i32 || cursor_y != target_y as i32 {
                     buffer.extend_from_slice(b"\x1b[");
-                    Self::write_u16_fast(buffer, target_y + 1);
+                    DisplayManager::write_u16_fast(buffer, target_y + 1);
                     buffer.push(b';');
-                    Self::write_u16_fast(buffer, target_x + 1);
+                    DisplayManager::write_u16_fast(buffer, target_x + 1);
                     buffer.push(b'H');
                     cursor_x = target_x as i32;
                     cursor_y = target_y as i32;
@@ -348,7 +770,7 @@ impl DisplayManager {
                 let mut bytes = [0u8; 4];
                 buffer.extend_from_slice(ascii_char.encode_utf8(&mut bytes).as_bytes());
                 last_ascii[cell_index] = ascii_char;
-                cursor_x += 1;
+                cursor_x += char_width(ascii_char).max(1) as i32;
             } else {
                 cursor_x = -1;
             }
@@ -356,44 +778,135 @@ impl DisplayManager {
 
         buffer.extend_from_slice(b"\x1b[0m");
         buffer.extend_from_slice(sync_end_sequence(self.supports_sync_output));
-        self.stdout.write_all(buffer)?;
-        self.stdout.flush()?;
-        Ok(())
+        EncodedFrame {
+            cleared,
+            keyframe: force_redraw,
+        }
     }
 
-    fn render_rgb_diff(&mut self, cells: &[CellData], viewport: RenderViewport) -> Result<()> {
+    fn encode_rgb_diff(
+        &mut self,
+        cells: &[CellData],
+        overlay: &mut CellOverlay,
+        viewport: RenderViewport,
+    ) -> EncodedFrame {
         let width = viewport.pixel_width as usize;
 
-        self.render_buffer.clear();
+        let overlay_cells: &[(usize, CellData)] =
+            if overlay.fits(width, viewport.pixel_height as usize / 2) {
+                overlay.sorted_cells()
+            } else {
+                &[]
+            };
         let buffer = &mut self.render_buffer;
 
         buffer.extend_from_slice(sync_begin_sequence(self.supports_sync_output));
 
-        let mut force_redraw = false;
+        let mut force_redraw = std::mem::take(&mut self.redraw_next_frame);
         if self.last_cells.as_ref().map(|v| v.len()).unwrap_or(0) != cells.len() {
             self.last_cells = Some(vec![CellData::default(); cells.len()]);
             force_redraw = true;
         }
-        if self.clear_next_frame {
+        let cleared = std::mem::take(&mut self.clear_next_frame);
+        if cleared {
             buffer.extend_from_slice(b"\x1b[2J");
             force_redraw = true;
-            self.clear_next_frame = false;
         }
 
         let last_cells = match &mut self.last_cells {
             Some(v) => v,
-            None => return Ok(()),
+            None => {
+                return EncodedFrame {
+                    cleared,
+                    keyframe: force_redraw,
+                }
+            }
         };
 
         let (term_cols, term_rows) =
             normalize_terminal_size(viewport.terminal_cols, viewport.terminal_rows);
+        let band = RgbBand {
+            first_index: 0,
+            width,
+            viewport,
+            term_cols,
+            term_rows,
+            force_redraw,
+        };
+
+        if cells.len() < PARALLEL_ENCODE_THRESHOLD || width == 0 {
+            Self::encode_rgb_band(buffer, cells, last_cells, overlay_cells, band);
+        } else {
+            // Row bands are independent: each starts with an unknown cursor
+            // and colour state and only touches its own slice of `last_cells`.
+            let band_cells = width * ENCODE_BAND_ROWS;
+            let band_count = cells.len().div_ceil(band_cells);
+            self.band_buffers.resize_with(band_count, Vec::new);
+            cells
+                .par_chunks(band_cells)
+                .zip(last_cells.par_chunks_mut(band_cells))
+                .zip(self.band_buffers.par_iter_mut())
+                .enumerate()
+                .for_each(|(index, ((cells, last_cells), out))| {
+                    let first_index = index * band_cells;
+                    let from = overlay_cells.partition_point(|(i, _)| *i < first_index);
+                    let to = overlay_cells.partition_point(|(i, _)| *i < first_index + cells.len());
+                    out.clear();
+                    Self::encode_rgb_band(
+                        out,
+                        cells,
+                        last_cells,
+                        &overlay_cells[from..to],
+                        RgbBand {
+                            first_index,
+                            ..band
+                        },
+                    );
+                });
+            for out in &self.band_buffers {
+                buffer.extend_from_slice(out);
+            }
+        }
+
+        buffer.extend_from_slice(b"\x1b[0m");
+        buffer.extend_from_slice(sync_end_sequence(self.supports_sync_output));
+        EncodedFrame {
+            cleared,
+            keyframe: force_redraw,
+        }
+    }
+
+    /// Encodes the changed cells of one run of rows. `cells` and
+    /// `last_cells` start at frame cell `band.first_index`; overlay indices
+    /// are frame-wide.
+    fn encode_rgb_band(
+        buffer: &mut Vec<u8>,
+        cells: &[CellData],
+        last_cells: &mut [CellData],
+        overlay_cells: &[(usize, CellData)],
+        band: RgbBand,
+    ) {
+        let RgbBand {
+            first_index,
+            width,
+            viewport,
+            term_cols,
+            term_rows,
+            force_redraw,
+        } = band;
+        let mut overlay_cells = overlay_cells.iter().peekable();
         let mut last_fg: Option<(u8, u8, u8)> = None;
         let mut last_bg: Option<(u8, u8, u8)> = None;
         let mut cursor_x: i32 = -1;
         let mut cursor_y: i32 = -1;
 
-        for (i, cell) in cells.iter().enumerate() {
-            let old_cell = &last_cells[i];
+        for (offset, cell) in cells.iter().enumerate() {
+            let i = first_index + offset;
+            let cell = match overlay_cells.next_if(|(index, _)| *index == i) {
+                Some((_, overlay_cell)) => overlay_cell,
+                None => cell,
+            };
+            let old_cell = &last_cells[offset];
             let is_different = if force_redraw {
                 true
             } else if cell.char != old_cell.char {
@@ -406,6 +919,10 @@ impl DisplayManager {
                 cursor_x = -1;
                 continue;
             }
+            if cell.char == WIDE_CONTINUATION {
+                last_cells[offset] = *cell;
+                continue;
+            }
 
             let x = (i % width) as u16;
             let y = (i / width) as u16;
@@ -419,9 +936,9 @@ impl DisplayManager {
 
             if cursor_x != target_x as i32 || cursor_y != target_y as i32 {
                 buffer.extend_from_slice(b"\x1b[");
-                Self::write_u16_fast(buffer, target_y + 1);
+                DisplayManager::write_u16_fast(buffer, target_y + 1);
                 buffer.push(b';');
-                Self::write_u16_fast(buffer, target_x + 1);
+                DisplayManager::write_u16_fast(buffer, target_x + 1);
                 buffer.push(b'H');
                 cursor_x = target_x as i32;
                 cursor_y = target_y as i32;
@@ -429,51 +946,81 @@ impl DisplayManager {
 
             if Some(cell.fg) != last_fg {
                 buffer.extend_from_slice(b"\x1b[38;2;");
-                Self::write_u8_fast(buffer, cell.fg.0);
+                DisplayManager::write_u8_fast(buffer, cell.fg.0);
                 buffer.push(b';');
-                Self::write_u8_fast(buffer, cell.fg.1);
+                DisplayManager::write_u8_fast(buffer, cell.fg.1);
                 buffer.push(b';');
-                Self::write_u8_fast(buffer, cell.fg.2);
+                DisplayManager::write_u8_fast(buffer, cell.fg.2);
                 buffer.push(b'm');
                 last_fg = Some(cell.fg);
             }
             if Some(cell.bg) != last_bg {
                 buffer.extend_from_slice(b"\x1b[48;2;");
-                Self::write_u8_fast(buffer, cell.bg.0);
+                DisplayManager::write_u8_fast(buffer, cell.bg.0);
                 buffer.push(b';');
-                Self::write_u8_fast(buffer, cell.bg.1);
+                DisplayManager::write_u8_fast(buffer, cell.bg.1);
                 buffer.push(b';');
-                Self::write_u8_fast(buffer, cell.bg.2);
+                DisplayManager::write_u8_fast(buffer, cell.bg.2);
                 buffer.push(b'm');
                 last_bg = Some(cell.bg);
             }
 
             let mut bytes = [0u8; 4];
             buffer.extend_from_slice(cell.char.encode_utf8(&mut bytes).as_bytes());
-            last_cells[i] = *cell;
-            cursor_x += 1;
+            last_cells[offset] = *cell;
+            cursor_x += char_width(cell.char).max(1) as i32;
         }
-
-        buffer.extend_from_slice(b"\x1b[0m");
-        buffer.extend_from_slice(sync_end_sequence(self.supports_sync_output));
-        self.stdout.write_all(buffer)?;
-        self.stdout.flush()?;
-        Ok(())
     }
 }
 
+/// Where a run of RGB cells sits in the frame and on the terminal
+#[derive(Clone, Copy)]
+struct RgbBand {
+    first_index: usize,
+    width: usize,
+    viewport: RenderViewport,
+    term_cols: u16,
+    term_rows: u16,
+    force_redraw: bool,
+}
+
+fn initialize_terminal(output: &mut impl Write, supports_sync_output: bool) -> Result<()> {
+    terminal::enable_raw_mode()?;
+    output.execute(EnterAlternateScreen)?;
+    output.execute(cursor::Hide)?;
+    output.execute(Print("\x1b[?7l"))?;
+    output.execute(Print(std::str::from_utf8(sync_begin_sequence(
+        supports_sync_output,
+    ))?))?;
+
+    output.execute(Print("\x1b[?12l"))?;
+    output.flush()?;
+    Ok(())
+}
+
+fn restore_terminal(output: &mut impl Write) {
+    let _ = output.execute(cursor::Show);
+    let _ = output.execute(LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
 impl Drop for DisplayManager {
     fn drop(&mut self) {
-        let _ = self.stdout.execute(cursor::Show);
-        let _ = self.stdout.execute(LeaveAlternateScreen);
-        let _ = terminal::disable_raw_mode();
+        // Everything queued reaches the terminal before it is restored.
+        let _ = self.finish_output();
+        if !self.is_headless() {
+            restore_terminal(&mut std::io::stdout());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::renderer::processor::FrameProcessor;
+    use crate::renderer::vt_screen::{assert_golden, VtScreen};
     use crate::utils::platform::{TerminalCapabilities, TerminalFamily};
+    use crossbeam_channel::{Receiver, Sender};
 
     #[test]
     fn ascii_brightness_mapping_is_stable() {
@@ -598,6 +1145,360 @@ mod tests {
         );
     }
 
+    #[test]
+    fn terminal_size_parses_cols_by_rows() {
+        assert_eq!(parse_terminal_size("120x40").unwrap(), (120, 40));
+        assert_eq!(parse_terminal_size("80X24").unwrap(), (80, 24));
+        assert!(parse_terminal_size("0x24").is_err());
+        assert!(parse_terminal_size("80").is_err());
+    }
+
+    #[test]
+    fn headless_display_counts_encoded_bytes_without_a_terminal() {
+        let path = std::env::temp_dir().join(format!("gascii-headless-{}.ans", std::process::id()));
+        let terminal = HeadlessTerminal {
+            cols: 8,
+            rows: 4,
+            output: Some(path.clone()),
+        };
+        let mut display =
+            DisplayManager::headless(DisplayMode::Rgb, ActiveRenderBackend::AnsiRgb, &terminal)
+                .unwrap();
+        assert!(display.is_headless());
+        assert_eq!(display.terminal_size_chars().unwrap(), (8, 4));
+
+        let viewport = RenderViewport {
+            offset_x: 0,
+            offset_y: 0,
+            terminal_cols: 8,
+            terminal_rows: 4,
+            pixel_width: 4,
+            pixel_height: 4,
+        };
+        let cells = vec![
+            CellData {
+                char: '▀',
+                fg: (255, 0, 0),
+                bg: (0, 0, 255),
+            };
+            8
+        ];
+        display.render(&[], Some(&cells), viewport).unwrap();
+        // Unchanged cells only cost the sync and reset sequences.
+        display.render(&[], Some(&cells), viewport).unwrap();
+
+        let stats = display.output_stats();
+        drop(display);
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(stats.frames, 2);
+        assert_eq!(stats.bytes, written.len() as u64);
+        assert!(written.starts_with(b"\x1b[?2026h\x1b[2J"));
+        // No alternate screen or cursor control, only frame content.
+        assert!(!written.windows(8).any(|window| window == b"\x1b[?1049h"));
+    }
+
+    fn headless_display(mode: DisplayMode, cols: u16, rows: u16) -> DisplayManager {
+        let terminal = HeadlessTerminal {
+            cols,
+            rows,
+            output: None,
+        };
+        DisplayManager::headless(mode, ActiveRenderBackend::for_mode(mode), &terminal).unwrap()
+    }
+
+    fn viewport_at(offset_x: u16, offset_y: u16, width: u32, height: u32) -> RenderViewport {
+        RenderViewport {
+            offset_x,
+            offset_y,
+            terminal_cols: 10,
+            terminal_rows: 4,
+            pixel_width: width,
+            pixel_height: height,
+        }
+    }
+
+    /// Horizontal gray ramp with a bright block at `marker` cell column.
+    fn ramp_frame(width: usize, height: usize, marker: usize) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let value = if x == marker && y < 2 {
+                    255
+                } else {
+                    (x * 255 / width) as u8
+                };
+                pixels.extend_from_slice(&[value, value / 2, 255 - value]);
+            }
+        }
+        pixels
+    }
+
+    fn render_to_screen(
+        display: &mut DisplayManager,
+        screen: &mut VtScreen,
+        pixels: &[u8],
+        viewport: RenderViewport,
+    ) {
+        let cells = FrameProcessor::new(
+            viewport.pixel_width as usize,
+            viewport.pixel_height as usize,
+        )
+        .process_frame(pixels);
+        display.render(pixels, Some(&cells), viewport).unwrap();
+        screen.feed(display.last_output());
+        assert!(!screen.in_sync_update(), "frame left sync output open");
+    }
+
+    /// What the screen should show for `pixels` if it were drawn from scratch.
+    fn fresh_screen(mode: DisplayMode, pixels: &[u8], viewport: RenderViewport) -> VtScreen {
+        let mut display = headless_display(mode, viewport.terminal_cols, viewport.terminal_rows);
+        let mut screen = VtScreen::new(viewport.terminal_cols, viewport.terminal_rows);
+        render_to_screen(&mut display, &mut screen, pixels, viewport);
+        screen
+    }
+
+    fn diffed_frames_match_fresh_render(mode: DisplayMode) -> VtScreen {
+        let mut display = headless_display(mode, 10, 4);
+        let mut screen = VtScreen::new(10, 4);
+        let viewport = viewport_at(1, 1, 8, 4);
+
+        render_to_screen(&mut display, &mut screen, &ramp_frame(8, 4, 1), viewport);
+        // Only columns 1 and 5 change, so the encoder has to skip cells in
+        // between and reposition the cursor correctly.
+        let second = ramp_frame(8, 4, 5);
+        render_to_screen(&mut display, &mut screen, &second, viewport);
+        assert_eq!(
+            screen.snapshot(),
+            fresh_screen(mode, &second, viewport).snapshot()
+        );
+
+        // Recentering invalidates the cache; nothing may linger at the old
+        // offset.
+        let moved = viewport_at(2, 2, 8, 4);
+        display.invalidate_cache();
+        render_to_screen(&mut display, &mut screen, &second, moved);
+        assert_eq!(
+            screen.snapshot(),
+            fresh_screen(mode, &second, moved).snapshot()
+        );
+        screen
+    }
+
+    #[test]
+    fn ascii_diff_output_reconstructs_golden_screen() {
+        let screen = diffed_frames_match_fresh_render(DisplayMode::Ascii);
+        assert_golden("ascii.screen", &screen.snapshot());
+    }
+
+    #[test]
+    fn rgb_diff_output_reconstructs_golden_screen() {
+        let screen = diffed_frames_match_fresh_render(DisplayMode::Rgb);
+        assert_golden("rgb.screen", &screen.snapshot());
+    }
+
+    #[test]
+    fn wide_rgb_frames_encode_in_bands_that_keep_the_diff_state() {
+        let (cols, rows) = (320u16, 40u16);
+        let viewport = RenderViewport {
+            offset_x: 0,
+            offset_y: 0,
+            terminal_cols: cols,
+            terminal_rows: rows,
+            pixel_width: cols as u32,
+            pixel_height: rows as u32 * 2,
+        };
+        assert!(cols as usize * rows as usize >= PARALLEL_ENCODE_THRESHOLD);
+        let mut display = headless_display(DisplayMode::Rgb, cols, rows);
+        let mut screen = VtScreen::new(cols, rows);
+
+        let first = ramp_frame(cols as usize, rows as usize * 2, 3);
+        render_to_screen(&mut display, &mut screen, &first, viewport);
+        // A block straddling the band boundary after cell row 8 changes.
+        let mut second = first.clone();
+        for y in 12..20 {
+            for x in 100..200 {
+                second[(y * cols as usize + x) * 3..][..3].copy_from_slice(&[9, 200, 30]);
+            }
+        }
+        render_to_screen(&mut display, &mut screen, &second, viewport);
+        assert_eq!(
+            screen.snapshot(),
+            fresh_screen(DisplayMode::Rgb, &second, viewport).snapshot()
+        );
+
+        // Every band recorded what it drew, so an unchanged frame is empty.
+        render_to_screen(&mut display, &mut screen, &second, viewport);
+        assert!(!display.last_output().contains(&b'H'));
+    }
+
+    /// Collects output; with a gate, the first write blocks until released
+    /// like a saturated terminal, so a test can queue frames behind it.
+    struct CollectingSink {
+        written: Arc<Mutex<Vec<u8>>>,
+        gate: Option<(Sender<()>, Receiver<()>)>,
+    }
+
+    impl Write for CollectingSink {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            if let Some((entered, release)) = self.gate.take() {
+                let _ = entered.send(());
+                let _ = release.recv();
+            }
+            self.written.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Plays `frames` through a pipelined display. A text row counting down
+    /// the frames left is drawn after each one, and the screen is
+    /// invalidated halfway. With `stalled`, the terminal accepts nothing
+    /// until every frame has been queued.
+    fn pipelined_screen(
+        mode: DisplayMode,
+        frames: &[Vec<u8>],
+        viewport: RenderViewport,
+        stalled: bool,
+    ) -> (VtScreen, OutputStats) {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (entered_sender, entered) = crossbeam_channel::bounded(1);
+        let (release, release_receiver) = crossbeam_channel::bounded(1);
+        let sink = CollectingSink {
+            written: written.clone(),
+            gate: stalled.then_some((entered_sender, release_receiver)),
+        };
+        let mut display = DisplayManager::headless_pipelined(mode, Box::new(sink), 10, 4).unwrap();
+        for (index, pixels) in frames.iter().enumerate() {
+            let cells = FrameProcessor::new(
+                viewport.pixel_width as usize,
+                viewport.pixel_height as usize,
+            )
+            .process_frame(pixels);
+            if index == frames.len() / 2 {
+                display.invalidate_cache();
+            }
+            display.render(pixels, Some(&cells), viewport).unwrap();
+            let text = format!("{:02}", frames.len() - index);
+            display
+                .draw_text_row(3, &text, (255, 255, 255), (0, 0, 0))
+                .unwrap();
+        }
+        if stalled {
+            entered.recv().unwrap();
+            release.send(()).unwrap();
+        }
+        let stats = Arc::clone(&display.output_stats);
+        drop(display);
+        let stats = *stats.lock().unwrap();
+
+        let mut screen = VtScreen::new(10, 4);
+        screen.feed(&written.lock().unwrap());
+        assert!(!screen.in_sync_update(), "output left sync output open");
+        (screen, stats)
+    }
+
+    fn pipelined_output_settles_on_the_newest_frame(mode: DisplayMode) {
+        let viewport = viewport_at(1, 1, 8, 4);
+        let frames: Vec<_> = (0..40).map(|index| ramp_frame(8, 4, index % 8)).collect();
+        // Frames are skipped while the terminal is busy, yet the screen
+        // ends up as if only the last one had been drawn.
+        let (screen, stats) = pipelined_screen(mode, &frames, viewport, true);
+        assert!(stats.stale_frames > 0);
+        let (expected, _) = pipelined_screen(mode, &frames[39..], viewport, false);
+        assert_eq!(screen.snapshot(), expected.snapshot());
+    }
+
+    #[test]
+    fn pipelined_ascii_output_settles_on_the_newest_frame() {
+        pipelined_output_settles_on_the_newest_frame(DisplayMode::Ascii);
+    }
+
+    #[test]
+    fn pipelined_rgb_output_settles_on_the_newest_frame() {
+        pipelined_output_settles_on_the_newest_frame(DisplayMode::Rgb);
+    }
+
+    #[test]
+    fn hiding_an_overlay_repaints_the_video_underneath() {
+        for mode in [DisplayMode::Ascii, DisplayMode::Rgb] {
+            let mut display = headless_display(mode, 10, 4);
+            let mut screen = VtScreen::new(10, 4);
+            let viewport = viewport_at(1, 1, 8, 4);
+            let pixels = ramp_frame(8, 4, 5);
+
+            display.overlay_mut().begin(8, 2);
+            display
+                .overlay_mut()
+                .put_text(1, 1, "HUD", (255, 255, 255), (0, 0, 0));
+            render_to_screen(&mut display, &mut screen, &pixels, viewport);
+            let text = screen.text();
+            let hud_row = text.lines().nth(2).unwrap();
+            assert_eq!(hud_row.chars().skip(2).take(3).collect::<String>(), "HUD");
+
+            display.overlay_mut().begin(8, 2);
+            render_to_screen(&mut display, &mut screen, &pixels, viewport);
+            assert_eq!(
+                screen.snapshot(),
+                fresh_screen(mode, &pixels, viewport).snapshot()
+            );
+        }
+    }
+
+    #[test]
+    fn wide_overlay_glyphs_keep_the_columns_after_them_aligned() {
+        for mode in [DisplayMode::Ascii, DisplayMode::Rgb] {
+            let mut display = headless_display(mode, 10, 4);
+            let mut screen = VtScreen::new(10, 4);
+            let viewport = viewport_at(1, 1, 8, 4);
+            let pixels = ramp_frame(8, 4, 5);
+
+            display.overlay_mut().begin(8, 2);
+            display
+                .overlay_mut()
+                .put_text(1, 0, "자막x", (255, 255, 255), (0, 0, 0));
+            render_to_screen(&mut display, &mut screen, &pixels, viewport);
+
+            // Overlay column 1 is screen column 2; the glyphs cover 2..6.
+            assert_eq!(screen.cell(2, 1).ch, '자');
+            assert_eq!(screen.cell(4, 1).ch, '막');
+            assert_eq!(screen.cell(6, 1).ch, 'x');
+            let fresh = fresh_screen(mode, &pixels, viewport);
+            assert_eq!(screen.cell(7, 1), fresh.cell(7, 1), "{:?}", mode);
+
+            display.overlay_mut().begin(8, 2);
+            render_to_screen(&mut display, &mut screen, &pixels, viewport);
+            assert_eq!(screen.snapshot(), fresh.snapshot());
+        }
+    }
+
+    #[test]
+    fn text_rows_are_written_only_when_they_change() {
+        let mut display = headless_display(DisplayMode::Rgb, 10, 4);
+        let white = (255, 255, 255);
+        let black = (0, 0, 0);
+
+        display.draw_text_row(3, "status", white, black).unwrap();
+        let first = display.output_stats().bytes;
+        assert!(first > 0);
+
+        display.draw_text_row(3, "status", white, black).unwrap();
+        assert_eq!(display.output_stats().bytes, first);
+
+        display.draw_text_row(3, "paused", white, black).unwrap();
+        let second = display.output_stats().bytes;
+        assert!(second > first);
+
+        // A full clear wipes the row, so the same text has to be redrawn.
+        display.invalidate_cache();
+        display.draw_text_row(3, "paused", white, black).unwrap();
+        assert!(display.output_stats().bytes > second);
+    }
+
     #[test]
     fn sync_output_sequences_are_omitted_when_unsupported() {
         assert_eq!(sync_begin_sequence(false), b"");
@@ -0,0 +1,391 @@
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Built-in looks in the order the cycle key visits them
+pub const PRESETS: [&str; 5] = ["sepia", "phosphor", "amber", "duotone", "posterize"];
+
+const POSTERIZE_LEVELS: u32 = 4;
+/// Brightness kept on the odd pixel rows of the CRT look, out of 256
+const SCANLINE_GAIN: u32 = 180;
+
+/// A stylized colour look applied to the scaled RGB frame
+///
+/// Runs on the player thread right before `FrameProcessor`, so it costs per
+/// output pixel and switching looks never touches the decoder.
+#[derive(Debug, Clone)]
+pub struct Look {
+    name: String,
+    transform: Transform,
+    scanlines: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Transform {
+    /// Luma mapped onto a colour ramp
+    Tone(Box<[[u8; 3]; 256]>),
+    /// The same table for every channel
+    Channels(Box<[u8; 256]>),
+    Cube(Arc<CubeLut>),
+}
+
+impl Look {
+    /// One of `PRESETS`.
+    pub fn preset(name: &str) -> Option<Self> {
+        let (transform, scanlines) = match name {
+            "sepia" => (tone([[0, 0, 0], [150, 102, 56], [255, 240, 205]]), false),
+            "phosphor" => (tone([[0, 0, 0], [24, 178, 64], [205, 255, 205]]), true),
+            "amber" => (tone([[0, 0, 0], [206, 112, 0], [255, 222, 140]]), false),
+            "duotone" => (
+                tone([[36, 24, 92], [146, 107, 106], [255, 190, 120]]),
+                false,
+            ),
+            "posterize" => (posterize(POSTERIZE_LEVELS), false),
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            transform,
+            scanlines,
+        })
+    }
+
+    /// A `.cube` 3D LUT file.
+    pub fn from_cube(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read LUT {}", path.display()))?;
+        let lut = CubeLut::parse(&text).with_context(|| format!("in {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "lut".to_string());
+        Ok(Self {
+            name,
+            transform: Transform::Cube(Arc::new(lut)),
+            scanlines: false,
+        })
+    }
+
+    /// `--grade` value: a preset name or a path to a `.cube` file.
+    pub fn parse(arg: &str) -> Result<Self> {
+        if let Some(look) = Self::preset(&arg.to_ascii_lowercase()) {
+            return Ok(look);
+        }
+        let path = Path::new(arg);
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cube"))
+        {
+            return Self::from_cube(path);
+        }
+        bail!(
+            "unknown look '{}'; expected {} or a .cube file",
+            arg,
+            PRESETS.join(", ")
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_lut(&self) -> bool {
+        matches!(self.transform, Transform::Cube(_))
+    }
+
+    /// Grades a packed RGB frame `width` pixels wide in place.
+    pub fn apply(&self, rgb: &mut [u8], width: usize) {
+        match &self.transform {
+            Transform::Tone(ramp) => {
+                for pixel in rgb.chunks_exact_mut(3) {
+                    pixel.copy_from_slice(&ramp[luma(pixel) as usize]);
+                }
+            }
+            Transform::Channels(table) => {
+                for value in rgb.iter_mut() {
+                    *value = table[*value as usize];
+                }
+            }
+            Transform::Cube(lut) => {
+                for pixel in rgb.chunks_exact_mut(3) {
+                    let graded = lut.sample([pixel[0], pixel[1], pixel[2]]);
+                    pixel.copy_from_slice(&graded);
+                }
+            }
+        }
+        if self.scanlines && width > 0 {
+            for row in rgb.chunks_exact_mut(width * 3).skip(1).step_by(2) {
+                for value in row {
+                    *value = ((u32::from(*value) * SCANLINE_GAIN) >> 8) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// The looks the cycle key steps through, with "off" between the last and
+/// the first
+#[derive(Debug, Clone)]
+pub struct GradeCycle {
+    looks: Vec<Look>,
+    active: Option<usize>,
+}
+
+impl GradeCycle {
+    /// Every preset plus `initial` when it is a LUT; starts on `initial`.
+    pub fn new(initial: Option<Look>) -> Self {
+        let mut looks: Vec<Look> = PRESETS
+            .iter()
+            .filter_map(|name| Look::preset(name))
+            .collect();
+        let active = initial.map(|look| {
+            looks
+                .iter()
+                .position(|preset| preset.name == look.name && !look.is_lut())
+                .unwrap_or_else(|| {
+                    looks.push(look);
+                    looks.len() - 1
+                })
+        });
+        Self { looks, active }
+    }
+
+    pub fn active(&self) -> Option<&Look> {
+        self.active.map(|index| &self.looks[index])
+    }
+
+    /// Moves to the next look and returns it; `None` is "off".
+    pub fn cycle(&mut self) -> Option<&Look> {
+        self.active = match self.active {
+            None if !self.looks.is_empty() => Some(0),
+            Some(index) if index + 1 < self.looks.len() => Some(index + 1),
+            _ => None,
+        };
+        self.active()
+    }
+}
+
+/// A 3D colour lookup table in the Adobe/Resolve `.cube` format
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLut {
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// Red varies fastest, then green, then blue
+    entries: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut entries = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            match keyword {
+                "TITLE" => {}
+                "LUT_1D_SIZE" => bail!("1D LUTs are not supported; use a 3D .cube"),
+                "LUT_3D_SIZE" => {
+                    let value: usize = words
+                        .next()
+                        .and_then(|value| value.parse().ok())
+                        .with_context(|| format!("line {}: bad LUT_3D_SIZE", index + 1))?;
+                    if !(2..=256).contains(&value) {
+                        bail!("LUT_3D_SIZE must be between 2 and 256, got {}", value);
+                    }
+                    size = Some(value);
+                }
+                "DOMAIN_MIN" => domain_min = triple(line, index)?,
+                "DOMAIN_MAX" => domain_max = triple(line, index)?,
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => entries.push(triple(line, index)?),
+            }
+        }
+
+        let size = size.context("missing LUT_3D_SIZE")?;
+        if entries.len() != size * size * size {
+            bail!(
+                "expected {} entries for a {}-point cube, found {}",
+                size * size * size,
+                size,
+                entries.len()
+            );
+        }
+        if (0..3).any(|channel| domain_max[channel] <= domain_min[channel]) {
+            bail!("DOMAIN_MAX must be above DOMAIN_MIN");
+        }
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            entries,
+        })
+    }
+
+    /// Trilinear lookup of one pixel.
+    fn sample(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let last = (self.size - 1) as f32;
+        let mut low = [0usize; 3];
+        let mut high = [0usize; 3];
+        let mut fraction = [0f32; 3];
+        for channel in 0..3 {
+            let span = self.domain_max[channel] - self.domain_min[channel];
+            let position = ((f32::from(rgb[channel]) / 255.0 - self.domain_min[channel]) / span)
+                .clamp(0.0, 1.0)
+                * last;
+            low[channel] = position.floor() as usize;
+            high[channel] = (low[channel] + 1).min(self.size - 1);
+            fraction[channel] = position - low[channel] as f32;
+        }
+
+        let at = |r: usize, g: usize, b: usize| self.entries[r + self.size * (g + self.size * b)];
+        let mut out = [0u8; 3];
+        for (channel, value) in out.iter_mut().enumerate() {
+            let lerp =
+                |a: [f32; 3], b: [f32; 3], t: f32| a[channel] + (b[channel] - a[channel]) * t;
+            let [fr, fg, fb] = fraction;
+            let c00 = lerp(at(low[0], low[1], low[2]), at(high[0], low[1], low[2]), fr);
+            let c10 = lerp(
+                at(low[0], high[1], low[2]),
+                at(high[0], high[1], low[2]),
+                fr,
+            );
+            let c01 = lerp(
+                at(low[0], low[1], high[2]),
+                at(high[0], low[1], high[2]),
+                fr,
+            );
+            let c11 = lerp(
+                at(low[0], high[1], high[2]),
+                at(high[0], high[1], high[2]),
+                fr,
+            );
+            let c0 = c00 + (c10 - c00) * fg;
+            let c1 = c01 + (c11 - c01) * fg;
+            *value = ((c0 + (c1 - c0) * fb) * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+        out
+    }
+}
+
+fn triple(line: &str, index: usize) -> Result<[f32; 3]> {
+    let values: Vec<f32> = line
+        .split_whitespace()
+        .filter_map(|word| word.parse().ok())
+        .collect();
+    match values[..] {
+        [r, g, b] => Ok([r, g, b]),
+        _ => bail!("line {}: expected three numbers", index + 1),
+    }
+}
+
+/// Shadow, mid and highlight colours stretched over the 256 luma levels.
+fn tone(stops: [[u8; 3]; 3]) -> Transform {
+    let mut ramp = Box::new([[0u8; 3]; 256]);
+    for (level, color) in ramp.iter_mut().enumerate() {
+        let (from, to, t) = if level < 128 {
+            (stops[0], stops[1], level as f32 / 128.0)
+        } else {
+            (stops[1], stops[2], (level - 128) as f32 / 127.0)
+        };
+        for channel in 0..3 {
+            let (a, b) = (f32::from(from[channel]), f32::from(to[channel]));
+            color[channel] = (a + (b - a) * t).round() as u8;
+        }
+    }
+    Transform::Tone(ramp)
+}
+
+fn posterize(levels: u32) -> Transform {
+    let steps = levels - 1;
+    let mut table = Box::new([0u8; 256]);
+    for (value, entry) in table.iter_mut().enumerate() {
+        let step = (value as u32 * steps + 127) / 255;
+        *entry = (step * 255 / steps) as u8;
+    }
+    Transform::Channels(table)
+}
+
+/// Rec. 601 luma in integer math
+fn luma(pixel: &[u8]) -> u8 {
+    ((77 * u32::from(pixel[0]) + 150 * u32::from(pixel[1]) + 29 * u32::from(pixel[2])) >> 8) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity_cube(size: usize) -> String {
+        let mut text = format!("TITLE \"identity\"\nLUT_3D_SIZE {}\n", size);
+        let last = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    text.push_str(&format!(
+                        "{} {} {}\n",
+                        r as f32 / last,
+                        g as f32 / last,
+                        b as f32 / last
+                    ));
+                }
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn identity_cube_leaves_pixels_alone() {
+        let lut = CubeLut::parse(&identity_cube(5)).unwrap();
+        for rgb in [[0, 0, 0], [255, 255, 255], [12, 130, 250], [200, 7, 99]] {
+            let out = lut.sample(rgb);
+            for channel in 0..3 {
+                assert!(out[channel].abs_diff(rgb[channel]) <= 1, "{:?}", out);
+            }
+        }
+
+        assert!(CubeLut::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(CubeLut::parse("LUT_1D_SIZE 4\n").is_err());
+    }
+
+    #[test]
+    fn tone_looks_keep_black_and_posterize_snaps_levels() {
+        for name in ["sepia", "phosphor", "amber"] {
+            let mut rgb = vec![0, 0, 0, 255, 255, 255];
+            Look::preset(name).unwrap().apply(&mut rgb, 2);
+            assert_eq!(&rgb[..3], [0, 0, 0], "{}", name);
+            assert!(rgb[3..].iter().any(|value| *value > 200), "{}", name);
+        }
+
+        let mut rgb = vec![0, 40, 100, 160, 230, 255];
+        Look::preset("posterize").unwrap().apply(&mut rgb, 2);
+        assert_eq!(rgb, [0, 0, 85, 170, 255, 255]);
+    }
+
+    #[test]
+    fn phosphor_darkens_every_other_row() {
+        let mut rgb = vec![255; 3 * 2];
+        Look::preset("phosphor").unwrap().apply(&mut rgb, 1);
+        assert!(rgb[4] < rgb[1]);
+    }
+
+    #[test]
+    fn cycle_visits_every_look_then_turns_off() {
+        let mut cycle = GradeCycle::new(None);
+        let mut names = Vec::new();
+        while let Some(look) = cycle.cycle() {
+            names.push(look.name().to_string());
+        }
+        assert_eq!(names, PRESETS);
+        assert!(cycle.active().is_none());
+
+        let cycle = GradeCycle::new(Look::preset("amber"));
+        assert_eq!(cycle.active().map(Look::name), Some("amber"));
+    }
+}
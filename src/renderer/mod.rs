@@ -1,11 +1,23 @@
 pub mod backend;
 pub mod cell;
 pub mod display;
+pub mod grading;
+pub mod mono;
+pub mod overlay;
+mod pipeline;
 pub mod processor;
+#[cfg(test)]
+pub(crate) mod vt_screen;
+mod writer;
 
 pub use backend::ActiveRenderBackend;
 pub use display::DisplayManager;
 pub use display::DisplayMode;
+pub use display::HeadlessTerminal;
+pub use display::OutputStats;
 pub use display::RenderViewport;
+pub use display::TextSpan;
 pub use display::TruecolorPolicy;
+pub use mono::{MonoMode, MonoRenderer};
+pub use overlay::CellOverlay;
 pub use processor::FrameProcessor;
@@ -0,0 +1,261 @@
+use super::backend::ActiveRenderBackend;
+use super::cell::CellData;
+
+/// Whether two-tone sources are drawn with fixed colours
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum MonoMode {
+    Off,
+    On,
+    /// Switch on while the source stays near-monochrome
+    Auto,
+}
+
+/// A pixel turns on above `HIGH` and off below `LOW`; in between it keeps
+/// its previous state so anti-aliased edges do not flicker.
+const THRESHOLD_LOW: u32 = 104;
+const THRESHOLD_HIGH: u32 = 152;
+/// Channel spread above which a pixel counts as coloured
+const CHROMA_LIMIT: u8 = 40;
+/// Share of coloured and of mid-grey samples a two-tone frame may have, in
+/// percent
+const MAX_CHROMATIC_PERCENT: usize = 2;
+const MAX_MIDTONE_PERCENT: usize = 15;
+/// Consecutive frames needed to switch auto mode on and off again
+const AUTO_ENTER_FRAMES: u32 = 8;
+const AUTO_LEAVE_FRAMES: u32 = 4;
+/// Every n-th pixel is sampled for detection
+const SAMPLE_STRIDE: usize = 4;
+
+pub const MONO_ON: (u8, u8, u8) = (255, 255, 255);
+pub const MONO_OFF: (u8, u8, u8) = (0, 0, 0);
+
+/// 1-bit rendering for black-and-white sources
+///
+/// Pixels are thresholded with hysteresis and drawn as half-block glyphs
+/// in two fixed colours, so the RGB encoder emits one SGR pair per frame
+/// instead of one per changed cell.
+pub struct MonoRenderer {
+    mode: MonoMode,
+    active: bool,
+    streak: u32,
+    bits: Vec<bool>,
+    width: usize,
+    on: (u8, u8, u8),
+    off: (u8, u8, u8),
+}
+
+impl MonoRenderer {
+    pub fn new(mode: MonoMode) -> Self {
+        Self {
+            mode,
+            active: mode == MonoMode::On,
+            streak: 0,
+            bits: Vec::new(),
+            width: 0,
+            on: MONO_ON,
+            off: MONO_OFF,
+        }
+    }
+
+    /// Mono only ever draws half-block cells, so the ASCII backend keeps
+    /// its full brightness ramp and never thresholds, even in auto mode.
+    pub fn for_backend(mode: MonoMode, backend: ActiveRenderBackend) -> Self {
+        if backend.requires_cell_buffer() {
+            Self::new(mode)
+        } else {
+            Self::new(MonoMode::Off)
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The two colours cells are drawn in.
+    pub fn set_colors(&mut self, on: (u8, u8, u8), off: (u8, u8, u8)) {
+        self.on = on;
+        self.off = off;
+    }
+
+    /// Runs detection in auto mode and, when mono is on, thresholds the
+    /// frame in place to pure black and white. Returns whether it did.
+    pub fn prepare(&mut self, rgb: &mut [u8], width: usize, height: usize) -> bool {
+        let pixels = width * height;
+        if rgb.len() < pixels * 3 || pixels == 0 {
+            return false;
+        }
+        if self.mode == MonoMode::Auto {
+            self.observe(is_two_tone(&rgb[..pixels * 3]));
+        }
+        if !self.active {
+            return false;
+        }
+
+        if self.bits.len() != pixels || self.width != width {
+            self.bits = rgb[..pixels * 3]
+                .chunks_exact(3)
+                .map(|pixel| luma(pixel) >= (THRESHOLD_LOW + THRESHOLD_HIGH) / 2)
+                .collect();
+            self.width = width;
+        }
+        for (pixel, bit) in rgb.chunks_exact_mut(3).zip(self.bits.iter_mut()) {
+            let value = luma(pixel);
+            if value >= THRESHOLD_HIGH {
+                *bit = true;
+            } else if value < THRESHOLD_LOW {
+                *bit = false;
+            }
+            pixel.fill(if *bit { 255 } else { 0 });
+        }
+        true
+    }
+
+    /// Half-block cells for the last prepared frame.
+    pub fn cells_into(&self, cells: &mut [CellData]) {
+        let width = self.width;
+        if width == 0 || cells.len() * 2 > self.bits.len() {
+            return;
+        }
+        for (index, cell) in cells.iter_mut().enumerate() {
+            let (cx, cy) = (index % width, index / width);
+            let top = self.bits[cy * 2 * width + cx];
+            let bottom = self.bits[(cy * 2 + 1) * width + cx];
+            *cell = CellData {
+                char: match (top, bottom) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                },
+                fg: self.on,
+                bg: self.off,
+            };
+        }
+    }
+
+    fn observe(&mut self, two_tone: bool) {
+        if two_tone == self.active {
+            self.streak = 0;
+            return;
+        }
+        self.streak += 1;
+        let needed = if self.active {
+            AUTO_LEAVE_FRAMES
+        } else {
+            AUTO_ENTER_FRAMES
+        };
+        if self.streak >= needed {
+            self.active = two_tone;
+            self.streak = 0;
+            crate::utils::logger::info(&format!(
+                "mono auto: {}",
+                if two_tone {
+                    "source looks two-tone, 1-bit rendering on"
+                } else {
+                    "source has colour or grey detail, 1-bit rendering off"
+                }
+            ));
+        }
+    }
+}
+
+/// Whether a frame is almost only black, white and neutral near them.
+fn is_two_tone(rgb: &[u8]) -> bool {
+    let mut samples = 0;
+    let mut chromatic = 0;
+    let mut midtone = 0;
+    for pixel in rgb.chunks_exact(3).step_by(SAMPLE_STRIDE) {
+        samples += 1;
+        let max = pixel.iter().max().copied().unwrap_or(0);
+        let min = pixel.iter().min().copied().unwrap_or(0);
+        if max - min > CHROMA_LIMIT {
+            chromatic += 1;
+        }
+        if (64..192).contains(&luma(pixel)) {
+            midtone += 1;
+        }
+    }
+    chromatic * 100 <= samples * MAX_CHROMATIC_PERCENT
+        && midtone * 100 <= samples * MAX_MIDTONE_PERCENT
+}
+
+fn luma(pixel: &[u8]) -> u32 {
+    (77 * u32::from(pixel[0]) + 150 * u32::from(pixel[1]) + 29 * u32::from(pixel[2])) >> 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(values: &[u8]) -> Vec<u8> {
+        values.iter().flat_map(|value| [*value; 3]).collect()
+    }
+
+    #[test]
+    fn hysteresis_holds_pixels_inside_the_band() {
+        let mut mono = MonoRenderer::new(MonoMode::On);
+        let mut frame = grey(&[0, 255, 0, 255]);
+        assert!(mono.prepare(&mut frame, 2, 2));
+
+        // Both middle values sit inside the band, so each pixel keeps its
+        // previous state instead of following a single cut-off.
+        let mut frame = grey(&[140, 120, 160, 90]);
+        mono.prepare(&mut frame, 2, 2);
+        assert_eq!(frame, grey(&[0, 255, 255, 0]));
+    }
+
+    #[test]
+    fn cells_use_half_blocks_in_two_fixed_colors() {
+        let mut mono = MonoRenderer::new(MonoMode::On);
+        let mut frame = grey(&[255, 0, 255, 0, 0, 0, 255, 255]);
+        mono.prepare(&mut frame, 4, 2);
+        let mut cells = vec![CellData::default(); 4];
+        mono.cells_into(&mut cells);
+
+        let glyphs: String = cells.iter().map(|cell| cell.char).collect();
+        assert_eq!(glyphs, "▀ █▄");
+        assert!(cells
+            .iter()
+            .all(|cell| cell.fg == MONO_ON && cell.bg == MONO_OFF));
+    }
+
+    #[test]
+    fn auto_mode_needs_a_run_of_two_tone_frames() {
+        let mut mono = MonoRenderer::new(MonoMode::Auto);
+        let two_tone = grey(&[0, 255, 0, 255]);
+        for _ in 1..AUTO_ENTER_FRAMES {
+            assert!(!mono.prepare(&mut two_tone.clone(), 2, 2));
+        }
+        assert!(mono.prepare(&mut two_tone.clone(), 2, 2));
+
+        let colour = [255, 0, 0].repeat(4);
+        for _ in 1..AUTO_LEAVE_FRAMES {
+            assert!(mono.prepare(&mut colour.clone(), 2, 2));
+        }
+        assert!(!mono.prepare(&mut colour.clone(), 2, 2));
+        assert!(!MonoRenderer::new(MonoMode::Off).prepare(&mut two_tone.clone(), 2, 2));
+    }
+
+    #[test]
+    fn ascii_keeps_its_grey_levels_with_the_default_mode() {
+        // Near two-tone, with one mid-grey sample in ten.
+        let values: Vec<u8> = (0..40)
+            .map(|index| match index {
+                0 => 128,
+                _ if index % 2 == 0 => 255,
+                _ => 0,
+            })
+            .collect();
+        let frame = grey(&values);
+
+        let mut ascii = MonoRenderer::for_backend(MonoMode::Auto, ActiveRenderBackend::AnsiAscii);
+        let mut rgb = MonoRenderer::for_backend(MonoMode::Auto, ActiveRenderBackend::AnsiRgb);
+        for _ in 0..AUTO_ENTER_FRAMES * 2 {
+            let mut ascii_frame = frame.clone();
+            assert!(!ascii.prepare(&mut ascii_frame, 8, 5));
+            assert_eq!(ascii_frame, frame);
+            rgb.prepare(&mut frame.clone(), 8, 5);
+        }
+        assert!(rgb.is_active());
+    }
+}
@@ -0,0 +1,158 @@
+use super::cell::{char_width, CellData, WIDE_CONTINUATION};
+
+/// Cells drawn on top of the video grid
+///
+/// Kept sparse and sorted by cell index so the encoders can merge it while
+/// walking the grid once. The diff cache stores the composited cell, so
+/// showing, changing or hiding an overlay repaints exactly the cells it
+/// covers.
+#[derive(Debug, Clone, Default)]
+pub struct CellOverlay {
+    width: usize,
+    height: usize,
+    cells: Vec<(usize, CellData)>,
+    sorted: bool,
+}
+
+impl CellOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clears the overlay and sizes it for a `width` x `height` cell grid.
+    pub fn begin(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.cells.clear();
+        self.sorted = true;
+    }
+
+    pub fn put(&mut self, x: usize, y: usize, cell: CellData) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = y * self.width + x;
+        if self.cells.last().is_some_and(|(last, _)| *last >= index) {
+            self.sorted = false;
+        }
+        self.cells.push((index, cell));
+    }
+
+    /// Writes `text` left to right from `(x, y)`, clipped at the grid edge.
+    ///
+    /// Double-width glyphs take two cells, the second holding
+    /// `WIDE_CONTINUATION`; a glyph that would straddle the edge is dropped,
+    /// as are zero-width marks. Returns the number of cells written.
+    pub fn put_text(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &str,
+        fg: (u8, u8, u8),
+        bg: (u8, u8, u8),
+    ) -> usize {
+        if y >= self.height {
+            return 0;
+        }
+        let mut column = x;
+        for char in text.chars() {
+            let width = char_width(char);
+            if width == 0 {
+                continue;
+            }
+            if column + width > self.width {
+                break;
+            }
+            self.put(column, y, CellData { char, fg, bg });
+            if width == 2 {
+                self.put(
+                    column + 1,
+                    y,
+                    CellData {
+                        char: WIDE_CONTINUATION,
+                        fg,
+                        bg,
+                    },
+                );
+            }
+            column += width;
+        }
+        column - x
+    }
+
+    /// Cells sorted by index; later writes to the same cell win.
+    pub(crate) fn sorted_cells(&mut self) -> &[(usize, CellData)] {
+        if !self.sorted {
+            self.cells.sort_by_key(|(index, _)| *index);
+            self.cells.dedup_by(|later, earlier| {
+                if later.0 == earlier.0 {
+                    *earlier = *later;
+                    true
+                } else {
+                    false
+                }
+            });
+            self.sorted = true;
+        }
+        &self.cells
+    }
+
+    /// Whether the overlay was laid out for this grid; a stale overlay from
+    /// before a resize is skipped rather than drawn at the wrong place.
+    pub(crate) fn fits(&self, width: usize, height: usize) -> bool {
+        self.width == width && self.height == height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_clipped_at_the_grid_edge() {
+        let mut overlay = CellOverlay::new();
+        overlay.begin(4, 2);
+
+        assert_eq!(overlay.put_text(2, 1, "abc", (1, 1, 1), (0, 0, 0)), 2);
+        assert_eq!(overlay.put_text(0, 2, "off", (1, 1, 1), (0, 0, 0)), 0);
+        let indices: Vec<usize> = overlay
+            .sorted_cells()
+            .iter()
+            .map(|(index, _)| *index)
+            .collect();
+        assert_eq!(indices, vec![6, 7]);
+    }
+
+    #[test]
+    fn wide_glyphs_fill_a_continuation_cell() {
+        let mut overlay = CellOverlay::new();
+        overlay.begin(5, 1);
+
+        // The third glyph would straddle the edge and is dropped.
+        assert_eq!(overlay.put_text(0, 0, "한a글자", (1, 1, 1), (0, 0, 0)), 5);
+        let chars: Vec<char> = overlay
+            .sorted_cells()
+            .iter()
+            .map(|(_, cell)| cell.char)
+            .collect();
+        assert_eq!(
+            chars,
+            vec!['한', WIDE_CONTINUATION, 'a', '글', WIDE_CONTINUATION]
+        );
+    }
+
+    #[test]
+    fn later_writes_win_after_sorting() {
+        let mut overlay = CellOverlay::new();
+        overlay.begin(4, 2);
+        overlay.put_text(1, 0, "xy", (1, 1, 1), (0, 0, 0));
+        overlay.put_text(0, 0, "ab", (2, 2, 2), (0, 0, 0));
+
+        let chars: Vec<(usize, char)> = overlay
+            .sorted_cells()
+            .iter()
+            .map(|(index, cell)| (*index, cell.char))
+            .collect();
+        assert_eq!(chars, vec![(0, 'a'), (1, 'b'), (2, 'y')]);
+    }
+}
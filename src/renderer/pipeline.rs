@@ -0,0 +1,171 @@
+use anyhow::{anyhow, Result};
+use crossbeam_channel::{Receiver, Sender};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use super::cell::CellData;
+use super::display::{FrameEncoder, OutputStats, RenderViewport};
+use super::overlay::CellOverlay;
+use super::writer::TerminalWriter;
+
+/// Frame jobs kept for reuse once encoded
+const SPARE_JOBS: usize = 2;
+
+/// Encodes frames on its own thread and hands them to a `TerminalWriter`
+///
+/// The player only copies a frame into a job and moves on. Jobs are
+/// handled in order, except that a frame already superseded by a newer
+/// queued one is skipped before it is encoded, which leaves the diff state
+/// untouched.
+pub(crate) struct RenderThread {
+    jobs: Option<Sender<RenderJob>>,
+    spare: Receiver<FrameJob>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Everything the encoder needs for one frame, copied off the player thread
+#[derive(Default)]
+pub(crate) struct FrameJob {
+    /// RGB pixels, filled for the ASCII backend
+    pub pixels: Vec<u8>,
+    /// Half-block cells, filled for the RGB backend
+    pub cells: Vec<CellData>,
+    pub overlay: CellOverlay,
+    pub viewport: Option<RenderViewport>,
+}
+
+enum RenderJob {
+    Frame(FrameJob),
+    Text(Vec<u8>),
+    Invalidate,
+}
+
+impl RenderThread {
+    pub(crate) fn spawn(
+        encoder: FrameEncoder,
+        sink: Box<dyn Write + Send>,
+        stats: Arc<Mutex<OutputStats>>,
+    ) -> Result<Self> {
+        let writer = TerminalWriter::spawn(sink, Arc::clone(&stats))?;
+        let (jobs, receiver) = crossbeam_channel::unbounded();
+        let (spare_sender, spare) = crossbeam_channel::bounded(SPARE_JOBS);
+        let handle = std::thread::Builder::new()
+            .name("render".to_string())
+            .spawn(move || {
+                if let Err(error) = encode_jobs(encoder, writer, receiver, spare_sender, stats) {
+                    crate::utils::logger::error(&format!("render thread stopped: {}", error));
+                }
+            })?;
+        let thread = Self {
+            jobs: Some(jobs),
+            spare,
+            handle: Some(handle),
+        };
+        // The first frame must not clear text drawn before it.
+        thread.invalidate();
+        Ok(thread)
+    }
+
+    /// An empty job, reusing the buffers of an encoded one if possible.
+    pub(crate) fn frame_job(&self) -> FrameJob {
+        self.spare.try_recv().unwrap_or_default()
+    }
+
+    pub(crate) fn render(&self, job: FrameJob) -> Result<()> {
+        self.send(RenderJob::Frame(job))
+    }
+
+    /// Queues text drawn outside the viewport, kept in order with frames.
+    pub(crate) fn text(&self, bytes: Vec<u8>) -> Result<()> {
+        self.send(RenderJob::Text(bytes))
+    }
+
+    /// Clears the screen before anything queued after this call; the next
+    /// frame is a keyframe. A stopped thread is reported by the next render.
+    pub(crate) fn invalidate(&self) {
+        let _ = self.send(RenderJob::Invalidate);
+    }
+
+    /// Encodes and writes everything still queued and stops both threads.
+    pub(crate) fn finish(&mut self) {
+        drop(self.jobs.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn send(&self, job: RenderJob) -> Result<()> {
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(job).ok())
+            .ok_or_else(|| anyhow!("render thread stopped"))
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn encode_jobs(
+    mut encoder: FrameEncoder,
+    writer: TerminalWriter,
+    receiver: Receiver<RenderJob>,
+    spare: Sender<FrameJob>,
+    stats: Arc<Mutex<OutputStats>>,
+) -> Result<()> {
+    let mut batch = Vec::new();
+    while let Ok(job) = receiver.recv() {
+        batch.push(job);
+        batch.extend(receiver.try_iter());
+        let newest_frame = batch
+            .iter()
+            .rposition(|job| matches!(job, RenderJob::Frame(_)));
+
+        for (index, job) in batch.drain(..).enumerate() {
+            match job {
+                RenderJob::Frame(job) if Some(index) != newest_frame => {
+                    if let Ok(mut stats) = stats.lock() {
+                        stats.stale_frames += 1;
+                    }
+                    let _ = spare.try_send(job);
+                }
+                RenderJob::Frame(mut job) => {
+                    let Some(viewport) = job.viewport else {
+                        continue;
+                    };
+                    if writer.take_resync() {
+                        encoder.redraw();
+                    }
+                    let encode_started = Instant::now();
+                    let encoded =
+                        encoder.encode(&job.pixels, Some(&job.cells), &mut job.overlay, viewport);
+                    let encode_time = encode_started.elapsed();
+                    let len = encoder.output().len();
+                    if len > 0 {
+                        writer
+                            .send_frame(encoder.take_output(writer.buffer()), encoded.keyframe)?;
+                    }
+                    if let Ok(mut stats) = stats.lock() {
+                        stats.encode_time += encode_time;
+                        if len > 0 {
+                            stats.frames += 1;
+                            stats.bytes += len as u64;
+                        }
+                    }
+                    let _ = spare.try_send(job);
+                }
+                RenderJob::Text(bytes) => writer.send_text(bytes)?,
+                RenderJob::Invalidate => {
+                    let mut bytes = writer.buffer();
+                    encoder.clear_into(&mut bytes);
+                    writer.send_text(bytes)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,55 @@
 use super::cell::CellData;
+use crate::utils::simd::{self, SimdLevel};
 use rayon::prelude::*;
 
 const PARALLEL_CELL_THRESHOLD: usize = 50_000;
 
+/// Whether cells are `[char LE; 4] [fg; 3] [bg; 3] [padding; 2]`, the layout
+/// the vector packers write. Tuple field order is not guaranteed, so other
+/// layouts use the scalar packer.
+const CELL_BYTES_PACKABLE: bool = cfg!(target_endian = "little")
+    && std::mem::size_of::<CellData>() == 12
+    && std::mem::offset_of!(CellData, char) == 0
+    && std::mem::offset_of!(CellData, fg.0) == 4
+    && std::mem::offset_of!(CellData, fg.1) == 5
+    && std::mem::offset_of!(CellData, fg.2) == 6
+    && std::mem::offset_of!(CellData, bg.0) == 7
+    && std::mem::offset_of!(CellData, bg.1) == 8
+    && std::mem::offset_of!(CellData, bg.2) == 9;
+
+/// Byte shuffles that build four packed cells (48 bytes, three vectors)
+/// from four top or four bottom pixels; 0x80 selects zero.
+const TOP_PACK_MASKS: [[u8; 16]; 3] = pack_masks(4);
+const BOTTOM_PACK_MASKS: [[u8; 16]; 3] = pack_masks(7);
+/// The half-block glyph in the char slot of each of the four cells.
+const GLYPH_PACK_BYTES: [[u8; 16]; 3] = glyph_bytes();
+
+const fn pack_masks(color_offset: usize) -> [[u8; 16]; 3] {
+    let mut masks = [[0x80; 16]; 3];
+    let mut byte = 0;
+    while byte < 48 {
+        let offset = byte % 12;
+        if offset >= color_offset && offset < color_offset + 3 {
+            masks[byte / 16][byte % 16] = ((byte / 12) * 3 + offset - color_offset) as u8;
+        }
+        byte += 1;
+    }
+    masks
+}
+
+const fn glyph_bytes() -> [[u8; 16]; 3] {
+    let glyph = ('▀' as u32).to_le_bytes();
+    let mut bytes = [[0; 16]; 3];
+    let mut byte = 0;
+    while byte < 48 {
+        if byte % 12 < 4 {
+            bytes[byte / 16][byte % 16] = glyph[byte % 12];
+        }
+        byte += 1;
+    }
+    bytes
+}
+
 pub struct FrameProcessor {
     pub width: usize,
     pub height: usize,
@@ -26,10 +73,26 @@ impl FrameProcessor {
         let term_height = h / 2;
         let cell_count = w * term_height;
 
-        if cells.len() != cell_count {
+        if cells.len() != cell_count || cell_count == 0 {
             return;
         }
 
+        let row_bytes = w * 3;
+        if pixel_data.len() >= term_height * 2 * row_bytes {
+            let pack_row = |(cy, row): (usize, &mut [CellData])| {
+                let top = &pixel_data[cy * 2 * row_bytes..][..row_bytes];
+                let bottom = &pixel_data[(cy * 2 + 1) * row_bytes..][..row_bytes];
+                pack_half_blocks(simd::level(), top, bottom, row);
+            };
+            if cell_count < PARALLEL_CELL_THRESHOLD {
+                cells.chunks_exact_mut(w).enumerate().for_each(pack_row);
+            } else {
+                cells.par_chunks_exact_mut(w).enumerate().for_each(pack_row);
+            }
+            return;
+        }
+
+        // A short buffer leaves the missing pixels black.
         if cell_count < PARALLEL_CELL_THRESHOLD {
             for (idx, cell) in cells.iter_mut().enumerate() {
                 write_cell(pixel_data, w, idx, cell);
@@ -53,6 +116,117 @@ impl FrameProcessor {
     }
 }
 
+/// Half-block cells for one row pair: `top` pixels become foregrounds,
+/// `bottom` pixels backgrounds.
+fn pack_half_blocks(level: SimdLevel, top: &[u8], bottom: &[u8], cells: &mut [CellData]) {
+    let packed = if CELL_BYTES_PACKABLE {
+        match level {
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Sse41 | SimdLevel::Avx2 => unsafe {
+                x86::pack_half_blocks(top, bottom, cells)
+            },
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            SimdLevel::Neon => unsafe { neon::pack_half_blocks(top, bottom, cells) },
+            _ => 0,
+        }
+    } else {
+        0
+    };
+    pack_half_blocks_scalar(
+        &top[packed * 3..],
+        &bottom[packed * 3..],
+        &mut cells[packed..],
+    );
+}
+
+fn pack_half_blocks_scalar(top: &[u8], bottom: &[u8], cells: &mut [CellData]) {
+    for ((top, bottom), cell) in top
+        .chunks_exact(3)
+        .zip(bottom.chunks_exact(3))
+        .zip(cells.iter_mut())
+    {
+        *cell = CellData {
+            char: '▀',
+            fg: (top[0], top[1], top[2]),
+            bg: (bottom[0], bottom[1], bottom[2]),
+        };
+    }
+}
+
+/// Four cells per step; returns how many cells were written. Callers must
+/// check `CELL_BYTES_PACKABLE`.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::{CellData, BOTTOM_PACK_MASKS, GLYPH_PACK_BYTES, TOP_PACK_MASKS};
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn pack_half_blocks(
+        top: &[u8],
+        bottom: &[u8],
+        cells: &mut [CellData],
+    ) -> usize {
+        let load = |bytes: &[u8; 16]| _mm_loadu_si128(bytes.as_ptr().cast());
+        let top_masks = TOP_PACK_MASKS.map(|mask| load(&mask));
+        let bottom_masks = BOTTOM_PACK_MASKS.map(|mask| load(&mask));
+        let glyphs = GLYPH_PACK_BYTES.map(|bytes| load(&bytes));
+        let mut cell = 0;
+        while cell + 4 <= cells.len() && cell * 3 + 16 <= top.len().min(bottom.len()) {
+            let top_pixels = _mm_loadu_si128(top.as_ptr().add(cell * 3).cast());
+            let bottom_pixels = _mm_loadu_si128(bottom.as_ptr().add(cell * 3).cast());
+            let out = cells.as_mut_ptr().add(cell).cast::<u8>();
+            for part in 0..3 {
+                let bytes = _mm_or_si128(
+                    _mm_or_si128(
+                        _mm_shuffle_epi8(top_pixels, top_masks[part]),
+                        _mm_shuffle_epi8(bottom_pixels, bottom_masks[part]),
+                    ),
+                    glyphs[part],
+                );
+                _mm_storeu_si128(out.add(part * 16).cast(), bytes);
+            }
+            cell += 4;
+        }
+        cell
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+mod neon {
+    use super::{CellData, BOTTOM_PACK_MASKS, GLYPH_PACK_BYTES, TOP_PACK_MASKS};
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn pack_half_blocks(
+        top: &[u8],
+        bottom: &[u8],
+        cells: &mut [CellData],
+    ) -> usize {
+        let load = |bytes: &[u8; 16]| vld1q_u8(bytes.as_ptr());
+        let top_masks = TOP_PACK_MASKS.map(|mask| load(&mask));
+        let bottom_masks = BOTTOM_PACK_MASKS.map(|mask| load(&mask));
+        let glyphs = GLYPH_PACK_BYTES.map(|bytes| load(&bytes));
+        let mut cell = 0;
+        while cell + 4 <= cells.len() && cell * 3 + 16 <= top.len().min(bottom.len()) {
+            let top_pixels = vld1q_u8(top.as_ptr().add(cell * 3));
+            let bottom_pixels = vld1q_u8(bottom.as_ptr().add(cell * 3));
+            let out = cells.as_mut_ptr().add(cell).cast::<u8>();
+            for part in 0..3 {
+                let bytes = vorrq_u8(
+                    vorrq_u8(
+                        vqtbl1q_u8(top_pixels, top_masks[part]),
+                        vqtbl1q_u8(bottom_pixels, bottom_masks[part]),
+                    ),
+                    glyphs[part],
+                );
+                vst1q_u8(out.add(part * 16), bytes);
+            }
+            cell += 4;
+        }
+        cell
+    }
+}
+
 fn write_cell(pixel_data: &[u8], width: usize, idx: usize, cell: &mut CellData) {
     let cx = idx % width;
     let cy = idx / width;
@@ -131,4 +305,21 @@ mod tests {
         assert_eq!(cells[0].fg, (255, 0, 0));
         assert_eq!(cells[0].bg, (0, 255, 0));
     }
+
+    #[test]
+    fn vector_cell_packing_is_bit_exact() {
+        let width = 37;
+        let pixels: Vec<u8> = (0..width * 2 * 3)
+            .map(|index| (index * 97 % 251) as u8)
+            .collect();
+        let (top, bottom) = pixels.split_at(width * 3);
+
+        let mut expected = vec![CellData::default(); width];
+        pack_half_blocks_scalar(top, bottom, &mut expected);
+        for level in simd::available_levels() {
+            let mut cells = vec![CellData::default(); width];
+            pack_half_blocks(level, top, bottom, &mut cells);
+            assert_eq!(cells, expected, "{:?}", level);
+        }
+    }
 }
@@ -0,0 +1,255 @@
+//! Minimal VT screen model for golden-output tests
+//!
+//! Understands exactly the subset the ANSI encoders emit: cursor positioning,
+//! full clears, truecolor SGR and private modes. Anything else panics so a new
+//! escape sequence in the encoder cannot slip past the tests unnoticed.
+
+use super::cell::{char_width, WIDE_CONTINUATION};
+use std::fmt::Write as _;
+
+pub(crate) type Rgb = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ScreenCell {
+    pub ch: char,
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+}
+
+impl Default for ScreenCell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct VtScreen {
+    cols: usize,
+    rows: usize,
+    cells: Vec<ScreenCell>,
+    cursor_x: usize,
+    cursor_y: usize,
+    fg: Option<Rgb>,
+    bg: Option<Rgb>,
+    /// Private modes currently set, e.g. 2026 between sync begin and end
+    private_modes: Vec<u16>,
+}
+
+impl VtScreen {
+    pub(crate) fn new(cols: u16, rows: u16) -> Self {
+        let cols = cols.max(1) as usize;
+        let rows = rows.max(1) as usize;
+        Self {
+            cols,
+            rows,
+            cells: vec![ScreenCell::default(); cols * rows],
+            cursor_x: 0,
+            cursor_y: 0,
+            fg: None,
+            bg: None,
+            private_modes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn cell(&self, x: usize, y: usize) -> ScreenCell {
+        self.cells[y * self.cols + x]
+    }
+
+    /// True while a synchronized update (`?2026h`) is still open.
+    pub(crate) fn in_sync_update(&self) -> bool {
+        self.private_modes.contains(&2026)
+    }
+
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        let text = std::str::from_utf8(bytes).expect("encoder output must be UTF-8");
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch != '\x1b' {
+                assert!(!ch.is_control(), "unexpected control character {:?}", ch);
+                self.print(ch);
+                continue;
+            }
+            assert_eq!(chars.next(), Some('['), "only CSI sequences are expected");
+
+            let private = chars.next_if_eq(&'?').is_some();
+            let mut params = String::new();
+            while let Some(next) = chars.next_if(|c| c.is_ascii_digit() || *c == ';') {
+                params.push(next);
+            }
+            let final_byte = chars.next().expect("truncated CSI sequence");
+            self.csi(private, &params, final_byte);
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        let width = char_width(ch).max(1);
+        let index = self.cursor_y * self.cols + self.cursor_x;
+        let cell = ScreenCell {
+            ch,
+            fg: self.fg,
+            bg: self.bg,
+        };
+        self.cells[index] = cell;
+        if width == 2 && self.cursor_x + 1 < self.cols {
+            self.cells[index + 1] = ScreenCell {
+                ch: WIDE_CONTINUATION,
+                ..cell
+            };
+        }
+        // Autowrap is off (`?7l`), so the last column absorbs overflow.
+        self.cursor_x = (self.cursor_x + width).min(self.cols - 1);
+    }
+
+    fn csi(&mut self, private: bool, params: &str, final_byte: char) {
+        let values: Vec<u16> = params
+            .split(';')
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().expect("numeric CSI parameter"))
+            .collect();
+
+        if private {
+            for mode in values {
+                match final_byte {
+                    'h' if !self.private_modes.contains(&mode) => self.private_modes.push(mode),
+                    'h' => {}
+                    'l' => self.private_modes.retain(|active| *active != mode),
+                    _ => panic!("unexpected private sequence ?{}{}", params, final_byte),
+                }
+            }
+            return;
+        }
+
+        match final_byte {
+            'H' => {
+                let row = values.first().copied().unwrap_or(1).max(1) as usize;
+                let col = values.get(1).copied().unwrap_or(1).max(1) as usize;
+                self.cursor_y = (row - 1).min(self.rows - 1);
+                self.cursor_x = (col - 1).min(self.cols - 1);
+            }
+            'J' if values == [2] => self.cells.fill(ScreenCell::default()),
+            'm' => self.sgr(&values),
+            _ => panic!("unexpected CSI sequence {}{}", params, final_byte),
+        }
+    }
+
+    fn sgr(&mut self, values: &[u16]) {
+        let mut rest = if values.is_empty() { &[0][..] } else { values };
+        while let Some((&code, tail)) = rest.split_first() {
+            rest = tail;
+            match code {
+                0 => {
+                    self.fg = None;
+                    self.bg = None;
+                }
+                38 | 48 => {
+                    let [2, r, g, b] = rest[..4] else {
+                        panic!("only truecolor SGR is expected, got {:?}", values);
+                    };
+                    let color = Some((r as u8, g as u8, b as u8));
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                    rest = &rest[4..];
+                }
+                _ => panic!("unexpected SGR parameter {}", code),
+            }
+        }
+    }
+
+    /// Characters only, one line per row; wide glyphs print once.
+    pub(crate) fn text(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.cols) {
+            out.extend(
+                row.iter()
+                    .map(|cell| cell.ch)
+                    .filter(|ch| *ch != WIDE_CONTINUATION),
+            );
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Characters followed by a `fg/bg` hex grid; unset colors print as `-`.
+    pub(crate) fn snapshot(&self) -> String {
+        let mut out = self.text();
+        out.push('\n');
+        for row in self.cells.chunks(self.cols) {
+            let line: Vec<String> = row
+                .iter()
+                .map(|cell| format!("{}/{}", hex(cell.fg), hex(cell.bg)))
+                .collect();
+            let _ = writeln!(out, "{}", line.join(" "));
+        }
+        out
+    }
+}
+
+fn hex(color: Option<Rgb>) -> String {
+    match color {
+        Some((r, g, b)) => format!("{:02x}{:02x}{:02x}", r, g, b),
+        None => "-".to_string(),
+    }
+}
+
+/// Compares `actual` with `tests/golden/<name>`.
+///
+/// Run with `GASCII_UPDATE_GOLDEN=1` to rewrite the snapshot after an
+/// intentional output change.
+pub(crate) fn assert_golden(name: &str, actual: &str) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name);
+    if std::env::var_os("GASCII_UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing golden file {}; run with GASCII_UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "screen differs from {} (GASCII_UPDATE_GOLDEN=1 to accept)\n--- expected\n{}\n--- actual\n{}",
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_tracks_cursor_and_truecolor_attributes() {
+        let mut screen = VtScreen::new(4, 2);
+        screen.feed(b"\x1b[?2026h\x1b[2;3H\x1b[38;2;1;2;3m\x1b[48;2;4;5;6mab\x1b[0m");
+
+        assert!(screen.in_sync_update());
+        assert_eq!(screen.text(), "    \n  ab\n");
+        assert_eq!(
+            screen.cell(2, 1),
+            ScreenCell {
+                ch: 'a',
+                fg: Some((1, 2, 3)),
+                bg: Some((4, 5, 6)),
+            }
+        );
+
+        screen.feed(b"\x1b[?2026l\x1b[2J");
+        assert!(!screen.in_sync_update());
+        assert_eq!(screen.text(), "    \n    \n");
+    }
+}
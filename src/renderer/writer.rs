@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Result};
+use crossbeam_channel::{Receiver, Sender};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use super::display::OutputStats;
+
+/// Encoded buffers kept for reuse once written
+const SPARE_BUFFERS: usize = 4;
+
+/// Pushes encoded output to the terminal on its own thread
+///
+/// A slow terminal only stalls this thread. Whenever it falls behind it
+/// writes just the newest queued frame; diffed frames after a dropped one
+/// would paint over the wrong picture, so it then waits for a keyframe and
+/// asks for one through `take_resync`. Text chunks are never dropped.
+pub(crate) struct TerminalWriter {
+    chunks: Option<Sender<Chunk>>,
+    spare: Receiver<Vec<u8>>,
+    resync: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+enum Chunk {
+    Text(Vec<u8>),
+    Frame { bytes: Vec<u8>, keyframe: bool },
+}
+
+impl TerminalWriter {
+    pub(crate) fn spawn(
+        sink: Box<dyn Write + Send>,
+        stats: Arc<Mutex<OutputStats>>,
+    ) -> Result<Self> {
+        let (chunks, receiver) = crossbeam_channel::unbounded();
+        let (spare_sender, spare) = crossbeam_channel::bounded(SPARE_BUFFERS);
+        let resync = Arc::new(AtomicBool::new(false));
+        let writer_resync = Arc::clone(&resync);
+        let handle = std::thread::Builder::new()
+            .name("terminal-writer".to_string())
+            .spawn(move || {
+                if let Err(error) = write_chunks(sink, receiver, spare_sender, writer_resync, stats)
+                {
+                    crate::utils::logger::error(&format!("terminal writer stopped: {}", error));
+                }
+            })?;
+        Ok(Self {
+            chunks: Some(chunks),
+            spare,
+            resync,
+            handle: Some(handle),
+        })
+    }
+
+    /// An empty buffer for the next chunk, reusing a written one if possible.
+    pub(crate) fn buffer(&self) -> Vec<u8> {
+        self.spare.try_recv().unwrap_or_default()
+    }
+
+    pub(crate) fn send_text(&self, bytes: Vec<u8>) -> Result<()> {
+        self.send(Chunk::Text(bytes))
+    }
+
+    /// Queues a frame; `keyframe` marks one that repaints every cell.
+    pub(crate) fn send_frame(&self, bytes: Vec<u8>, keyframe: bool) -> Result<()> {
+        self.send(Chunk::Frame { bytes, keyframe })
+    }
+
+    /// Whether the writer dropped a frame since the last call, so the next
+    /// one has to be a keyframe.
+    pub(crate) fn take_resync(&self) -> bool {
+        self.resync.swap(false, Ordering::AcqRel)
+    }
+
+    /// Writes everything still queued and stops the thread.
+    pub(crate) fn finish(&mut self) {
+        drop(self.chunks.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn send(&self, chunk: Chunk) -> Result<()> {
+        self.chunks
+            .as_ref()
+            .and_then(|chunks| chunks.send(chunk).ok())
+            .ok_or_else(|| anyhow!("terminal writer stopped"))
+    }
+}
+
+impl Drop for TerminalWriter {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn write_chunks(
+    mut sink: Box<dyn Write + Send>,
+    receiver: Receiver<Chunk>,
+    spare: Sender<Vec<u8>>,
+    resync: Arc<AtomicBool>,
+    stats: Arc<Mutex<OutputStats>>,
+) -> Result<()> {
+    let mut batch = Vec::new();
+    let mut awaiting_keyframe = false;
+    while let Ok(chunk) = receiver.recv() {
+        batch.push(chunk);
+        batch.extend(receiver.try_iter());
+        let newest_frame = batch
+            .iter()
+            .rposition(|chunk| matches!(chunk, Chunk::Frame { .. }));
+
+        let mut dropped = 0;
+        let write_started = Instant::now();
+        for (index, chunk) in batch.drain(..).enumerate() {
+            let mut bytes = match chunk {
+                Chunk::Text(bytes) => bytes,
+                Chunk::Frame { bytes, keyframe } => {
+                    let stale = Some(index) != newest_frame;
+                    if stale || (awaiting_keyframe && !keyframe) {
+                        // Skipping the newest diff while waiting needs no new
+                        // request; the keyframe asked for earlier is coming.
+                        if stale {
+                            resync.store(true, Ordering::Release);
+                        }
+                        awaiting_keyframe = true;
+                        dropped += 1;
+                        let _ = spare.try_send(recycled(bytes));
+                        continue;
+                    }
+                    awaiting_keyframe = false;
+                    // A keyframe repaints everything, so a request made for
+                    // a frame skipped earlier is already answered.
+                    if keyframe {
+                        resync.store(false, Ordering::Release);
+                    }
+                    bytes
+                }
+            };
+            sink.write_all(&bytes)?;
+            bytes.clear();
+            let _ = spare.try_send(bytes);
+        }
+        sink.flush()?;
+
+        if let Ok(mut stats) = stats.lock() {
+            stats.write_time += write_started.elapsed();
+            stats.stale_frames += dropped;
+        }
+    }
+    sink.flush()?;
+    Ok(())
+}
+
+fn recycled(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.clear();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records everything written; the first write blocks until released so
+    /// a test can queue chunks behind it.
+    struct GatedSink {
+        written: Arc<Mutex<Vec<u8>>>,
+        entered: Sender<()>,
+        release: Receiver<()>,
+        gated: bool,
+    }
+
+    impl Write for GatedSink {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            if std::mem::take(&mut self.gated) {
+                let _ = self.entered.send(());
+                let _ = self.release.recv();
+            }
+            self.written.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn a_saturated_writer_skips_to_the_next_keyframe() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (entered_sender, entered) = crossbeam_channel::bounded(1);
+        let (release, release_receiver) = crossbeam_channel::bounded(1);
+        let sink = GatedSink {
+            written: Arc::clone(&written),
+            entered: entered_sender,
+            release: release_receiver,
+            gated: true,
+        };
+        let stats = Arc::new(Mutex::new(OutputStats::default()));
+        let mut writer = TerminalWriter::spawn(Box::new(sink), Arc::clone(&stats)).unwrap();
+
+        writer.send_frame(b"[key 1]".to_vec(), true).unwrap();
+        entered.recv().unwrap();
+        // Queued while the terminal is busy with the first frame.
+        writer.send_frame(b"[diff 2]".to_vec(), false).unwrap();
+        writer.send_text(b"[status]".to_vec()).unwrap();
+        writer.send_frame(b"[diff 3]".to_vec(), false).unwrap();
+        release.send(()).unwrap();
+
+        // The newest diff is unusable once its predecessor was skipped.
+        while !writer.take_resync() {
+            std::thread::yield_now();
+        }
+        writer.send_frame(b"[key 4]".to_vec(), true).unwrap();
+        writer.finish();
+
+        let written = written.lock().unwrap().clone();
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.starts_with("[key 1][status]"));
+        assert!(written.ends_with("[key 4]"));
+        assert!(!written.contains("diff 2") && !written.contains("diff 3"));
+        assert_eq!(stats.lock().unwrap().stale_frames, 2);
+    }
+
+    #[test]
+    fn a_queued_keyframe_answers_the_resync_itself() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (entered_sender, entered) = crossbeam_channel::bounded(1);
+        let (release, release_receiver) = crossbeam_channel::bounded(1);
+        let sink = GatedSink {
+            written: Arc::clone(&written),
+            entered: entered_sender,
+            release: release_receiver,
+            gated: true,
+        };
+        let stats = Arc::new(Mutex::new(OutputStats::default()));
+        let mut writer = TerminalWriter::spawn(Box::new(sink), Arc::clone(&stats)).unwrap();
+
+        writer.send_frame(b"[key 1]".to_vec(), true).unwrap();
+        entered.recv().unwrap();
+        writer.send_frame(b"[diff 2]".to_vec(), false).unwrap();
+        writer.send_frame(b"[key 3]".to_vec(), true).unwrap();
+        release.send(()).unwrap();
+        writer.finish();
+
+        assert_eq!(written.lock().unwrap().as_slice(), b"[key 1][key 3]");
+        assert_eq!(stats.lock().unwrap().stale_frames, 1);
+        assert!(!writer.take_resync());
+    }
+}
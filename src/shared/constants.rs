@@ -10,6 +10,9 @@ pub const VIDEO_EXTENSIONS: &[&str] = &[
     "mp4", "m4v", "mkv", "avi", "mov", "webm", "flv", "wmv", "mpg", "mpeg", "3gp", "3g2", "ts",
     "m2ts", "mts",
 ];
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+pub const ANIMATED_IMAGE_EXTENSIONS: &[&str] = &["gif"];
+pub const DEFAULT_IMAGE_SEQUENCE_FPS: f64 = 24.0;
 pub const AUDIO_EXTENSIONS: &[&str] = &[
     "mp3", "wav", "wave", "m4a", "mp4", "aac", "flac", "ogg", "oga",
 ];
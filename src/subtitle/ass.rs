@@ -0,0 +1,181 @@
+use super::{parse_timestamp, Cue, CuePlacement, CueStyle};
+use std::collections::HashMap;
+
+/// Parses ASS/SSA `[Events]` using each section's `Format:` line.
+///
+/// Styles contribute their primary colour and whether their alignment is
+/// top-anchored; inline `\an`, `\c`/`\1c` overrides win over the style and
+/// every other override is dropped. Comment lines are ignored.
+pub(super) fn parse(text: &str) -> Vec<Cue> {
+    let mut section = String::new();
+    let mut style_format: Vec<String> = Vec::new();
+    let mut event_format: Vec<String> = Vec::new();
+    let mut styles: HashMap<String, CueStyle> = HashMap::new();
+    let mut cues = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line.to_ascii_lowercase();
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let is_styles = section.contains("styles");
+        let is_events = section == "[events]";
+        match key.trim() {
+            "Format" if is_styles => style_format = field_names(value),
+            "Format" if is_events => event_format = field_names(value),
+            "Style" if is_styles => {
+                let fields = split_fields(value, style_format.len());
+                let field = |name: &str| field(&style_format, &fields, name);
+                let Some(name) = field("name") else {
+                    continue;
+                };
+                let style = CueStyle {
+                    color: field("primarycolour").and_then(parse_color),
+                    placement: field("alignment")
+                        .and_then(|value| value.parse().ok())
+                        .map(alignment_placement)
+                        .unwrap_or_default(),
+                };
+                styles.insert(name.to_string(), style);
+            }
+            "Dialogue" if is_events => {
+                let fields = split_fields(value, event_format.len());
+                let field = |name: &str| field(&event_format, &fields, name);
+                let (Some(start), Some(end), Some(text)) = (
+                    field("start").and_then(parse_timestamp),
+                    field("end").and_then(parse_timestamp),
+                    field("text"),
+                ) else {
+                    continue;
+                };
+                let mut style = field("style")
+                    .and_then(|name| styles.get(name.trim_start_matches('*')))
+                    .copied()
+                    .unwrap_or_default();
+                let text = apply_overrides(text, &mut style)
+                    .replace("\\N", "\n")
+                    .replace("\\n", "\n")
+                    .replace("\\h", " ");
+                cues.push(Cue {
+                    start,
+                    end,
+                    text,
+                    style,
+                });
+            }
+            _ => {}
+        }
+    }
+    cues
+}
+
+/// Strips `{...}` override blocks, applying the ones a cell grid can show.
+pub(super) fn apply_overrides(text: &str, style: &mut CueStyle) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            out.push_str(&rest[open..]);
+            return out;
+        };
+        for tag in rest[open + 1..open + close].split('\\').map(str::trim) {
+            if let Some(alignment) = tag.strip_prefix("an") {
+                if let Ok(alignment) = alignment.parse() {
+                    style.placement = alignment_placement(alignment);
+                }
+            } else if let Some(color) = tag.strip_prefix("1c").or_else(|| tag.strip_prefix('c')) {
+                if let Some(color) = parse_color(color) {
+                    style.color = Some(color);
+                }
+            }
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Numpad-style alignment: 7, 8 and 9 sit at the top.
+fn alignment_placement(alignment: u8) -> CuePlacement {
+    if (7..=9).contains(&alignment) {
+        CuePlacement::Top
+    } else {
+        CuePlacement::Bottom
+    }
+}
+
+/// `&HAABBGGRR`, `&HBBGGRR&` or a decimal SSA colour.
+fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    let value = value.trim().trim_end_matches('&');
+    let bgr = match value
+        .strip_prefix("&H")
+        .or_else(|| value.strip_prefix("&h"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => value.parse::<i64>().ok()? as u32,
+    };
+    Some((bgr as u8, (bgr >> 8) as u8, (bgr >> 16) as u8))
+}
+
+fn field_names(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect()
+}
+
+/// Splits on commas into at most `count` fields; the last one (the text)
+/// keeps its commas.
+fn split_fields(value: &str, count: usize) -> Vec<&str> {
+    value
+        .trim_start()
+        .splitn(count.max(1), ',')
+        .map(str::trim)
+        .collect()
+}
+
+fn field<'a>(format: &[String], fields: &[&'a str], name: &str) -> Option<&'a str> {
+    let index = format.iter().position(|field| field == name)?;
+    fields.get(index).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const SCRIPT: &str = "[Script Info]\nTitle: demo\n\n\
+        [V4+ Styles]\n\
+        Format: Name, Fontname, Fontsize, PrimaryColour, Alignment\n\
+        Style: Default,Arial,20,&H00FFFFFF,2\n\
+        Style: Sign,Arial,20,&H0000FFFF,8\n\n\
+        [Events]\n\
+        Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+        Comment: 0,0:00:00.00,0:00:09.00,Default,,0,0,0,,hidden\n\
+        Dialogue: 0,0:00:01.50,0:00:03.00,Default,,0,0,0,,{\\i1}Hello, there\\Nfriend\n\
+        Dialogue: 0,0:00:02.00,0:00:04.00,Sign,,0,0,0,,출구\n\
+        Dialogue: 0,0:00:05.00,0:00:06.00,Default,,0,0,0,,{\\an8\\c&H0000FF&}Red\\htop\n";
+
+    #[test]
+    fn dialogue_uses_styles_and_overrides() {
+        let cues = parse(SCRIPT);
+
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[0].start, Duration::from_millis(1_500));
+        assert_eq!(cues[0].text, "Hello, there\nfriend");
+        assert_eq!(cues[0].style.color, Some((255, 255, 255)));
+        assert_eq!(cues[0].style.placement, CuePlacement::Bottom);
+
+        assert_eq!(cues[1].text, "출구");
+        assert_eq!(cues[1].style.color, Some((255, 255, 0)));
+        assert_eq!(cues[1].style.placement, CuePlacement::Top);
+
+        assert_eq!(cues[2].text, "Red top");
+        assert_eq!(cues[2].style.color, Some((255, 0, 0)));
+        assert_eq!(cues[2].style.placement, CuePlacement::Top);
+    }
+}
@@ -0,0 +1,461 @@
+//! Synced lyrics from `.lrc` files
+//!
+//! Plain `[mm:ss.xx]` line tags and the enhanced `<mm:ss.xx>` word tags are
+//! both understood. Word timing drives a karaoke sweep through the line;
+//! without it the whole line lights up when it starts.
+
+use super::{parse_timestamp, wrap};
+use crate::renderer::cell::text_width;
+use crate::renderer::CellOverlay;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub(crate) const LYRIC_SUNG: (u8, u8, u8) = (255, 200, 60);
+pub(crate) const LYRIC_UNSUNG: (u8, u8, u8) = (235, 235, 235);
+pub(crate) const LYRIC_DIM: (u8, u8, u8) = (110, 110, 110);
+pub(crate) const LYRIC_BG: (u8, u8, u8) = (0, 0, 0);
+/// How long the last word of a line sweeps when nothing marks its end
+const LAST_WORD_SWEEP: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LyricWord {
+    start: Duration,
+    /// Non-whitespace characters in the word
+    glyphs: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricLine {
+    pub start: Duration,
+    pub text: String,
+    words: Vec<LyricWord>,
+    /// When the last word finishes, if an end tag gave it
+    words_end: Option<Duration>,
+}
+
+impl LyricLine {
+    /// Non-whitespace characters already sung at `time`.
+    pub fn sung_glyphs(&self, time: Duration) -> usize {
+        if time < self.start {
+            return 0;
+        }
+        if self.words.is_empty() {
+            return glyph_count(&self.text);
+        }
+
+        let mut sung = 0;
+        for (index, word) in self.words.iter().enumerate() {
+            if time < word.start {
+                break;
+            }
+            let end = self
+                .words
+                .get(index + 1)
+                .map(|next| next.start)
+                .or(self.words_end)
+                .unwrap_or(word.start + LAST_WORD_SWEEP);
+            if time >= end || end <= word.start {
+                sung += word.glyphs;
+                continue;
+            }
+            let progress = (time - word.start).as_secs_f64() / (end - word.start).as_secs_f64();
+            sung += (word.glyphs as f64 * progress).floor() as usize;
+            break;
+        }
+        sung
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lyrics {
+    /// Sorted by start; lines with empty text mark instrumental breaks
+    lines: Vec<LyricLine>,
+}
+
+impl Lyrics {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read lyrics {}", path.display()))?;
+        Ok(Self::parse(
+            String::from_utf8_lossy(&bytes).trim_start_matches('\u{feff}'),
+        ))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut offset_ms = 0i64;
+        let mut lines = Vec::new();
+
+        for raw in text.lines().map(str::trim) {
+            let mut rest = raw;
+            let mut starts = Vec::new();
+            while let Some(tag_end) = rest.strip_prefix('[').and_then(|tag| tag.find(']')) {
+                let tag = &rest[1..=tag_end];
+                rest = &rest[tag_end + 2..];
+                if let Some(start) = parse_timestamp(tag) {
+                    starts.push(start);
+                } else if let Some(value) = tag.strip_prefix("offset:") {
+                    offset_ms = value.trim().parse().unwrap_or(0);
+                }
+            }
+            if starts.is_empty() {
+                continue;
+            }
+
+            let (text, words, words_end) = parse_words(rest.trim());
+            let written_at = starts.iter().copied().min().unwrap_or_default();
+            for start in starts {
+                // Word tags are absolute, so a line repeated under several
+                // tags moves its words along with it.
+                let shift = |time: Duration| time.saturating_sub(written_at) + start;
+                lines.push(LyricLine {
+                    start,
+                    text: text.clone(),
+                    words: words
+                        .iter()
+                        .map(|word| LyricWord {
+                            start: shift(word.start),
+                            glyphs: word.glyphs,
+                        })
+                        .collect(),
+                    words_end: words_end.map(shift),
+                });
+            }
+        }
+
+        // A positive offset shows lyrics sooner.
+        for line in &mut lines {
+            let adjust = |time: Duration| {
+                let millis = time.as_millis() as i64 - offset_ms;
+                Duration::from_millis(millis.max(0) as u64)
+            };
+            line.start = adjust(line.start);
+            for word in &mut line.words {
+                word.start = adjust(word.start);
+            }
+            line.words_end = line.words_end.map(adjust);
+        }
+        lines.sort_by_key(|line| line.start);
+        Self { lines }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| !line.text.is_empty())
+            .count()
+    }
+
+    /// Index of the line being sung at `time`; `None` before the first
+    /// line and during instrumental breaks.
+    pub fn current(&self, time: Duration) -> Option<usize> {
+        let started = self.lines.partition_point(|line| line.start <= time);
+        let index = started.checked_sub(1)?;
+        (!self.lines[index].text.is_empty()).then_some(index)
+    }
+
+    /// The line to feature at `time`: the current one, or the next to come.
+    fn focus(&self, time: Duration) -> Option<usize> {
+        self.current(time).or_else(|| {
+            let started = self.lines.partition_point(|line| line.start <= time);
+            (started..self.lines.len()).find(|index| !self.lines[*index].text.is_empty())
+        })
+    }
+}
+
+/// `video.lrc` next to the audio track, falling back to one next to the
+/// video.
+pub fn find_lyrics(audio_path: Option<&Path>, video_path: &Path) -> Option<PathBuf> {
+    audio_path
+        .into_iter()
+        .chain(Some(video_path))
+        .filter(|path| path.extension().is_some())
+        .map(|path| path.with_extension("lrc"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// One styled run of a lyrics row.
+pub type LyricSpan = (String, (u8, u8, u8));
+
+/// Rows for a side panel `width` x `height`: the focused line karaoke-style,
+/// vertically centered, with dimmed neighbours above and below. Every row is
+/// padded to exactly `width` columns.
+pub fn panel_rows(
+    lyrics: &Lyrics,
+    time: Duration,
+    width: usize,
+    height: usize,
+) -> Vec<Vec<LyricSpan>> {
+    let mut rows: Vec<Vec<LyricSpan>> = vec![Vec::new(); height];
+    let wrap_width = width.saturating_sub(2).max(1);
+    if let Some(focus) = lyrics.focus(time) {
+        let line = &lyrics.lines[focus];
+        let sung = if lyrics.current(time) == Some(focus) {
+            line.sung_glyphs(time)
+        } else {
+            0
+        };
+        let block = karaoke_rows(&line.text, sung, wrap_width);
+        let top = (height / 2).saturating_sub(block.len() / 2);
+        for (row, spans) in (top..height).zip(block) {
+            rows[row] = spans;
+        }
+
+        let mut row = top;
+        for line in lyrics.lines[..focus]
+            .iter()
+            .rev()
+            .filter(|line| !line.text.is_empty())
+        {
+            let wrapped = wrap(&line.text, wrap_width);
+            if wrapped.len() + 1 > row {
+                break;
+            }
+            row -= wrapped.len() + 1;
+            for (offset, text) in wrapped.into_iter().enumerate() {
+                rows[row + offset] = vec![(text, LYRIC_DIM)];
+            }
+        }
+
+        let mut row = top + rows_used(&line.text, wrap_width) + 1;
+        for line in lyrics.lines[focus + 1..]
+            .iter()
+            .filter(|line| !line.text.is_empty())
+        {
+            let wrapped = wrap(&line.text, wrap_width);
+            if row + wrapped.len() > height {
+                break;
+            }
+            for text in wrapped {
+                rows[row] = vec![(text, LYRIC_DIM)];
+                row += 1;
+            }
+            row += 1;
+        }
+    }
+
+    rows.into_iter()
+        .map(|spans| pad_row(spans, width))
+        .collect()
+}
+
+/// Karaoke line centered over the bottom of the video, with the next line
+/// dimmed below it when there is room.
+pub fn draw_karaoke(
+    overlay: &mut CellOverlay,
+    lyrics: &Lyrics,
+    time: Duration,
+    width: usize,
+    height: usize,
+) {
+    let Some(current) = lyrics.current(time) else {
+        return;
+    };
+    let line = &lyrics.lines[current];
+    let wrap_width = width.saturating_sub(2).max(1);
+    let mut block = karaoke_rows(&line.text, line.sung_glyphs(time), wrap_width);
+    if height >= 6 {
+        if let Some(next) = lyrics.lines[current + 1..]
+            .iter()
+            .find(|line| !line.text.is_empty())
+        {
+            block.extend(
+                wrap(&next.text, wrap_width)
+                    .into_iter()
+                    .take(1)
+                    .map(|text| vec![(text, LYRIC_DIM)]),
+            );
+        }
+    }
+
+    let margin = usize::from(height >= 3);
+    let top = (height - margin.min(height)).saturating_sub(block.len());
+    for (row, spans) in (top..).zip(block) {
+        let row_width: usize = spans
+            .iter()
+            .map(|(text, _)| text_width(text))
+            .sum::<usize>()
+            + 2;
+        let mut x = width.saturating_sub(row_width) / 2;
+        x += overlay.put_text(x, row, " ", LYRIC_UNSUNG, LYRIC_BG);
+        for (text, fg) in spans {
+            x += overlay.put_text(x, row, &text, fg, LYRIC_BG);
+        }
+        overlay.put_text(x, row, " ", LYRIC_UNSUNG, LYRIC_BG);
+    }
+}
+
+/// Wraps `text` and splits each row where the first `sung` glyphs end.
+fn karaoke_rows(text: &str, sung: usize, width: usize) -> Vec<Vec<LyricSpan>> {
+    let mut remaining = sung;
+    wrap(text, width)
+        .into_iter()
+        .map(|row| {
+            let split = row
+                .char_indices()
+                .filter(|(_, c)| !c.is_whitespace())
+                .nth(remaining)
+                .map(|(index, _)| index)
+                .unwrap_or(row.len());
+            remaining = remaining.saturating_sub(glyph_count(&row));
+            let (done, todo) = row.split_at(split);
+            [(done, LYRIC_SUNG), (todo, LYRIC_UNSUNG)]
+                .into_iter()
+                .filter(|(text, _)| !text.is_empty())
+                .map(|(text, fg)| (text.to_string(), fg))
+                .collect()
+        })
+        .collect()
+}
+
+fn rows_used(text: &str, width: usize) -> usize {
+    wrap(text, width).len()
+}
+
+/// Centers `spans` in `width` columns, padding with spaces.
+fn pad_row(spans: Vec<LyricSpan>, width: usize) -> Vec<LyricSpan> {
+    let used: usize = spans.iter().map(|(text, _)| text_width(text)).sum();
+    let left = width.saturating_sub(used) / 2;
+    let right = width.saturating_sub(used + left);
+    let mut row = Vec::with_capacity(spans.len() + 2);
+    row.push((" ".repeat(left), LYRIC_UNSUNG));
+    row.extend(spans);
+    row.push((" ".repeat(right), LYRIC_UNSUNG));
+    row.retain(|(text, _)| !text.is_empty());
+    row
+}
+
+fn glyph_count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// Splits enhanced `<mm:ss.xx>word` markup into plain text and word starts;
+/// a trailing tag with no text marks when the last word ends.
+fn parse_words(text: &str) -> (String, Vec<LyricWord>, Option<Duration>) {
+    let mut plain = String::new();
+    let mut words: Vec<LyricWord> = Vec::new();
+    let mut words_end = None;
+    let mut rest = text;
+
+    loop {
+        let next_tag = rest
+            .find('<')
+            .filter(|open| rest[*open..].find('>').is_some());
+        let (segment, tag) = match next_tag {
+            Some(open) => {
+                let close = open + rest[open..].find('>').unwrap_or(0);
+                let tag = parse_timestamp(&rest[open + 1..close]);
+                let segment = &rest[..open];
+                rest = &rest[close + 1..];
+                (segment, Some(tag))
+            }
+            None => (std::mem::take(&mut rest), None),
+        };
+
+        plain.push_str(segment);
+        if let Some(word) = words.last_mut() {
+            word.glyphs += glyph_count(segment);
+        }
+        match tag {
+            Some(Some(start)) => {
+                words_end = Some(start);
+                words.push(LyricWord { start, glyphs: 0 });
+            }
+            Some(None) => {}
+            None => break,
+        }
+    }
+
+    // The last tag is an end marker unless text followed it.
+    if words.last().is_some_and(|word| word.glyphs == 0) {
+        words.pop();
+    } else {
+        words_end = None;
+    }
+    (plain.trim().to_string(), words, words_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn line_tags_offsets_and_breaks_are_parsed() {
+        let lyrics = Lyrics::parse(
+            "[ti:Bad Apple]\n[offset:500]\n[00:10.00][00:30.00]chorus\n[00:20.00]verse\n[00:25.00]\n",
+        );
+
+        assert_eq!(lyrics.line_count(), 3);
+        let current = |millis| {
+            lyrics
+                .current(ms(millis))
+                .map(|index| lyrics.lines[index].text.as_str())
+        };
+        assert_eq!(current(9_000), None);
+        assert_eq!(current(9_500), Some("chorus"));
+        assert_eq!(current(19_600), Some("verse"));
+        assert_eq!(current(24_600), None);
+        assert_eq!(current(29_500), Some("chorus"));
+    }
+
+    #[test]
+    fn enhanced_word_tags_sweep_through_the_line() {
+        let lyrics = Lyrics::parse("[00:01.00]<00:01.00>Bad <00:02.00>Apple<00:03.00>\n");
+        let line = &lyrics.lines[0];
+
+        assert_eq!(line.text, "Bad Apple");
+        assert_eq!(line.sung_glyphs(ms(500)), 0);
+        assert_eq!(line.sung_glyphs(ms(1_400)), 1);
+        assert_eq!(line.sung_glyphs(ms(2_000)), 3);
+        assert_eq!(line.sung_glyphs(ms(2_500)), 5);
+        assert_eq!(line.sung_glyphs(ms(3_000)), 8);
+    }
+
+    #[test]
+    fn karaoke_rows_split_at_the_sung_glyph() {
+        let rows = karaoke_rows("나는 노래해요", 3, 6);
+
+        assert_eq!(
+            rows,
+            vec![
+                vec![("나는".to_string(), LYRIC_SUNG)],
+                vec![
+                    ("노".to_string(), LYRIC_SUNG),
+                    ("래해".to_string(), LYRIC_UNSUNG)
+                ],
+                vec![("요".to_string(), LYRIC_UNSUNG)],
+            ]
+        );
+    }
+
+    #[test]
+    fn panel_rows_center_the_focus_between_its_neighbours() {
+        let lyrics = Lyrics::parse("[00:01.00]one\n[00:02.00]two\n[00:03.00]three\n");
+        let rows = panel_rows(&lyrics, ms(2_500), 9, 5);
+
+        let text: Vec<String> = rows
+            .iter()
+            .map(|row| row.iter().map(|(text, _)| text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            text,
+            [
+                "   one   ",
+                "         ",
+                "   two   ",
+                "         ",
+                "  three  "
+            ]
+        );
+        assert!(rows[2]
+            .iter()
+            .any(|(text, fg)| text == "two" && *fg == LYRIC_SUNG));
+        assert!(rows
+            .iter()
+            .all(|row| row.iter().map(|(text, _)| text_width(text)).sum::<usize>() == 9));
+    }
+}
@@ -0,0 +1,412 @@
+//! Subtitle tracks drawn on top of the video
+//!
+//! SRT, WebVTT and ASS/SSA are parsed into one list of timed cues; styling
+//! is reduced to what a cell grid can show, a text color and whether the cue
+//! sits at the top or the bottom of the picture.
+
+mod ass;
+pub mod lyrics;
+mod srt;
+mod vtt;
+
+use crate::renderer::cell::{char_width, text_width};
+use crate::renderer::CellOverlay;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SUBTITLE_FG: (u8, u8, u8) = (240, 240, 240);
+pub(crate) const SUBTITLE_BG: (u8, u8, u8) = (0, 0, 0);
+const SIBLING_EXTENSIONS: [&str; 4] = ["srt", "vtt", "ass", "ssa"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CuePlacement {
+    #[default]
+    Bottom,
+    Top,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CueStyle {
+    pub color: Option<(u8, u8, u8)>,
+    pub placement: CuePlacement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    pub start: Duration,
+    pub end: Duration,
+    /// Plain text with markup removed; `\n` separates lines
+    pub text: String,
+    pub style: CueStyle,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleTrack {
+    /// Sorted by start time
+    cues: Vec<Cue>,
+}
+
+impl SubtitleTrack {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read subtitles {}", path.display()))?;
+        let text = String::from_utf8_lossy(&bytes);
+        let text = text.trim_start_matches('\u{feff}');
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let cues = match extension.as_str() {
+            "srt" => srt::parse(text),
+            "vtt" => vtt::parse(text),
+            "ass" | "ssa" => ass::parse(text),
+            _ => bail!(
+                "unsupported subtitle format {} (expected .srt, .vtt, .ass or .ssa)",
+                path.display()
+            ),
+        };
+        Ok(Self::from_cues(cues))
+    }
+
+    pub fn from_cues(mut cues: Vec<Cue>) -> Self {
+        cues.retain(|cue| cue.end > cue.start && !cue.text.trim().is_empty());
+        cues.sort_by_key(|cue| cue.start);
+        Self { cues }
+    }
+
+    pub fn cue_count(&self) -> usize {
+        self.cues.len()
+    }
+
+    /// Cues showing at `time`, in start order.
+    pub fn active(&self, time: Duration) -> Vec<&Cue> {
+        let started = self.cues.partition_point(|cue| cue.start <= time);
+        self.cues[..started]
+            .iter()
+            .filter(|cue| cue.end > time)
+            .collect()
+    }
+
+    /// Cues showing at `position` in the source once shifted by `delay`
+    /// seconds; a positive delay shows every cue later.
+    pub fn active_with_delay(&self, position: Duration, delay: f64) -> Vec<&Cue> {
+        match Duration::try_from_secs_f64(position.as_secs_f64() - delay) {
+            Ok(time) => self.active(time),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// `video.srt` (or `.vtt`, `.ass`, `.ssa`) next to `video.mp4`.
+pub fn find_sibling(video_path: &Path) -> Option<PathBuf> {
+    video_path.extension()?;
+    SIBLING_EXTENSIONS
+        .iter()
+        .map(|extension| video_path.with_extension(extension))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Draws `cues` into the overlay's grid: bottom cues stacked up from one row
+/// above the bottom edge, top cues down from one row below the top, each line
+/// centered on a dark box.
+pub fn draw_cues(overlay: &mut CellOverlay, cues: &[&Cue], width: usize, height: usize) {
+    let margin = usize::from(height >= 3);
+    let max_width = width.saturating_sub(2).max(1);
+
+    let mut bottom: Vec<(String, CueStyle)> = Vec::new();
+    let mut top: Vec<(String, CueStyle)> = Vec::new();
+    for cue in cues {
+        let lines = wrap(&cue.text, max_width)
+            .into_iter()
+            .map(|line| (line, cue.style));
+        match cue.style.placement {
+            CuePlacement::Bottom => bottom.extend(lines),
+            CuePlacement::Top => top.extend(lines),
+        }
+    }
+
+    let bottom_start = (height - margin.min(height)).saturating_sub(bottom.len());
+    for (row, (line, style)) in (bottom_start..).zip(&bottom) {
+        draw_line(overlay, row, line, *style, width);
+    }
+    for (row, (line, style)) in (margin..).zip(&top) {
+        draw_line(overlay, row, line, *style, width);
+    }
+}
+
+/// Lines of `cues` for a text region `width` columns wide: top-placed cues
+/// first, each line centered and padded to the full width, at most `rows`
+/// lines. Returns `(text, fg)` pairs drawn on `SUBTITLE_BG`.
+pub fn cue_rows(cues: &[&Cue], width: usize, rows: usize) -> Vec<(String, (u8, u8, u8))> {
+    let max_width = width.saturating_sub(2).max(1);
+    let mut ordered: Vec<&Cue> = cues.to_vec();
+    ordered.sort_by_key(|cue| cue.style.placement != CuePlacement::Top);
+    ordered
+        .iter()
+        .flat_map(|cue| {
+            wrap(&cue.text, max_width)
+                .into_iter()
+                .map(|line| (line, cue.style.color.unwrap_or(SUBTITLE_FG)))
+        })
+        .take(rows)
+        .map(|(line, fg)| {
+            let used = text_width(&line);
+            let left = width.saturating_sub(used) / 2;
+            let right = width.saturating_sub(used + left);
+            (
+                format!("{}{}{}", " ".repeat(left), line, " ".repeat(right)),
+                fg,
+            )
+        })
+        .collect()
+}
+
+fn draw_line(overlay: &mut CellOverlay, row: usize, line: &str, style: CueStyle, width: usize) {
+    let boxed = format!(" {} ", line);
+    let x = width.saturating_sub(text_width(&boxed)) / 2;
+    overlay.put_text(
+        x,
+        row,
+        &boxed,
+        style.color.unwrap_or(SUBTITLE_FG),
+        SUBTITLE_BG,
+    );
+}
+
+/// Word-wraps `text` to `max_width` columns; words wider than a line, such
+/// as unbroken CJK runs, are split between glyphs.
+fn wrap(text: &str, max_width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = text_width(word);
+            let gap = usize::from(!line.is_empty());
+            if line_width + gap + word_width <= max_width {
+                if gap == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                line_width += gap + word_width;
+                continue;
+            }
+            if word_width <= max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+                line_width = word_width;
+                continue;
+            }
+            let mut pending_gap = gap == 1;
+            for glyph in word.chars() {
+                let glyph_width = char_width(glyph);
+                if line_width + usize::from(pending_gap) + glyph_width > max_width
+                    && !line.is_empty()
+                {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                    pending_gap = false;
+                }
+                if pending_gap {
+                    line.push(' ');
+                    line_width += 1;
+                    pending_gap = false;
+                }
+                line.push(glyph);
+                line_width += glyph_width;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Parses `HH:MM:SS,mmm`, `MM:SS.mmm` and ASS-style `H:MM:SS.cc`.
+pub(crate) fn parse_timestamp(value: &str) -> Option<Duration> {
+    let value = value.trim().replace(',', ".");
+    let (clock, fraction) = value.split_once('.').unwrap_or((&value, ""));
+
+    let mut seconds = 0u64;
+    for part in clock.split(':') {
+        if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    if clock.split(':').count() > 3 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let millis = fraction
+        .chars()
+        .chain("000".chars())
+        .take(3)
+        .collect::<String>()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds) + Duration::from_millis(millis))
+}
+
+/// Splits a `start --> end [settings]` timing line.
+pub(crate) fn parse_timing(line: &str) -> Option<(Duration, Duration, &str)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_timestamp(start)?,
+        parse_timestamp(end)?,
+        settings.trim(),
+    ))
+}
+
+/// Removes `<...>` tags, picking up `<font color="#rrggbb">` on the way.
+pub(crate) fn strip_html_tags(text: &str, style: &mut CueStyle) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('<') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            out.push_str(&rest[open..]);
+            return out;
+        };
+        let tag = &rest[open + 1..open + close];
+        if let Some(color) = font_color(tag) {
+            style.color = Some(color);
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn font_color(tag: &str) -> Option<(u8, u8, u8)> {
+    let lower = tag.to_ascii_lowercase();
+    let value = lower
+        .strip_prefix("font")?
+        .split("color=")
+        .nth(1)?
+        .trim_matches(|c: char| c == '"' || c == '\'' || c.is_whitespace());
+    let hex = value.strip_prefix('#')?.get(..6)?;
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start_ms: u64, end_ms: u64, text: &str) -> Cue {
+        Cue {
+            start: Duration::from_millis(start_ms),
+            end: Duration::from_millis(end_ms),
+            text: text.to_string(),
+            style: CueStyle::default(),
+        }
+    }
+
+    #[test]
+    fn timestamps_accept_srt_vtt_and_ass_forms() {
+        assert_eq!(
+            parse_timestamp("01:02:03,456"),
+            Some(Duration::from_millis(3_723_456))
+        );
+        assert_eq!(
+            parse_timestamp("02:03.5"),
+            Some(Duration::from_millis(123_500))
+        );
+        assert_eq!(
+            parse_timestamp("0:00:01.25"),
+            Some(Duration::from_millis(1_250))
+        );
+        assert_eq!(parse_timestamp("1:x:00"), None);
+    }
+
+    #[test]
+    fn active_cues_respect_end_times_and_delay() {
+        let track = SubtitleTrack::from_cues(vec![
+            cue(2_000, 3_000, "second"),
+            cue(0, 1_000, "first"),
+            cue(500, 2_500, "overlap"),
+        ]);
+        let texts = |cues: Vec<&Cue>| cues.iter().map(|c| c.text.clone()).collect::<Vec<_>>();
+
+        assert_eq!(
+            texts(track.active(Duration::from_millis(700))),
+            ["first", "overlap"]
+        );
+        assert!(track.active(Duration::from_millis(3_000)).is_empty());
+        assert_eq!(
+            texts(track.active_with_delay(Duration::from_millis(3_200), 0.5)),
+            ["second"]
+        );
+        assert!(track
+            .active_with_delay(Duration::from_millis(100), 0.5)
+            .is_empty());
+        for delay in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(track
+                .active_with_delay(Duration::from_secs(2), delay)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn wrapping_counts_wide_glyphs_as_two_columns() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap("가나다라마", 6), ["가나다", "라마"]);
+        assert_eq!(wrap("hi 안녕하세요", 8), ["hi 안녕", "하세요"]);
+    }
+
+    #[test]
+    fn cues_are_centered_above_the_bottom_margin() {
+        let mut overlay = CellOverlay::new();
+        overlay.begin(12, 4);
+        let caption = cue(0, 1_000, "자막");
+        draw_cues(&mut overlay, &[&caption], 12, 4);
+
+        // " 자막 " is 6 columns wide, centered in 12, on the second-last row.
+        let cells: Vec<(usize, char)> = overlay
+            .sorted_cells()
+            .iter()
+            .map(|(index, cell)| (*index, cell.char))
+            .collect();
+        let row = 2 * 12;
+        assert_eq!(cells.first(), Some(&(row + 3, ' ')));
+        assert_eq!(cells[1], (row + 4, '자'));
+        assert_eq!(cells[3], (row + 6, '막'));
+        assert_eq!(cells.last(), Some(&(row + 8, ' ')));
+    }
+
+    #[test]
+    fn cue_rows_are_centered_full_width_lines() {
+        let mut top = cue(0, 1_000, "sign");
+        top.style.placement = CuePlacement::Top;
+        let bottom = cue(0, 1_000, "한 줄");
+
+        let rows = cue_rows(&[&bottom, &top], 10, 4);
+        let texts: Vec<&str> = rows.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(texts, ["   sign   ", "  한 줄   "]);
+        assert!(rows.iter().all(|(text, _)| text_width(text) == 10));
+        assert_eq!(cue_rows(&[&bottom, &top], 10, 1).len(), 1);
+    }
+
+    #[test]
+    fn siblings_are_found_by_extension() {
+        let dir = std::env::temp_dir().join(format!("gascii-subs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let video = dir.join("movie.mp4");
+        assert_eq!(find_sibling(&video), None);
+
+        std::fs::write(dir.join("movie.vtt"), "WEBVTT\n").unwrap();
+        assert_eq!(find_sibling(&video), Some(dir.join("movie.vtt")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use super::{parse_timing, strip_html_tags, Cue, CueStyle};
+
+/// Parses SubRip: numbered blocks of a timing line followed by text.
+///
+/// `<i>`/`<b>`-style tags are dropped, `<font color>` sets the cue color and
+/// the common `{\an8}` override moves a cue to the top.
+pub(super) fn parse(text: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let mut lines = text.lines().map(str::trim_end).peekable();
+
+    while let Some(line) = lines.next() {
+        let Some((start, end, _)) = parse_timing(line) else {
+            continue;
+        };
+        let mut style = CueStyle::default();
+        let mut body = Vec::new();
+        while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+            let line = super::ass::apply_overrides(line, &mut style);
+            body.push(strip_html_tags(&line, &mut style));
+        }
+        cues.push(Cue {
+            start,
+            end,
+            text: body.join("\n"),
+            style,
+        });
+    }
+    cues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subtitle::CuePlacement;
+    use std::time::Duration;
+
+    #[test]
+    fn blocks_become_cues_with_markup_removed() {
+        let cues = parse(
+            "1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello</i>\r\nworld\r\n\r\n\
+             2\n00:00:03,000 --> 00:00:04,000\n{\\an8}<font color=\"#ff8000\">위</font>\n",
+        );
+
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start, Duration::from_millis(1_000));
+        assert_eq!(cues[0].end, Duration::from_millis(2_500));
+        assert_eq!(cues[0].text, "Hello\nworld");
+        assert_eq!(cues[1].text, "위");
+        assert_eq!(cues[1].style.color, Some((255, 128, 0)));
+        assert_eq!(cues[1].style.placement, CuePlacement::Top);
+    }
+}
@@ -0,0 +1,80 @@
+use super::{decode_entities, parse_timing, strip_html_tags, Cue, CuePlacement, CueStyle};
+
+/// Parses WebVTT, skipping the header and `NOTE`, `STYLE` and `REGION`
+/// blocks.
+///
+/// Cue settings only matter for placement: `line:` near the top moves the
+/// cue up. Voice, class and karaoke timestamp tags are dropped.
+pub(super) fn parse(text: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let mut lines = text.lines().map(str::trim_end).peekable();
+
+    while let Some(line) = lines.next() {
+        let first_word = line.split_whitespace().next().unwrap_or("");
+        if matches!(first_word, "WEBVTT" | "NOTE" | "STYLE" | "REGION") {
+            while lines.next_if(|line| !line.trim().is_empty()).is_some() {}
+            continue;
+        }
+        let Some((start, end, settings)) = parse_timing(line) else {
+            continue;
+        };
+        let mut style = CueStyle {
+            placement: placement(settings),
+            ..CueStyle::default()
+        };
+        let mut body = Vec::new();
+        while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+            body.push(decode_entities(&strip_html_tags(line, &mut style)));
+        }
+        cues.push(Cue {
+            start,
+            end,
+            text: body.join("\n"),
+            style,
+        });
+    }
+    cues
+}
+
+fn placement(settings: &str) -> CuePlacement {
+    let line = settings
+        .split_whitespace()
+        .find_map(|setting| setting.strip_prefix("line:"))
+        .map(|value| value.split(',').next().unwrap_or(value));
+    let is_top = match line {
+        Some(value) => match value.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().is_ok_and(|percent| percent < 50.0),
+            None => value
+                .parse::<i32>()
+                .is_ok_and(|line| (0..3).contains(&line)),
+        },
+        None => false,
+    };
+    if is_top {
+        CuePlacement::Top
+    } else {
+        CuePlacement::Bottom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn notes_and_styles_are_skipped_and_settings_place_cues() {
+        let cues = parse(
+            "WEBVTT - demo\n\nNOTE this is\na comment\n\nSTYLE\n::cue { color: red }\n\n\
+             intro\n00:01.000 --> 00:02.000 align:center\n<v Ann>Hi &amp; <c.yellow>bye</c>\n\n\
+             00:03.000 --> 00:04.000 line:0\nTop\n",
+        );
+
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start, Duration::from_secs(1));
+        assert_eq!(cues[0].text, "Hi & bye");
+        assert_eq!(cues[0].style.placement, CuePlacement::Bottom);
+        assert_eq!(cues[1].text, "Top");
+        assert_eq!(cues[1].style.placement, CuePlacement::Top);
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Playback position before which frames are dropped on arrival
+///
+/// The player publishes it every loop; the decoder thread reads it to
+/// skip frames that would be dropped anyway before paying for colour
+/// conversion and resize.
+#[derive(Debug, Clone, Default)]
+pub struct DropDeadline {
+    micros: Arc<AtomicU64>,
+}
+
+impl DropDeadline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn publish(&self, deadline: Duration) {
+        let micros = u64::try_from(deadline.as_micros()).unwrap_or(u64::MAX);
+        self.micros.store(micros, Ordering::Relaxed);
+    }
+
+    /// Latest published deadline; zero until the player starts.
+    pub fn get(&self) -> Duration {
+        Duration::from_micros(self.micros.load(Ordering::Relaxed))
+    }
+
+    /// Whether a frame due at `timestamp` would arrive too late.
+    pub fn is_missed(&self, timestamp: Duration) -> bool {
+        timestamp < self.get()
+    }
+}
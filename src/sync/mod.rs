@@ -1,4 +1,8 @@
 #![allow(unused)]
 pub mod clock;
+pub mod deadline;
+pub mod pause;
 
 pub use clock::MasterClock;
+pub use deadline::DropDeadline;
+pub use pause::PauseGate;
@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Pause state shared between the player and the decoder thread
+///
+/// The player toggles it together with `MasterClock`; the decoder thread
+/// subtracts the paused time when pacing, so it stops running ahead while
+/// playback is frozen instead of filling the queue and discarding frames.
+#[derive(Debug, Clone, Default)]
+pub struct PauseGate {
+    state: Arc<Mutex<PauseState>>,
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused_at: Option<Instant>,
+    total: Duration,
+}
+
+impl PauseGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pause(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.paused_at.get_or_insert_with(Instant::now);
+        }
+    }
+
+    pub fn resume(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(paused_at) = state.paused_at.take() {
+                state.total += paused_at.elapsed();
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state
+            .lock()
+            .is_ok_and(|state| state.paused_at.is_some())
+    }
+
+    /// Time spent paused so far, including a pause still in progress.
+    pub fn paused_total(&self) -> Duration {
+        self.state.lock().map_or(Duration::ZERO, |state| {
+            state.total + state.paused_at.map_or(Duration::ZERO, |at| at.elapsed())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paused_time_accumulates_across_pauses() {
+        let gate = PauseGate::new();
+        assert!(!gate.is_paused());
+        assert_eq!(gate.paused_total(), Duration::ZERO);
+
+        gate.pause();
+        gate.pause();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(gate.is_paused());
+        let during = gate.paused_total();
+        assert!(during >= Duration::from_millis(20));
+
+        gate.resume();
+        let after = gate.paused_total();
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(!gate.is_paused());
+        assert!(after >= during);
+        assert_eq!(gate.paused_total(), after);
+    }
+}
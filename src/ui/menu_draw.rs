@@ -144,6 +144,9 @@ fn draw_confirm(frame: &mut Frame<'_>, area: Rect, app: &MenuApp) {
     let viewport = match app.selected_viewport_mode() {
         ViewportMode::Fullscreen => "전체 화면",
         ViewportMode::CinemaScope => "시네마스코프 (2.39:1)",
+        ViewportMode::Original => "원본 비율",
+        ViewportMode::Aspect(_) => "고정 비율",
+        ViewportMode::ZoomPan => "줌/팬",
     };
     let quality = match app.selected_quality() {
         RenderQuality::Full => "Full",
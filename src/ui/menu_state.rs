@@ -1,6 +1,7 @@
 use crate::core::player::{RenderQuality, ViewportMode};
 use crate::renderer::DisplayMode;
 use crate::shared::constants;
+use crate::utils::file_utils;
 use anyhow::{Context, Result};
 use crossterm::event::KeyCode;
 use std::{
@@ -278,7 +279,7 @@ fn scan_video_files() -> Result<Vec<PathBuf>> {
         .with_context(|| format!("failed to read {}", video_dir.display()))?
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
-        .filter(|path| has_allowed_extension(path, constants::VIDEO_EXTENSIONS))
+        .filter(|path| file_utils::has_allowed_extension(path, constants::VIDEO_EXTENSIONS))
         .collect();
 
     video_files.sort();
@@ -300,24 +301,13 @@ fn scan_audio_files() -> Result<Vec<PathBuf>> {
         .with_context(|| format!("failed to read {}", audio_dir.display()))?
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
-        .filter(|path| has_allowed_extension(path, constants::AUDIO_EXTENSIONS))
+        .filter(|path| file_utils::has_allowed_extension(path, constants::AUDIO_EXTENSIONS))
         .collect();
 
     audio_files.sort();
     Ok(audio_files)
 }
 
-fn has_allowed_extension(path: &Path, allowed: &[&str]) -> bool {
-    let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
-        return false;
-    };
-
-    let ext = ext.to_ascii_lowercase();
-    allowed
-        .iter()
-        .any(|allowed_ext| *allowed_ext == ext.as_str())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
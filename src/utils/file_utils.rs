@@ -24,3 +24,13 @@ pub fn list_files(dir: &str, extension: &str) -> Result<Vec<PathBuf>> {
 pub fn read_file(path: &Path) -> Result<Vec<u8>> {
     fs::read(path).with_context(|| format!("Failed to read file: {:?}", path))
 }
+
+/// Case-insensitive extension check against a list like `VIDEO_EXTENSIONS`.
+pub fn has_allowed_extension(path: &Path, allowed: &[&str]) -> bool {
+    let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+        return false;
+    };
+
+    let ext = ext.to_ascii_lowercase();
+    allowed.contains(&ext.as_str())
+}
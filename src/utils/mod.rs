@@ -4,5 +4,6 @@ pub mod logger;
 pub mod memory;
 pub mod platform;
 pub mod runtime;
+pub mod simd;
 pub mod terminal_control;
 pub mod time_utils;
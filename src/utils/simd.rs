@@ -0,0 +1,338 @@
+//! Vectorised pixel loops with a scalar fallback
+//!
+//! The instruction set is detected once at runtime; every path produces
+//! exactly the same bytes as the scalar one.
+
+use std::sync::OnceLock;
+
+/// Widest instruction set the pixel loops can use on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    Scalar,
+    /// SSSE3 shuffles plus SSE4.1 32-bit multiplies and packs
+    Sse41,
+    Avx2,
+    Neon,
+}
+
+pub fn level() -> SimdLevel {
+    static LEVEL: OnceLock<SimdLevel> = OnceLock::new();
+    *LEVEL.get_or_init(detect)
+}
+
+fn detect() -> SimdLevel {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("sse4.1") {
+            return SimdLevel::Avx2;
+        }
+        if is_x86_feature_detected!("sse4.1") && is_x86_feature_detected!("ssse3") {
+            return SimdLevel::Sse41;
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return SimdLevel::Neon;
+        }
+    }
+    SimdLevel::Scalar
+}
+
+/// Copies BGR pixels into `dst` as RGB.
+pub fn bgr_to_rgb(src: &[u8], dst: &mut [u8]) {
+    bgr_to_rgb_with(level(), src, dst);
+}
+
+/// BT.601 luma `(299 R + 587 G + 114 B) / 1000` of each RGB pixel.
+pub fn luma_bt601(rgb: &[u8], out: &mut [u8]) {
+    luma_bt601_with(level(), rgb, out);
+}
+
+pub(crate) fn bgr_to_rgb_with(level: SimdLevel, src: &[u8], dst: &mut [u8]) {
+    let len = src.len().min(dst.len()) / 3 * 3;
+    let (src, dst) = (&src[..len], &mut dst[..len]);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::bgr_to_rgb_avx2(src, dst) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Sse41 => unsafe { x86::bgr_to_rgb_ssse3(src, dst) },
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        SimdLevel::Neon => unsafe { neon::bgr_to_rgb(src, dst) },
+        _ => scalar::bgr_to_rgb(src, dst),
+    }
+}
+
+pub(crate) fn luma_bt601_with(level: SimdLevel, rgb: &[u8], out: &mut [u8]) {
+    let pixels = (rgb.len() / 3).min(out.len());
+    let (rgb, out) = (&rgb[..pixels * 3], &mut out[..pixels]);
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::luma_avx2(rgb, out) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Sse41 => unsafe { x86::luma_sse41(rgb, out) },
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        SimdLevel::Neon => unsafe { neon::luma(rgb, out) },
+        _ => scalar::luma(rgb, out),
+    }
+}
+
+mod scalar {
+    pub(super) fn bgr_to_rgb(src: &[u8], dst: &mut [u8]) {
+        for (src_pixel, dst_pixel) in src.chunks_exact(3).zip(dst.chunks_exact_mut(3)) {
+            dst_pixel[0] = src_pixel[2];
+            dst_pixel[1] = src_pixel[1];
+            dst_pixel[2] = src_pixel[0];
+        }
+    }
+
+    pub(super) fn luma(rgb: &[u8], out: &mut [u8]) {
+        for (pixel, value) in rgb.chunks_exact(3).zip(out.iter_mut()) {
+            *value = ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114)
+                / 1000) as u8;
+        }
+    }
+}
+
+// The weighted sum is at most 255 000, so it converts to f32 exactly and a
+// correctly rounded division truncates to the same quotient as integer
+// division: a fractional part is at least 0.001, far above f32 precision
+// near 255.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::scalar;
+    use std::arch::x86_64::*;
+
+    /// Byte order that swaps B and R in five pixels; byte 15 is rewritten
+    /// by the next step.
+    const SWAP_MASK: [i8; 16] = [2, 1, 0, 5, 4, 3, 8, 7, 6, 11, 10, 9, 14, 13, 12, 15];
+    /// Spreads one channel of four packed pixels into 32-bit lanes.
+    const R_MASK: [i8; 16] = [0, -1, -1, -1, 3, -1, -1, -1, 6, -1, -1, -1, 9, -1, -1, -1];
+    const G_MASK: [i8; 16] = [1, -1, -1, -1, 4, -1, -1, -1, 7, -1, -1, -1, 10, -1, -1, -1];
+    const B_MASK: [i8; 16] = [2, -1, -1, -1, 5, -1, -1, -1, 8, -1, -1, -1, 11, -1, -1, -1];
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn mask(bytes: &[i8; 16]) -> __m128i {
+        _mm_loadu_si128(bytes.as_ptr().cast())
+    }
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn bgr_to_rgb_ssse3(src: &[u8], dst: &mut [u8]) {
+        let swap = mask(&SWAP_MASK);
+        let mut i = 0;
+        while i + 16 <= src.len() {
+            let pixels = _mm_loadu_si128(src.as_ptr().add(i).cast());
+            _mm_storeu_si128(
+                dst.as_mut_ptr().add(i).cast(),
+                _mm_shuffle_epi8(pixels, swap),
+            );
+            i += 15;
+        }
+        scalar::bgr_to_rgb(&src[i..], &mut dst[i..]);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn bgr_to_rgb_avx2(src: &[u8], dst: &mut [u8]) {
+        let swap = _mm256_broadcastsi128_si256(mask(&SWAP_MASK));
+        let mut i = 0;
+        while i + 31 <= src.len() {
+            let low = _mm_loadu_si128(src.as_ptr().add(i).cast());
+            let high = _mm_loadu_si128(src.as_ptr().add(i + 15).cast());
+            let pixels = _mm256_inserti128_si256::<1>(_mm256_castsi128_si256(low), high);
+            let swapped = _mm256_shuffle_epi8(pixels, swap);
+            _mm_storeu_si128(
+                dst.as_mut_ptr().add(i).cast(),
+                _mm256_castsi256_si128(swapped),
+            );
+            _mm_storeu_si128(
+                dst.as_mut_ptr().add(i + 15).cast(),
+                _mm256_extracti128_si256::<1>(swapped),
+            );
+            i += 30;
+        }
+        bgr_to_rgb_ssse3(&src[i..], &mut dst[i..]);
+    }
+
+    #[target_feature(enable = "sse4.1,ssse3")]
+    pub(super) unsafe fn luma_sse41(rgb: &[u8], out: &mut [u8]) {
+        let (r_mask, g_mask, b_mask) = (mask(&R_MASK), mask(&G_MASK), mask(&B_MASK));
+        let divisor = _mm_set1_ps(1000.0);
+        let mut pixel = 0;
+        while pixel * 3 + 16 <= rgb.len() {
+            let bytes = _mm_loadu_si128(rgb.as_ptr().add(pixel * 3).cast());
+            let sum = _mm_add_epi32(
+                _mm_add_epi32(
+                    _mm_mullo_epi32(_mm_shuffle_epi8(bytes, r_mask), _mm_set1_epi32(299)),
+                    _mm_mullo_epi32(_mm_shuffle_epi8(bytes, g_mask), _mm_set1_epi32(587)),
+                ),
+                _mm_mullo_epi32(_mm_shuffle_epi8(bytes, b_mask), _mm_set1_epi32(114)),
+            );
+            let luma = _mm_cvttps_epi32(_mm_div_ps(_mm_cvtepi32_ps(sum), divisor));
+            let words = _mm_packus_epi32(luma, luma);
+            let packed = _mm_packus_epi16(words, words);
+            out[pixel..pixel + 4].copy_from_slice(&_mm_cvtsi128_si32(packed).to_le_bytes());
+            pixel += 4;
+        }
+        scalar::luma(&rgb[pixel * 3..], &mut out[pixel..]);
+    }
+
+    #[target_feature(enable = "avx2,sse4.1,ssse3")]
+    pub(super) unsafe fn luma_avx2(rgb: &[u8], out: &mut [u8]) {
+        let r_mask = _mm256_broadcastsi128_si256(mask(&R_MASK));
+        let g_mask = _mm256_broadcastsi128_si256(mask(&G_MASK));
+        let b_mask = _mm256_broadcastsi128_si256(mask(&B_MASK));
+        let divisor = _mm256_set1_ps(1000.0);
+        let mut pixel = 0;
+        while pixel * 3 + 28 <= rgb.len() {
+            let low = _mm_loadu_si128(rgb.as_ptr().add(pixel * 3).cast());
+            let high = _mm_loadu_si128(rgb.as_ptr().add(pixel * 3 + 12).cast());
+            let bytes = _mm256_inserti128_si256::<1>(_mm256_castsi128_si256(low), high);
+            let sum = _mm256_add_epi32(
+                _mm256_add_epi32(
+                    _mm256_mullo_epi32(_mm256_shuffle_epi8(bytes, r_mask), _mm256_set1_epi32(299)),
+                    _mm256_mullo_epi32(_mm256_shuffle_epi8(bytes, g_mask), _mm256_set1_epi32(587)),
+                ),
+                _mm256_mullo_epi32(_mm256_shuffle_epi8(bytes, b_mask), _mm256_set1_epi32(114)),
+            );
+            let luma = _mm256_cvttps_epi32(_mm256_div_ps(_mm256_cvtepi32_ps(sum), divisor));
+            let words = _mm256_packus_epi32(luma, luma);
+            let packed = _mm256_packus_epi16(words, words);
+            let first = _mm_cvtsi128_si32(_mm256_castsi256_si128(packed));
+            let second = _mm_cvtsi128_si32(_mm256_extracti128_si256::<1>(packed));
+            out[pixel..pixel + 4].copy_from_slice(&first.to_le_bytes());
+            out[pixel + 4..pixel + 8].copy_from_slice(&second.to_le_bytes());
+            pixel += 8;
+        }
+        luma_sse41(&rgb[pixel * 3..], &mut out[pixel..]);
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+mod neon {
+    use super::scalar;
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn bgr_to_rgb(src: &[u8], dst: &mut [u8]) {
+        let mut i = 0;
+        while i + 48 <= src.len() {
+            let pixels = vld3q_u8(src.as_ptr().add(i));
+            vst3q_u8(
+                dst.as_mut_ptr().add(i),
+                uint8x16x3_t(pixels.2, pixels.1, pixels.0),
+            );
+            i += 48;
+        }
+        scalar::bgr_to_rgb(&src[i..], &mut dst[i..]);
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn luma_quarter(r: uint16x4_t, g: uint16x4_t, b: uint16x4_t) -> uint16x4_t {
+        let sum = vmulq_n_u32(vmovl_u16(r), 299);
+        let sum = vmlaq_n_u32(sum, vmovl_u16(g), 587);
+        let sum = vmlaq_n_u32(sum, vmovl_u16(b), 114);
+        let luma = vcvtq_u32_f32(vdivq_f32(vcvtq_f32_u32(sum), vdupq_n_f32(1000.0)));
+        vmovn_u32(luma)
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn luma_half(r: uint8x8_t, g: uint8x8_t, b: uint8x8_t) -> uint8x8_t {
+        let (r, g, b) = (vmovl_u8(r), vmovl_u8(g), vmovl_u8(b));
+        let low = luma_quarter(vget_low_u16(r), vget_low_u16(g), vget_low_u16(b));
+        let high = luma_quarter(vget_high_u16(r), vget_high_u16(g), vget_high_u16(b));
+        vmovn_u16(vcombine_u16(low, high))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn luma(rgb: &[u8], out: &mut [u8]) {
+        let mut pixel = 0;
+        while pixel * 3 + 48 <= rgb.len() {
+            let pixels = vld3q_u8(rgb.as_ptr().add(pixel * 3));
+            let low = luma_half(
+                vget_low_u8(pixels.0),
+                vget_low_u8(pixels.1),
+                vget_low_u8(pixels.2),
+            );
+            let high = luma_half(
+                vget_high_u8(pixels.0),
+                vget_high_u8(pixels.1),
+                vget_high_u8(pixels.2),
+            );
+            vst1q_u8(out.as_mut_ptr().add(pixel), vcombine_u8(low, high));
+            pixel += 16;
+        }
+        scalar::luma(&rgb[pixel * 3..], &mut out[pixel..]);
+    }
+}
+
+/// Levels this machine can run, scalar first.
+#[cfg(test)]
+pub(crate) fn available_levels() -> Vec<SimdLevel> {
+    let mut levels = vec![SimdLevel::Scalar];
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("sse4.1") && is_x86_feature_detected!("ssse3") {
+            levels.push(SimdLevel::Sse41);
+        }
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("sse4.1") {
+            levels.push(SimdLevel::Avx2);
+        }
+    }
+    if level() == SimdLevel::Neon {
+        levels.push(SimdLevel::Neon);
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudo-random bytes covering every value, lengths not a multiple of
+    /// any vector width.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swizzle_is_bit_exact_on_every_level() {
+        for pixels in [0, 1, 4, 5, 6, 10, 11, 16, 31, 97, 640] {
+            let src = noise(pixels * 3);
+            let mut expected = vec![0xAA; pixels * 3 + 7];
+            bgr_to_rgb_with(SimdLevel::Scalar, &src, &mut expected);
+            for level in available_levels() {
+                let mut dst = vec![0xAA; pixels * 3 + 7];
+                bgr_to_rgb_with(level, &src, &mut dst);
+                assert_eq!(dst, expected, "{:?} with {} pixels", level, pixels);
+            }
+        }
+    }
+
+    #[test]
+    fn luma_is_bit_exact_on_every_level() {
+        // Every grey level, the channel extremes and noise.
+        let mut rgb: Vec<u8> = (0..=255u8).flat_map(|value| [value; 3]).collect();
+        rgb.extend_from_slice(&[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]);
+        rgb.extend(noise(3 * 1001));
+        for pixels in [0, 3, 4, 7, 8, 9, 17, rgb.len() / 3] {
+            let mut expected = vec![0; pixels + 3];
+            luma_bt601_with(SimdLevel::Scalar, &rgb[..pixels * 3], &mut expected);
+            for level in available_levels() {
+                let mut out = vec![0; pixels + 3];
+                luma_bt601_with(level, &rgb[..pixels * 3], &mut out);
+                assert_eq!(out, expected, "{:?} with {} pixels", level, pixels);
+            }
+        }
+        let mut white = [0];
+        luma_bt601(&[255, 255, 255], &mut white);
+        assert_eq!(white, [255]);
+    }
+}